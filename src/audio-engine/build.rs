@@ -1,8 +1,8 @@
-/// Build script for audio-engine WebAssembly target
-/// 
-/// This script configures compiler settings for optimal WASM performance:
-/// - Target-specific features for WebAssembly
-/// - Optimization flags for small binary size and fast execution
+//! Build script for audio-engine WebAssembly target
+//! 
+//! This script configures compiler settings for optimal WASM performance:
+//! - Target-specific features for WebAssembly
+//! - Optimization flags for small binary size and fast execution
 
 fn main() {
     // Configure Rust flags for WASM optimization
@@ -123,7 +123,11 @@ mod examples {
     /// Example: Stereo crossfading
     #[test]
     fn example_crossfader() {
-        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        // Crossfading happens across decks, so it lives on the Mixer
+        let mut mixer = Mixer::new(48000, 1024, 2).unwrap();
+        mixer.set_crossfader_assign(0, CrossfaderAssign::A);
+        mixer.set_crossfader_assign(1, CrossfaderAssign::B);
+        mixer.set_crossfader_curve(FaderCurve::ConstantPower);
         
         let positions = vec![
             (-1.0, "Full Left"),
@@ -134,9 +138,9 @@ mod examples {
         ];
         
         for (position, label) in positions {
-            processor.set_fader_position(position);
-            assert_eq!(processor.get_fader_position(), position);
-            println!("Fader: {} ({})", position, label);
+            mixer.set_crossfader_position(position);
+            assert_eq!(mixer.get_crossfader_position(), position);
+            println!("Crossfader: {} ({})", position, label);
         }
     }
 
@@ -177,28 +181,28 @@ mod examples {
         let mut deck_1 = AudioProcessor::new(48000, 1024).unwrap();
         let mut deck_2 = AudioProcessor::new(48000, 1024).unwrap();
         
-        // Deck 1: Set to 120 BPM, normal pitch, 70% left
+        // Deck 1: Set to 120 BPM, normal pitch, panned 40% left
         deck_1.set_tempo_ratio(1.0);
         deck_1.set_pitch_shift(0);
-        deck_1.set_fader_position(-0.4);
+        deck_1.set_pan(-0.4);
         deck_1.set_master_volume(0.9);
         
-        // Deck 2: Set to 124 BPM, +3 semitones (camelot +1), 30% right
+        // Deck 2: Set to 124 BPM, +3 semitones (camelot +1), panned 40% right
         deck_2.set_tempo_ratio(124.0 / 120.0);
         deck_2.set_pitch_shift(3);
-        deck_2.set_fader_position(0.4);
+        deck_2.set_pan(0.4);
         deck_2.set_master_volume(0.85);
         
-        println!("Deck 1: Tempo={:.1}x, Pitch={} ST, Fader={:.1}",
+        println!("Deck 1: Tempo={:.1}x, Pitch={} ST, Pan={:.1}",
             deck_1.get_tempo_ratio(),
             deck_1.get_pitch_shift(),
-            deck_1.get_fader_position()
+            deck_1.get_pan()
         );
         
-        println!("Deck 2: Tempo={:.1}x, Pitch={} ST, Fader={:.1}",
+        println!("Deck 2: Tempo={:.1}x, Pitch={} ST, Pan={:.1}",
             deck_2.get_tempo_ratio(),
             deck_2.get_pitch_shift(),
-            deck_2.get_fader_position()
+            deck_2.get_pan()
         );
         
         // Simulate processing
@@ -239,16 +243,16 @@ mod examples {
         
         // Proper gain staging for DJing:
         // 1. Set input gain based on source level
-        // 2. Use fader for mixing
+        // 2. Mix with the Mixer's channel faders and crossfader
         // 3. Set master volume to prevent clipping
         
         // Source is quiet (-6dB)
         processor.set_input_gain(1.5); // Boost by ~3.5dB
         println!("Input Gain: {:.1} (source boost)", processor.get_input_gain());
         
-        // Use fader for main control
-        processor.set_fader_position(-0.3); // Mostly from this deck
-        println!("Fader Position: {:.1}", processor.get_fader_position());
+        // Keep the deck centred; levels between decks are set on the Mixer
+        processor.set_pan(0.0);
+        println!("Pan: {:.1}", processor.get_pan());
         
         // Set master to prevent clipping
         processor.set_master_volume(0.9); // Small headroom
@@ -335,7 +339,7 @@ fn main() {
     // Configure as a DJ deck
     processor.set_input_gain(1.0);
     processor.set_master_volume(1.0);
-    processor.set_fader_position(0.0);
+    processor.set_pan(0.0);
     
     // Main processing loop
     let chunk_size = 256;
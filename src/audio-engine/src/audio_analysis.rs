@@ -9,6 +9,46 @@ struct FrequencyBand {
     weight: f32,
 }
 
+/// Number of bands used for multi-band spectral flux
+pub(crate) const FLUX_BAND_COUNT: usize = 6;
+
+/// Frequency bands for multi-band analysis
+const FREQUENCY_BANDS: [FrequencyBand; FLUX_BAND_COUNT] = [
+    FrequencyBand {
+        low_freq: 20.0,
+        high_freq: 60.0,
+        weight: 0.8,
+    },
+    FrequencyBand {
+        low_freq: 60.0,
+        high_freq: 250.0,
+        weight: 1.5,
+    },
+    FrequencyBand {
+        low_freq: 250.0,
+        high_freq: 500.0,
+        weight: 1.2,
+    },
+    FrequencyBand {
+        low_freq: 500.0,
+        high_freq: 2000.0,
+        weight: 1.0,
+    },
+    FrequencyBand {
+        low_freq: 2000.0,
+        high_freq: 4000.0,
+        weight: 0.7,
+    },
+    FrequencyBand {
+        low_freq: 4000.0,
+        high_freq: 8000.0,
+        weight: 0.5,
+    },
+];
+
+/// Summed magnitude of each spectral flux band for one STFT frame
+pub(crate) type BandMagnitudes = [f32; FLUX_BAND_COUNT];
+
 /// Audio analysis module for BPM and key detection
 pub struct AudioAnalyzer {
     _sample_rate: u32,
@@ -20,42 +60,6 @@ impl AudioAnalyzer {
         Self { _sample_rate: sample_rate, _fft_size: fft_size }
     }
 
-    /// Define frequency bands for multi-band analysis
-    fn frequency_bands() -> Vec<FrequencyBand> {
-        vec![
-            FrequencyBand {
-                low_freq: 20.0,
-                high_freq: 60.0,
-                weight: 0.8,
-            },
-            FrequencyBand {
-                low_freq: 60.0,
-                high_freq: 250.0,
-                weight: 1.5,
-            },
-            FrequencyBand {
-                low_freq: 250.0,
-                high_freq: 500.0,
-                weight: 1.2,
-            },
-            FrequencyBand {
-                low_freq: 500.0,
-                high_freq: 2000.0,
-                weight: 1.0,
-            },
-            FrequencyBand {
-                low_freq: 2000.0,
-                high_freq: 4000.0,
-                weight: 0.7,
-            },
-            FrequencyBand {
-                low_freq: 4000.0,
-                high_freq: 8000.0,
-                weight: 0.5,
-            },
-        ]
-    }
-
     /// Detect BPM from audio samples using Multi-band Spectral Flux Analysis
     /// with multi-section consensus voting for improved accuracy and reliability
     /// Returns BPM as u32
@@ -67,7 +71,7 @@ impl AudioAnalyzer {
         // Analyze multiple sections of the track for consensus
         let section_duration = 10.0; // 10 seconds per section
         let section_samples = (sample_rate as f32 * section_duration) as usize;
-        let num_sections = (samples.len() / section_samples).min(5).max(1); // Analyze up to 5 sections
+        let num_sections = (samples.len() / section_samples).clamp(1, 5); // Analyze up to 5 sections
         
         let mut bpm_candidates = Vec::new();
 
@@ -103,9 +107,7 @@ impl AudioAnalyzer {
         }
 
         // Use consensus with octave correction
-        let final_bpm = Self::consensus_bpm(&bpm_candidates);
-
-        final_bpm
+        Self::consensus_bpm(&bpm_candidates)
     }
 
     /// Analyze a single section for BPM
@@ -133,9 +135,7 @@ impl AudioAnalyzer {
         }
 
         // Use autocorrelation to find periodic patterns
-        let bpm = Self::estimate_bpm_from_onsets(&onset_strength, sample_rate, hop_size);
-
-        bpm
+        Self::estimate_bpm_from_onsets(&onset_strength, sample_rate, hop_size)
     }
 
     /// Determine consensus BPM from multiple candidates using clustering and octave correction
@@ -203,15 +203,14 @@ impl AudioAnalyzer {
         hop_size: usize,
         num_frames: usize,
     ) -> Vec<f32> {
-        let bands = Self::frequency_bands();
         let mut onset_strength = vec![0.0; num_frames];
         let mut planner = FftPlanner::new();
         let fft = planner.plan_fft_forward(fft_size);
 
         // Previous frame magnitudes per band for flux calculation
-        let mut prev_band_magnitudes: Vec<Vec<f32>> = vec![Vec::new(); bands.len()];
+        let mut prev_band_magnitudes: Option<BandMagnitudes> = None;
 
-        for frame_idx in 0..num_frames {
+        for (frame_idx, strength) in onset_strength.iter_mut().enumerate() {
             let start = frame_idx * hop_size;
             let end = start + fft_size;
             
@@ -236,33 +235,14 @@ impl AudioAnalyzer {
                 .map(|c| (c.re * c.re + c.im * c.im).sqrt())
                 .collect();
 
-            // Calculate spectral flux for each frequency band
-            let mut frame_flux = 0.0;
-
-            for (band_idx, band) in bands.iter().enumerate() {
-                let bin_low = ((band.low_freq * fft_size as f32) / sample_rate as f32) as usize;
-                let bin_high = ((band.high_freq * fft_size as f32) / sample_rate as f32) as usize;
-                let bin_high = bin_high.min(magnitudes.len());
-
-                if bin_low >= bin_high {
-                    continue;
-                }
-
-                // Sum magnitudes in this band
-                let band_magnitude: f32 = magnitudes[bin_low..bin_high].iter().sum();
-
-                // Calculate spectral flux (positive difference only)
-                if frame_idx > 0 && !prev_band_magnitudes[band_idx].is_empty() {
-                    let prev_magnitude = prev_band_magnitudes[band_idx][0];
-                    let flux = (band_magnitude - prev_magnitude).max(0.0);
-                    frame_flux += flux * band.weight;
-                }
-
-                // Store current magnitude for next frame
-                prev_band_magnitudes[band_idx] = vec![band_magnitude];
+            // Calculate spectral flux across the frequency bands
+            let band_magnitudes = Self::band_magnitudes(&magnitudes, sample_rate, fft_size);
+            if let Some(prev) = &prev_band_magnitudes {
+                *strength = Self::band_spectral_flux(&band_magnitudes, prev);
             }
 
-            onset_strength[frame_idx] = frame_flux;
+            // Store current magnitudes for next frame
+            prev_band_magnitudes = Some(band_magnitudes);
         }
 
         // Normalize onset strength
@@ -271,6 +251,38 @@ impl AudioAnalyzer {
         onset_strength
     }
 
+    /// Sums a magnitude spectrum into the spectral flux frequency bands
+    ///
+    /// # Arguments
+    /// * `magnitudes` - Magnitudes of bins `0..fft_size / 2` (extra bins are ignored)
+    /// * `sample_rate` - Sample rate in Hz
+    /// * `fft_size` - FFT size the spectrum was computed with
+    pub(crate) fn band_magnitudes(magnitudes: &[f32], sample_rate: u32, fft_size: usize) -> BandMagnitudes {
+        let mut band_magnitudes = [0.0; FLUX_BAND_COUNT];
+        let usable_bins = magnitudes.len().min(fft_size / 2);
+
+        for (band_magnitude, band) in band_magnitudes.iter_mut().zip(FREQUENCY_BANDS.iter()) {
+            let bin_low = ((band.low_freq * fft_size as f32) / sample_rate as f32) as usize;
+            let bin_high = ((band.high_freq * fft_size as f32) / sample_rate as f32) as usize;
+            let bin_high = bin_high.min(usable_bins);
+
+            if bin_low < bin_high {
+                *band_magnitude = magnitudes[bin_low..bin_high].iter().sum();
+            }
+        }
+
+        band_magnitudes
+    }
+
+    /// Weighted spectral flux between two frames (positive differences only)
+    pub(crate) fn band_spectral_flux(current: &BandMagnitudes, previous: &BandMagnitudes) -> f32 {
+        FREQUENCY_BANDS
+            .iter()
+            .zip(current.iter().zip(previous.iter()))
+            .map(|(band, (&cur, &prev))| (cur - prev).max(0.0) * band.weight)
+            .sum()
+    }
+
     /// Normalize onset strength function
     fn normalize_onset_strength(onset_strength: &mut [f32]) {
         if onset_strength.is_empty() {
@@ -459,7 +471,7 @@ impl AudioAnalyzer {
         let mut bin_energy = vec![0.0; 128];
         let samples_per_bin = fft_size / 128;
 
-        for bin_idx in 0..128 {
+        for (bin_idx, bin) in bin_energy.iter_mut().enumerate() {
             let start = bin_idx * samples_per_bin;
             let end = (start + samples_per_bin).min(fft_size);
             let mut energy = 0.0;
@@ -468,15 +480,15 @@ impl AudioAnalyzer {
                 energy += sample * sample;
             }
 
-            bin_energy[bin_idx] = (energy / samples_per_bin as f32).sqrt();
+            *bin = (energy / samples_per_bin as f32).sqrt();
         }
 
         // Map frequency bins to chromatic notes
         // A4 = 440 Hz is MIDI note 69
-        for bin_idx in 0..bin_energy.len() {
+        for (bin_idx, &energy) in bin_energy.iter().enumerate() {
             let freq = bin_idx as f32 * sample_rate as f32 / fft_size as f32;
 
-            if freq < 80.0 || freq > 4000.0 {
+            if !(80.0..=4000.0).contains(&freq) {
                 continue;
             }
 
@@ -484,7 +496,7 @@ impl AudioAnalyzer {
             let midi_note = 12.0 * (freq / 440.0).log2() + 69.0;
             let chromatic_note = ((midi_note + 0.5) as usize) % 12;
 
-            chromatic_energy[chromatic_note] += bin_energy[bin_idx];
+            chromatic_energy[chromatic_note] += energy;
         }
 
         // Normalize
@@ -517,8 +529,8 @@ mod tests {
             let start = beat * samples_per_beat;
             let end = (start + 4410).min(samples.len()); // 0.1 second click
 
-            for i in start..end {
-                samples[i] = 0.5;
+            for sample in &mut samples[start..end] {
+                *sample = 0.5;
             }
         }
 
@@ -0,0 +1,457 @@
+//! Beat roll (loop roll / stutter)
+//!
+//! While held, repeats a 1/32 to 1 beat slice of the live signal, looping it
+//! on the beat grid. The slice starts on the most recent division boundary,
+//! not when the button was pressed, so rolls stay in time however sloppily
+//! they are triggered. Playback carries on underneath (like slip mode):
+//! releasing fades back to where the track would have been had the roll
+//! never happened.
+//!
+//! ```text
+//! beat grid   |-------|-------|-------|-------|   (1/2-beat roll)
+//! live        A1 A2 | B1 B2 | C1 C2 | D1 D2 |
+//! press          ^          release ^
+//! output      A1 A2 | A1 A2 | A1 A2 | D1 D2 |
+//! ```
+//!
+//! The last second and a half of input is always recorded, so a roll can
+//! reach back to its start on the grid. Changing the length mid-roll
+//! crossfades from the old slice into the new one.
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(1) per sample; engaging copies up to one slice
+//! - Memory: ~1.7MB at 48kHz (history and two slices, stereo)
+//! - Latency: none
+
+use crate::buffer_manager::BufferManager;
+use crate::smoothing::SmoothedParam;
+
+/// Shortest and longest roll in beats
+pub const MIN_BEATS: f32 = 1.0 / 32.0;
+pub const MAX_BEATS: f32 = 1.0;
+
+/// Slowest tempo, which sets the longest roll in seconds
+const MIN_BPM: f32 = 40.0;
+
+/// Fade at engage/release and across the loop seam
+const FADE_MS: f32 = 3.0;
+
+/// A captured slice and its playback position
+///
+/// The buffers hold the slice plus `fade` samples of what followed it, and
+/// are empty while checked in to the pool.
+struct Slice {
+    buffer: [Vec<f32>; 2],
+    len: usize,
+    captured: usize,
+    position: usize,
+    wrapped: bool,
+}
+
+impl Slice {
+    fn new() -> Self {
+        Slice {
+            buffer: [Vec::new(), Vec::new()],
+            len: 0,
+            captured: 0,
+            position: 0,
+            wrapped: false,
+        }
+    }
+
+    /// Captures a live frame if still needed and returns the rolled frame
+    #[inline]
+    fn tick(&mut self, input: [f32; 2], fade: usize) -> [f32; 2] {
+        // Keep capturing through the first pass and the seam fade
+        if self.captured < self.len + fade {
+            for (buffer, &sample) in self.buffer.iter_mut().zip(&input) {
+                buffer[self.captured] = sample;
+            }
+            self.captured += 1;
+        }
+
+        let seam = if self.wrapped && self.position < fade {
+            self.position as f32 / fade as f32
+        } else {
+            1.0
+        };
+        let mut output = [0.0; 2];
+        for (out, buffer) in output.iter_mut().zip(&self.buffer) {
+            *out = buffer[self.position];
+            if seam < 1.0 {
+                // Fade from what followed the slice into its start
+                let tail = buffer[self.len + self.position];
+                *out = tail + (*out - tail) * seam;
+            }
+        }
+
+        self.position += 1;
+        if self.position == self.len {
+            self.position = 0;
+            self.wrapped = true;
+        }
+        output
+    }
+}
+
+/// Momentary beat-synced loop roll
+///
+/// Starts idle at 120 BPM with a 1/4-beat roll.
+///
+/// # Performance
+/// - Per-sample complexity: O(1) (twice that during a length change)
+/// - Memory: ~1.7MB per instance at 48kHz
+pub struct BeatRoll {
+    sample_rate: u32,
+    bpm: f32,
+    beats: f32,
+
+    // Position in the current beat (0-1) and increment per sample
+    beat_phase: f32,
+    phase_step: f32,
+
+    // Pool of slice buffers, all sized for the longest roll
+    buffers: BufferManager,
+
+    // Always-recording history of the input
+    history: [Vec<f32>; 2],
+    write_pos: usize,
+
+    // The rolled slice, and the one it replaced while they crossfade
+    slice: Slice,
+    outgoing: Slice,
+    handoff: usize,
+    fade: usize,
+
+    active: bool,
+    held: bool,
+    wet: SmoothedParam,
+}
+
+impl BeatRoll {
+    /// Creates an idle beat roll
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn new(sample_rate: u32) -> Self {
+        let fade = (FADE_MS * sample_rate as f32 / 1000.0) as usize;
+        let capacity = (MAX_BEATS * 60.0 / MIN_BPM * sample_rate as f32) as usize + fade;
+
+        // The history is taken for good; the pool keeps the buffers of the
+        // two slices, checked out while a roll plays, so engaging and
+        // changing length never allocate
+        let mut buffers = BufferManager::new(capacity);
+        let history = [buffers.get_buffer(capacity), buffers.get_buffer(capacity)];
+        let slices: [Vec<f32>; 4] = std::array::from_fn(|_| buffers.get_buffer(capacity));
+        for buffer in slices {
+            buffers.return_buffer(buffer);
+        }
+
+        let mut roll = BeatRoll {
+            sample_rate,
+            bpm: 120.0,
+            beats: 0.25,
+            beat_phase: 0.0,
+            phase_step: 0.0,
+            buffers,
+            history,
+            write_pos: 0,
+            slice: Slice::new(),
+            outgoing: Slice::new(),
+            handoff: 0,
+            fade,
+            active: false,
+            held: false,
+            wet: SmoothedParam::new(0.0, sample_rate, FADE_MS),
+        };
+        roll.set_bpm(120.0);
+        roll
+    }
+
+    /// Sets the tempo the roll locks to
+    ///
+    /// # Arguments
+    /// * `bpm` - Beats per minute (40-300)
+    pub fn set_bpm(&mut self, bpm: f32) {
+        self.bpm = bpm.clamp(MIN_BPM, 300.0);
+        self.phase_step = self.bpm / 60.0 / self.sample_rate as f32;
+    }
+
+    /// Gets the tempo in BPM
+    pub fn get_bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Sets the roll length
+    ///
+    /// Snapped to the nearest power-of-two division. Changing it mid-roll
+    /// restarts the roll on the grid at the new length.
+    ///
+    /// # Arguments
+    /// * `beats` - Roll length in beats (1/32 to 1)
+    pub fn set_beats(&mut self, beats: f32) {
+        let beats = beats.clamp(MIN_BEATS, MAX_BEATS).log2().round().exp2();
+        if beats != self.beats {
+            self.beats = beats;
+            if self.held {
+                self.capture();
+            }
+        }
+    }
+
+    /// Gets the roll length in beats
+    pub fn get_beats(&self) -> f32 {
+        self.beats
+    }
+
+    /// Moves the beat clock to a point in the beat
+    ///
+    /// Call on the host's beat grid (e.g. 0.0 on each beat) to keep rolls
+    /// aligned with the track.
+    ///
+    /// # Arguments
+    /// * `phase` - Position in the beat (0.0 to 1.0; wraps)
+    pub fn set_beat_phase(&mut self, phase: f32) {
+        self.beat_phase = phase.rem_euclid(1.0);
+    }
+
+    /// Gets the position in the current beat (0.0 to 1.0)
+    pub fn get_beat_phase(&self) -> f32 {
+        self.beat_phase
+    }
+
+    /// Presses (true) or releases (false) the roll
+    pub fn set_active(&mut self, active: bool) {
+        if active == self.held {
+            return;
+        }
+        self.held = active;
+        if active {
+            // A press during the release fade keeps the loop already playing
+            if !self.active {
+                self.capture();
+            }
+            self.active = true;
+            self.wet.set_target(1.0);
+        } else {
+            self.wet.set_target(0.0);
+        }
+    }
+
+    /// Gets whether the roll is held
+    pub fn is_active(&self) -> bool {
+        self.held
+    }
+
+    /// Roll length in samples at the current tempo
+    pub fn loop_samples(&self) -> usize {
+        ((self.beats * 60.0 / self.bpm * self.sample_rate as f32).round() as usize).max(1)
+    }
+
+    /// Processes a stereo pair in place
+    ///
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let capacity = self.history[0].len();
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let input = [*l, *r];
+            for (history, &sample) in self.history.iter_mut().zip(&input) {
+                history[self.write_pos] = sample;
+            }
+            self.write_pos = (self.write_pos + 1) % capacity;
+
+            self.beat_phase += self.phase_step;
+            if self.beat_phase >= 1.0 {
+                self.beat_phase -= 1.0;
+            }
+
+            if !self.active {
+                continue;
+            }
+
+            let mut rolled = self.slice.tick(input, self.fade);
+            if self.handoff > 0 {
+                // Fade from where the old slice would have gone
+                let old = self.outgoing.tick(input, self.fade);
+                let t = 1.0 - self.handoff as f32 / self.fade as f32;
+                for (new, old) in rolled.iter_mut().zip(old) {
+                    *new = old + (*new - old) * t;
+                }
+                self.handoff -= 1;
+            }
+
+            let wet = self.wet.next_value();
+            for (sample, rolled) in [&mut *l, &mut *r].into_iter().zip(rolled) {
+                *sample += (rolled - *sample) * wet;
+            }
+
+            if !self.held && !self.wet.is_smoothing() {
+                self.active = false;
+                self.check_in_slices();
+            }
+        }
+    }
+
+    /// Stops any roll and clears the recorded audio
+    pub fn reset(&mut self) {
+        for buffer in self.history.iter_mut() {
+            buffer.fill(0.0);
+        }
+        self.write_pos = 0;
+        self.active = false;
+        self.held = false;
+        self.wet.set_immediate(0.0);
+        self.check_in_slices();
+    }
+
+    /// Starts the loop at the last division boundary
+    ///
+    /// The part of the slice already played is copied from the history; the
+    /// rest is captured live. A slice already playing keeps going for the
+    /// length of a fade, crossfading into the new one.
+    fn capture(&mut self) {
+        if self.active {
+            std::mem::swap(&mut self.slice, &mut self.outgoing);
+            self.handoff = self.fade;
+        }
+        let capacity = self.history[0].len();
+        let len = self.loop_samples();
+        let slice = &mut self.slice;
+        if slice.buffer[0].is_empty() {
+            slice.buffer = [self.buffers.get_buffer(capacity), self.buffers.get_buffer(capacity)];
+        }
+
+        slice.len = len;
+        let division = (self.beat_phase / self.beats).fract();
+        let elapsed = (division * slice.len as f32).round() as usize % slice.len;
+
+        let start = (self.write_pos + capacity - elapsed) % capacity;
+        for (buffer, history) in slice.buffer.iter_mut().zip(&self.history) {
+            for (i, sample) in buffer[..elapsed].iter_mut().enumerate() {
+                *sample = history[(start + i) % capacity];
+            }
+        }
+        slice.captured = elapsed;
+        slice.position = elapsed;
+        slice.wrapped = false;
+    }
+
+    /// Returns the slice buffers to the pool once no roll is playing
+    fn check_in_slices(&mut self) {
+        self.handoff = 0;
+        for slice in [&mut self.slice, &mut self.outgoing] {
+            for buffer in slice.buffer.iter_mut() {
+                if !buffer.is_empty() {
+                    self.buffers.return_buffer(std::mem::take(buffer));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds a ramp (sample value = sample index) through the roll
+    fn process_ramp(roll: &mut BeatRoll, start: usize, len: usize) -> Vec<f32> {
+        let mut left: Vec<f32> = (start..start + len).map(|i| i as f32).collect();
+        let mut right = left.clone();
+        roll.process_stereo(&mut left, &mut right);
+        left
+    }
+
+    #[test]
+    fn test_idle_passes_through() {
+        let mut roll = BeatRoll::new(48000);
+        let output = process_ramp(&mut roll, 0, 1000);
+        assert!(output.iter().enumerate().all(|(i, &s)| s == i as f32));
+
+        // Half a second is one beat at 120 BPM
+        process_ramp(&mut roll, 1000, 23000);
+        let phase = roll.get_beat_phase();
+        assert!(!(0.01..=0.99).contains(&phase), "{}", phase);
+    }
+
+    #[test]
+    fn test_roll_starts_on_grid() {
+        let mut roll = BeatRoll::new(48000);
+        roll.set_beats(0.125);
+        assert_eq!(roll.loop_samples(), 3000);
+
+        // Pressed halfway through the second 1/8-beat slice (samples 3000..6000)
+        process_ramp(&mut roll, 0, 4500);
+        roll.set_active(true);
+        let output = process_ramp(&mut roll, 4500, 6000);
+
+        // Live until the slice ends, then the slice again and again
+        assert_eq!(output[1000], 5500.0);
+        assert_eq!(output[1500 + 500], 3500.0);
+        assert_eq!(output[4500 + 500], 3500.0);
+        assert_eq!(output[1500 + 2999], 5999.0);
+    }
+
+    #[test]
+    fn test_release_returns_to_live_playback() {
+        let mut roll = BeatRoll::new(48000);
+        roll.set_beats(0.25);
+        roll.set_active(true);
+        process_ramp(&mut roll, 0, 20000);
+        assert!(roll.is_active());
+
+        roll.set_active(false);
+        let output = process_ramp(&mut roll, 20000, 2000);
+        // After the fade, the track is where it would have been
+        assert!(output[500..].iter().zip(20500..).all(|(&s, i)| s == i as f32));
+        assert!(!roll.is_active());
+    }
+
+    #[test]
+    fn test_length_change_crossfades() {
+        let mut roll = BeatRoll::new(48000);
+        assert_eq!(roll.buffers.pool_size(), 4);
+
+        // 440 Hz at 0.5 moves by at most 0.03 per sample
+        let tone = |i: usize| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 48000.0).sin() * 0.5;
+        let mut left: Vec<f32> = (0..48000).map(tone).collect();
+        let mut right = left.clone();
+        for (block, (l, r)) in left.chunks_mut(128).zip(right.chunks_mut(128)).enumerate() {
+            match block {
+                10 => roll.set_active(true),
+                // Mid-loop, well past the first pass
+                130 => roll.set_beats(0.125),
+                200 => roll.set_beats(0.5),
+                300 => roll.set_active(false),
+                _ => {}
+            }
+            roll.process_stereo(l, r);
+        }
+
+        let largest_step = left.windows(2).fold(0.0f32, |m, w| m.max((w[1] - w[0]).abs()));
+        assert!(largest_step < 0.05, "step {}", largest_step);
+
+        // Both slices went back to the pool
+        assert!(!roll.is_active());
+        assert_eq!(roll.buffers.pool_size(), 4);
+    }
+
+    #[test]
+    fn test_beats_snap_to_divisions() {
+        let mut roll = BeatRoll::new(48000);
+        roll.set_beats(0.3);
+        assert_eq!(roll.get_beats(), 0.25);
+        roll.set_beats(0.001);
+        assert_eq!(roll.get_beats(), MIN_BEATS);
+        roll.set_beats(3.0);
+        assert_eq!(roll.get_beats(), MAX_BEATS);
+
+        // A full beat at the slowest tempo fits the buffers
+        roll.set_bpm(10.0);
+        roll.set_active(true);
+        let output = process_ramp(&mut roll, 0, 200000);
+        assert!(output.iter().all(|s| s.is_finite()));
+    }
+}
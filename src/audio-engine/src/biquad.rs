@@ -0,0 +1,281 @@
+//! Second-order IIR (biquad) filters
+//!
+//! Coefficient formulas follow Robert Bristow-Johnson's "Cookbook formulae
+//! for audio EQ biquad filter coefficients". Filters run in transposed
+//! direct form II, which keeps two state variables per filter and behaves
+//! well with single-precision floats.
+//!
+//! # Performance
+//! - 5 multiplies and 4 adds per sample
+//! - Memory: 7 floats per filter
+
+use std::f32::consts::PI;
+
+use rustfft::num_complex::Complex;
+
+/// Normalized biquad coefficients (`a0` divided out)
+///
+/// Transfer function:
+/// ```text
+///         b0 + b1·z⁻¹ + b2·z⁻²
+/// H(z) = ----------------------
+///          1 + a1·z⁻¹ + a2·z⁻²
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BiquadCoefficients {
+    pub b0: f32,
+    pub b1: f32,
+    pub b2: f32,
+    pub a1: f32,
+    pub a2: f32,
+}
+
+/// Intermediate values shared by all RBJ formulas
+struct Rbj {
+    cos_w0: f32,
+    alpha: f32,
+}
+
+impl Rbj {
+    fn new(sample_rate: u32, freq: f32, q: f32) -> Self {
+        // Keep the centre frequency strictly inside (0, Nyquist)
+        let nyquist = sample_rate as f32 * 0.5;
+        let freq = freq.clamp(1.0, nyquist * 0.999);
+        let w0 = 2.0 * PI * freq / sample_rate as f32;
+        Rbj {
+            cos_w0: w0.cos(),
+            alpha: w0.sin() / (2.0 * q.max(0.01)),
+        }
+    }
+}
+
+impl BiquadCoefficients {
+    /// Pass-through filter (H(z) = 1)
+    pub const IDENTITY: BiquadCoefficients = BiquadCoefficients {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    /// Builds normalized coefficients from raw RBJ values
+    fn normalized(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        BiquadCoefficients {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    /// Second-order lowpass (-3 dB at `freq` when `q` = 0.707)
+    pub fn lowpass(sample_rate: u32, freq: f32, q: f32) -> Self {
+        let Rbj { cos_w0, alpha } = Rbj::new(sample_rate, freq, q);
+        let b1 = 1.0 - cos_w0;
+        Self::normalized(b1 * 0.5, b1, b1 * 0.5, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha)
+    }
+
+    /// Second-order highpass (-3 dB at `freq` when `q` = 0.707)
+    pub fn highpass(sample_rate: u32, freq: f32, q: f32) -> Self {
+        let Rbj { cos_w0, alpha } = Rbj::new(sample_rate, freq, q);
+        let b1 = -(1.0 + cos_w0);
+        Self::normalized(-b1 * 0.5, b1, -b1 * 0.5, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha)
+    }
+
+    /// Bandpass with 0 dB peak gain at `freq`
+    pub fn bandpass(sample_rate: u32, freq: f32, q: f32) -> Self {
+        let Rbj { cos_w0, alpha } = Rbj::new(sample_rate, freq, q);
+        Self::normalized(alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha)
+    }
+
+    /// Notch (band-reject) centred on `freq`
+    pub fn notch(sample_rate: u32, freq: f32, q: f32) -> Self {
+        let Rbj { cos_w0, alpha } = Rbj::new(sample_rate, freq, q);
+        Self::normalized(1.0, -2.0 * cos_w0, 1.0, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha)
+    }
+
+    /// Allpass with 180° phase shift at `freq`
+    pub fn allpass(sample_rate: u32, freq: f32, q: f32) -> Self {
+        let Rbj { cos_w0, alpha } = Rbj::new(sample_rate, freq, q);
+        Self::normalized(
+            1.0 - alpha,
+            -2.0 * cos_w0,
+            1.0 + alpha,
+            1.0 + alpha,
+            -2.0 * cos_w0,
+            1.0 - alpha,
+        )
+    }
+
+    /// Peaking EQ: `gain_db` at `freq`, 0 dB far away
+    pub fn peaking(sample_rate: u32, freq: f32, q: f32, gain_db: f32) -> Self {
+        let Rbj { cos_w0, alpha } = Rbj::new(sample_rate, freq, q);
+        let a = 10.0_f32.powf(gain_db / 40.0);
+        Self::normalized(
+            1.0 + alpha * a,
+            -2.0 * cos_w0,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos_w0,
+            1.0 - alpha / a,
+        )
+    }
+
+    /// Low shelf: `gain_db` below `freq`, 0 dB above (half the gain at `freq`)
+    pub fn low_shelf(sample_rate: u32, freq: f32, q: f32, gain_db: f32) -> Self {
+        let Rbj { cos_w0, alpha } = Rbj::new(sample_rate, freq, q);
+        let a = 10.0_f32.powf(gain_db / 40.0);
+        let beta = 2.0 * a.sqrt() * alpha;
+        Self::normalized(
+            a * ((a + 1.0) - (a - 1.0) * cos_w0 + beta),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+            a * ((a + 1.0) - (a - 1.0) * cos_w0 - beta),
+            (a + 1.0) + (a - 1.0) * cos_w0 + beta,
+            -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+            (a + 1.0) + (a - 1.0) * cos_w0 - beta,
+        )
+    }
+
+    /// High shelf: `gain_db` above `freq`, 0 dB below (half the gain at `freq`)
+    pub fn high_shelf(sample_rate: u32, freq: f32, q: f32, gain_db: f32) -> Self {
+        let Rbj { cos_w0, alpha } = Rbj::new(sample_rate, freq, q);
+        let a = 10.0_f32.powf(gain_db / 40.0);
+        let beta = 2.0 * a.sqrt() * alpha;
+        Self::normalized(
+            a * ((a + 1.0) + (a - 1.0) * cos_w0 + beta),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+            a * ((a + 1.0) + (a - 1.0) * cos_w0 - beta),
+            (a + 1.0) - (a - 1.0) * cos_w0 + beta,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+            (a + 1.0) - (a - 1.0) * cos_w0 - beta,
+        )
+    }
+
+    /// Complex frequency response H(e^jω) at `freq`
+    ///
+    /// Needed when filter outputs are summed (band splits), where phase
+    /// matters as much as magnitude.
+    pub fn response_at(&self, freq: f32, sample_rate: u32) -> Complex<f32> {
+        let w = 2.0 * PI * freq / sample_rate as f32;
+        let z1 = Complex::from_polar(1.0, -w);
+        let z2 = z1 * z1;
+
+        let numerator = self.b0 + z1 * self.b1 + z2 * self.b2;
+        let denominator = 1.0 + z1 * self.a1 + z2 * self.a2;
+        numerator / denominator
+    }
+
+    /// Linear magnitude response at `freq`
+    ///
+    /// Evaluates |H(e^jω)| directly, for drawing EQ curves and testing.
+    pub fn magnitude_at(&self, freq: f32, sample_rate: u32) -> f32 {
+        self.response_at(freq, sample_rate).norm()
+    }
+}
+
+/// Biquad filter with its own state
+#[derive(Clone, Debug)]
+pub struct Biquad {
+    coefficients: BiquadCoefficients,
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    /// Creates a filter with the given coefficients and cleared state
+    pub fn new(coefficients: BiquadCoefficients) -> Self {
+        Biquad {
+            coefficients,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    /// Replaces the coefficients, keeping the state (click-free for small changes)
+    pub fn set_coefficients(&mut self, coefficients: BiquadCoefficients) {
+        self.coefficients = coefficients;
+    }
+
+    /// Gets the current coefficients
+    pub fn coefficients(&self) -> &BiquadCoefficients {
+        &self.coefficients
+    }
+
+    /// Filters a single sample
+    #[inline]
+    pub fn process_sample(&mut self, input: f32) -> f32 {
+        let c = &self.coefficients;
+        let output = c.b0 * input + self.z1;
+        self.z1 = c.b1 * input - c.a1 * output + self.z2;
+        self.z2 = c.b2 * input - c.a2 * output;
+        output
+    }
+
+    /// Filters a buffer in place
+    pub fn process(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process_sample(*sample);
+        }
+    }
+
+    /// Clears the filter state
+    pub fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db(linear: f32) -> f32 {
+        20.0 * linear.log10()
+    }
+
+    #[test]
+    fn test_shelf_and_peak_gains() {
+        let low = BiquadCoefficients::low_shelf(48000, 250.0, 0.707, -12.0);
+        assert!((db(low.magnitude_at(10.0, 48000)) + 12.0).abs() < 0.1);
+        assert!((db(low.magnitude_at(250.0, 48000)) + 6.0).abs() < 0.1);
+        assert!(db(low.magnitude_at(10000.0, 48000)).abs() < 0.1);
+
+        let high = BiquadCoefficients::high_shelf(48000, 2000.0, 0.707, 6.0);
+        assert!((db(high.magnitude_at(20000.0, 48000)) - 6.0).abs() < 0.1);
+        assert!(db(high.magnitude_at(20.0, 48000)).abs() < 0.1);
+
+        let peak = BiquadCoefficients::peaking(48000, 1000.0, 1.0, 9.0);
+        assert!((db(peak.magnitude_at(1000.0, 48000)) - 9.0).abs() < 0.01);
+        assert!(db(peak.magnitude_at(20.0, 48000)).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_pass_filters() {
+        let lp = BiquadCoefficients::lowpass(48000, 1000.0, std::f32::consts::FRAC_1_SQRT_2);
+        assert!((db(lp.magnitude_at(1000.0, 48000)) + 3.01).abs() < 0.05);
+        assert!(db(lp.magnitude_at(10000.0, 48000)) < -35.0);
+
+        let hp = BiquadCoefficients::highpass(48000, 1000.0, std::f32::consts::FRAC_1_SQRT_2);
+        assert!((db(hp.magnitude_at(1000.0, 48000)) + 3.01).abs() < 0.05);
+        assert!(db(hp.magnitude_at(100.0, 48000)) < -35.0);
+
+        let ap = BiquadCoefficients::allpass(48000, 1000.0, 0.707);
+        assert!((ap.magnitude_at(300.0, 48000) - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_filter_matches_magnitude() {
+        let coefficients = BiquadCoefficients::peaking(48000, 1000.0, 2.0, -9.0);
+        let mut filter = Biquad::new(coefficients);
+
+        let mut buffer: Vec<f32> = (0..48000)
+            .map(|i| (2.0 * PI * 1000.0 * i as f32 / 48000.0).sin())
+            .collect();
+        filter.process(&mut buffer);
+
+        let peak = buffer[24000..].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((peak - coefficients.magnitude_at(1000.0, 48000)).abs() < 1e-3);
+    }
+}
@@ -110,18 +110,41 @@ pub mod sample_utils {
         }
     }
 
+    /// Hard clips audio to the -1.0 to 1.0 range
+    pub fn hard_clip(sample: f32) -> f32 {
+        sample.clamp(-1.0, 1.0)
+    }
+
     /// Soft clips audio to prevent harsh distortion
     /// 
-    /// Uses a tanh-based soft clipping curve for smooth saturation
+    /// Uses a tanh curve: nearly linear at low levels, rounding off smoothly
+    /// towards ±1.0. Symmetric, so it adds odd harmonics only.
     pub fn soft_clip(sample: f32) -> f32 {
-        // Tanh approximation: x / (1 + |x|)
-        // More accurate but still fast
-        let abs_sample = sample.abs();
-        if abs_sample < 1.0 {
-            sample
-        } else {
-            sample / abs_sample
-        }
+        sample.tanh()
+    }
+
+    /// Tube-style asymmetric saturation
+    /// 
+    /// A tanh curve biased off-centre, so positive peaks compress sooner
+    /// than negative ones and even harmonics are added as well as odd.
+    /// Silence stays silent, but loud signals gain a DC offset; follow it
+    /// with a DC blocker.
+    pub fn tube_clip(sample: f32) -> f32 {
+        const BIAS: f32 = 0.3;
+        (sample + BIAS).tanh() - BIAS.tanh()
+    }
+
+    /// Foldback distortion
+    /// 
+    /// Signal beyond the threshold is reflected back into range instead of
+    /// being clipped, for a harsh, metallic sound that gets richer as the
+    /// drive increases.
+    /// 
+    /// # Arguments
+    /// * `sample` - Input sample
+    /// * `threshold` - Fold point (> 0); output stays within ±threshold
+    pub fn foldback(sample: f32, threshold: f32) -> f32 {
+        ((sample - threshold).rem_euclid(4.0 * threshold) - 2.0 * threshold).abs() - threshold
     }
 
     /// Applies a simple linear fade between two buffers
@@ -187,6 +210,26 @@ mod tests {
         assert!((db - db_back).abs() < 0.01);
     }
 
+    #[test]
+    fn test_shapers() {
+        use sample_utils::*;
+
+        assert_eq!(hard_clip(1.5), 1.0);
+        assert_eq!(hard_clip(-0.5), -0.5);
+        assert!((soft_clip(0.01) - 0.01).abs() < 1e-5);
+        assert!(soft_clip(10.0) <= 1.0 && soft_clip(-10.0) >= -1.0);
+
+        // Tube: silent at rest, asymmetric when driven
+        assert!(tube_clip(0.0).abs() < 1e-7);
+        assert!((tube_clip(2.0) + tube_clip(-2.0)).abs() > 0.1);
+
+        // Foldback: transparent in range, reflected outside it
+        assert!((foldback(0.7, 1.0) - 0.7).abs() < 1e-6);
+        assert!((foldback(1.5, 1.0) - 0.5).abs() < 1e-6);
+        assert!((foldback(-1.25, 1.0) + 0.75).abs() < 1e-6);
+        assert!((foldback(3.5, 1.0) + 0.5).abs() < 1e-6);
+    }
+
     #[test]
     fn test_crossfade() {
         use sample_utils::crossfade;
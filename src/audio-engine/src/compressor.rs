@@ -0,0 +1,439 @@
+//! Compressor / downward expander
+//!
+//! Evens out levels for radio-style sets (compressor) or pushes down noise
+//! and bleed between phrases (expander). Detection is stereo linked and can
+//! run through a high-pass filter so the kick drum does not pump the mix.
+//!
+//! Algorithm: peak envelope detector feeding a log-domain gain computer
+//! with soft knee, and a smoothed gain-reduction branch with separate attack
+//! and release
+//! Reference: Giannoulis, Massberg & Reiss, "Digital Dynamic Range
+//! Compressor Design — A Tutorial and Analysis" (JAES 2012)
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(1) per sample (one log, one exp)
+//! - Memory: ~100 bytes per instance
+//! - Latency: none
+
+use wasm_bindgen::prelude::*;
+
+use crate::biquad::{Biquad, BiquadCoefficients};
+use crate::buffer_manager::sample_utils::{db_to_linear, linear_to_db};
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+
+/// Deepest gain reduction the expander applies, in dB
+const MAX_EXPANSION_DB: f32 = 80.0;
+
+/// Decay of the level detector, long enough to ride over the zero crossings
+/// of a 20 Hz bass note without the expander mistaking them for silence
+const DETECTOR_RELEASE_MS: f32 = 50.0;
+
+/// Direction of the dynamics processing
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DynamicsMode {
+    /// Turns down signal above the threshold
+    Compressor = 0,
+    /// Turns down signal below the threshold
+    Expander = 1,
+}
+
+/// Stereo-linked compressor / expander
+///
+/// A ratio of 1 with no makeup gain is a bypass, which is the default.
+///
+/// # Performance
+/// - Per-sample complexity: O(1)
+/// - Memory: ~100 bytes per instance
+pub struct Compressor {
+    sample_rate: u32,
+    mode: DynamicsMode,
+    threshold_db: f32,
+    ratio: f32,
+    knee_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    makeup_db: f32,
+    sidechain_hz: f32,
+
+    // Derived coefficients
+    attack_coefficient: f32,
+    release_coefficient: f32,
+    // Linear makeup gain, gliding towards the setting
+    makeup: SmoothedParam,
+
+    sidechain: [Biquad; 2],
+    envelope: f32,
+    detector_coefficient: f32,
+    reduction_db: f32,
+    max_reduction_db: f32,
+}
+
+impl Compressor {
+    /// Creates a bypassed compressor (ratio 1, threshold -18 dB, 6 dB knee)
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn new(sample_rate: u32) -> Self {
+        let filter = Biquad::new(BiquadCoefficients::highpass(sample_rate, 20.0, 0.707));
+        let mut compressor = Compressor {
+            sample_rate,
+            mode: DynamicsMode::Compressor,
+            threshold_db: -18.0,
+            ratio: 1.0,
+            knee_db: 6.0,
+            attack_ms: 0.0,
+            release_ms: 0.0,
+            makeup_db: 0.0,
+            sidechain_hz: 0.0,
+            attack_coefficient: 0.0,
+            release_coefficient: 0.0,
+            makeup: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            sidechain: [filter.clone(), filter],
+            envelope: 0.0,
+            detector_coefficient: 0.0,
+            reduction_db: 0.0,
+            max_reduction_db: 0.0,
+        };
+        compressor.detector_coefficient = compressor.time_coefficient(DETECTOR_RELEASE_MS);
+        compressor.set_attack(10.0);
+        compressor.set_release(100.0);
+        compressor
+    }
+
+    /// Sets compression or expansion
+    pub fn set_mode(&mut self, mode: DynamicsMode) {
+        self.mode = mode;
+    }
+
+    /// Gets compression or expansion
+    pub fn get_mode(&self) -> DynamicsMode {
+        self.mode
+    }
+
+    /// Sets the threshold
+    ///
+    /// # Arguments
+    /// * `db` - Threshold in dBFS (-60.0 to 0.0)
+    pub fn set_threshold(&mut self, db: f32) {
+        self.threshold_db = db.clamp(-60.0, 0.0);
+    }
+
+    /// Gets the threshold in dBFS
+    pub fn get_threshold(&self) -> f32 {
+        self.threshold_db
+    }
+
+    /// Sets the ratio
+    ///
+    /// # Arguments
+    /// * `ratio` - Ratio (1.0 to 20.0)
+    ///   - 1.0 = no processing
+    ///   - 4.0 = compressor: 4 dB over the threshold comes out as 1 dB;
+    ///     expander: 1 dB under the threshold comes out as 4 dB under
+    ///   - 20.0 = limiting / gating
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = ratio.clamp(1.0, 20.0);
+    }
+
+    /// Gets the ratio
+    pub fn get_ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    /// Sets the knee width
+    ///
+    /// # Arguments
+    /// * `db` - Width of the soft transition around the threshold (0.0 = hard knee to 24.0)
+    pub fn set_knee(&mut self, db: f32) {
+        self.knee_db = db.clamp(0.0, 24.0);
+    }
+
+    /// Gets the knee width in dB
+    pub fn get_knee(&self) -> f32 {
+        self.knee_db
+    }
+
+    /// Sets the attack time (how fast gain reduction increases)
+    ///
+    /// # Arguments
+    /// * `time_ms` - Attack time constant in milliseconds (0.1-100)
+    pub fn set_attack(&mut self, time_ms: f32) {
+        self.attack_ms = time_ms.clamp(0.1, 100.0);
+        self.attack_coefficient = self.time_coefficient(self.attack_ms);
+    }
+
+    /// Gets the attack time in milliseconds
+    pub fn get_attack(&self) -> f32 {
+        self.attack_ms
+    }
+
+    /// Sets the release time (how fast gain reduction recovers)
+    ///
+    /// # Arguments
+    /// * `time_ms` - Release time constant in milliseconds (10-2000)
+    pub fn set_release(&mut self, time_ms: f32) {
+        self.release_ms = time_ms.clamp(10.0, 2000.0);
+        self.release_coefficient = self.time_coefficient(self.release_ms);
+    }
+
+    /// Gets the release time in milliseconds
+    pub fn get_release(&self) -> f32 {
+        self.release_ms
+    }
+
+    /// Sets the makeup gain applied after the gain reduction
+    ///
+    /// # Arguments
+    /// * `db` - Makeup gain in dB (0.0 to 24.0)
+    pub fn set_makeup_gain(&mut self, db: f32) {
+        self.makeup_db = db.clamp(0.0, 24.0);
+        self.makeup.set_target(db_to_linear(self.makeup_db));
+    }
+
+    /// Gets the makeup gain in dB
+    pub fn get_makeup_gain(&self) -> f32 {
+        self.makeup_db
+    }
+
+    /// Sets the sidechain high-pass frequency
+    ///
+    /// Only the detector hears the filtered signal; the audio is untouched.
+    ///
+    /// # Arguments
+    /// * `freq` - Cutoff in Hz (20-1000), or 0 to disable the filter
+    pub fn set_sidechain_highpass(&mut self, freq: f32) {
+        self.sidechain_hz = if freq <= 0.0 { 0.0 } else { freq.clamp(20.0, 1000.0) };
+        if self.sidechain_hz > 0.0 {
+            let coefficients = BiquadCoefficients::highpass(self.sample_rate, self.sidechain_hz, 0.707);
+            for filter in self.sidechain.iter_mut() {
+                filter.set_coefficients(coefficients);
+            }
+        }
+    }
+
+    /// Gets the sidechain high-pass frequency in Hz (0 = off)
+    pub fn get_sidechain_highpass(&self) -> f32 {
+        self.sidechain_hz
+    }
+
+    /// Sets how long makeup gain changes take to settle
+    ///
+    /// # Arguments
+    /// * `time_ms` - Smoothing time in milliseconds (0 = immediate)
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.makeup.set_smoothing_time(time_ms);
+    }
+
+    /// Whether the settings leave the signal untouched
+    pub fn is_bypassed(&self) -> bool {
+        self.ratio == 1.0 && self.makeup_db == 0.0 && !self.makeup.is_smoothing()
+    }
+
+    /// Largest gain reduction during the last [`Compressor::process_stereo`] call
+    ///
+    /// Returns a positive number of dB (makeup gain not included).
+    pub fn gain_reduction_db(&self) -> f32 {
+        self.max_reduction_db
+    }
+
+    /// Static gain curve: gain in dB (≤ 0) for a detector level in dBFS
+    pub fn gain_at(&self, level_db: f32) -> f32 {
+        let over = level_db - self.threshold_db;
+        let half_knee = self.knee_db * 0.5;
+
+        match self.mode {
+            DynamicsMode::Compressor => {
+                let slope = 1.0 / self.ratio - 1.0;
+                if over <= -half_knee {
+                    0.0
+                } else if over < half_knee {
+                    slope * (over + half_knee).powi(2) / (2.0 * self.knee_db)
+                } else {
+                    slope * over
+                }
+            }
+            DynamicsMode::Expander => {
+                let slope = self.ratio - 1.0;
+                let gain = if over >= half_knee {
+                    0.0
+                } else if over > -half_knee {
+                    -slope * (over - half_knee).powi(2) / (2.0 * self.knee_db)
+                } else {
+                    slope * over
+                };
+                gain.max(-MAX_EXPANSION_DB)
+            }
+        }
+    }
+
+    /// Processes a stereo pair in place
+    ///
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        self.max_reduction_db = 0.0;
+        if self.is_bypassed() {
+            self.envelope = 0.0;
+            self.reduction_db = 0.0;
+            return;
+        }
+
+        let filtered = self.sidechain_hz > 0.0;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let (detect_left, detect_right) = if filtered {
+                let [left_filter, right_filter] = &mut self.sidechain;
+                (left_filter.process_sample(*l), right_filter.process_sample(*r))
+            } else {
+                (*l, *r)
+            };
+
+            // Peak envelope: jumps to new peaks, decays between them
+            let peak = detect_left.abs().max(detect_right.abs());
+            self.envelope = peak.max(self.envelope * self.detector_coefficient);
+
+            let level_db = linear_to_db(self.envelope);
+            let target = -self.gain_at(level_db);
+
+            let coefficient = if target > self.reduction_db {
+                self.attack_coefficient
+            } else {
+                self.release_coefficient
+            };
+            self.reduction_db = target + (self.reduction_db - target) * coefficient;
+            self.max_reduction_db = self.max_reduction_db.max(self.reduction_db);
+
+            let gain = db_to_linear(-self.reduction_db) * self.makeup.next_value();
+            *l *= gain;
+            *r *= gain;
+        }
+    }
+
+    /// Clears the detector and sidechain filter state
+    pub fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.reduction_db = 0.0;
+        self.max_reduction_db = 0.0;
+        for filter in self.sidechain.iter_mut() {
+            filter.reset();
+        }
+    }
+
+    /// One-pole coefficient for a time constant in milliseconds
+    fn time_coefficient(&self, time_ms: f32) -> f32 {
+        (-1.0 / (time_ms * 0.001 * self.sample_rate as f32)).exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    /// Steady-state output peak for a sine of `amplitude`
+    fn settled_peak(compressor: &mut Compressor, freq: f32, amplitude: f32) -> f32 {
+        compressor.reset();
+        let mut left: Vec<f32> = (0..48000)
+            .map(|i| (2.0 * PI * freq * i as f32 / 48000.0).sin() * amplitude)
+            .collect();
+        let mut right = left.clone();
+        compressor.process_stereo(&mut left, &mut right);
+        left[36000..].iter().fold(0.0f32, |m, s| m.max(s.abs()))
+    }
+
+    #[test]
+    fn test_default_is_bypass() {
+        let mut compressor = Compressor::new(48000);
+        assert!(compressor.is_bypassed());
+        let input: Vec<f32> = (0..256).map(|i| (i as f32 * 0.1).sin()).collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+        compressor.process_stereo(&mut left, &mut right);
+        assert_eq!(left, input);
+    }
+
+    #[test]
+    fn test_static_curve() {
+        let mut compressor = Compressor::new(48000);
+        compressor.set_threshold(-20.0);
+        compressor.set_ratio(4.0);
+        compressor.set_knee(0.0);
+
+        assert_eq!(compressor.gain_at(-30.0), 0.0);
+        assert!((compressor.gain_at(-8.0) + 9.0).abs() < 1e-4);
+
+        // Soft knee is continuous and halfway at the threshold
+        compressor.set_knee(10.0);
+        assert!((compressor.gain_at(-15.0) + 3.75).abs() < 1e-4);
+        assert!((compressor.gain_at(-20.0) + 0.9375).abs() < 1e-4);
+        assert_eq!(compressor.gain_at(-25.0), 0.0);
+
+        compressor.set_mode(DynamicsMode::Expander);
+        compressor.set_knee(0.0);
+        compressor.set_ratio(2.0);
+        assert_eq!(compressor.gain_at(-10.0), 0.0);
+        assert!((compressor.gain_at(-30.0) + 10.0).abs() < 1e-4);
+        compressor.set_ratio(20.0);
+        assert_eq!(compressor.gain_at(-60.0), -MAX_EXPANSION_DB);
+    }
+
+    #[test]
+    fn test_compression_and_makeup() {
+        let mut compressor = Compressor::new(48000);
+        compressor.set_threshold(-12.0);
+        compressor.set_ratio(4.0);
+        compressor.set_knee(0.0);
+        compressor.set_release(1000.0);
+
+        // 0 dBFS peaks come down by 9 dB; the slow release holds the
+        // reduction between peaks of the detector
+        let peak = linear_to_db(settled_peak(&mut compressor, 1000.0, 1.0));
+        assert!((peak + 9.0).abs() < 0.5, "{}", peak);
+        assert!((compressor.gain_reduction_db() - 9.0).abs() < 0.5);
+
+        compressor.set_makeup_gain(6.0);
+        let boosted = linear_to_db(settled_peak(&mut compressor, 1000.0, 1.0));
+        assert!((boosted - peak - 6.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_expander_passes_loud_signal() {
+        let mut compressor = Compressor::new(48000);
+        compressor.set_mode(DynamicsMode::Expander);
+        compressor.set_threshold(-18.0);
+        compressor.set_ratio(4.0);
+
+        // A 0 dBFS bass tone is far above the threshold, zero crossings and
+        // all (it starts on a peak, so no silence precedes it)
+        let input: Vec<f32> = (0..48000)
+            .map(|i| (2.0 * PI * 100.0 * i as f32 / 48000.0).cos())
+            .collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+        compressor.process_stereo(&mut left, &mut right);
+
+        let error = left.iter().zip(&input).fold(0.0f32, |m, (o, i)| m.max((o - i).abs()));
+        assert!(error < 1e-3, "{}", error);
+        assert!(compressor.gain_reduction_db() < 0.01);
+
+        // A quiet one is pushed down
+        assert!(settled_peak(&mut compressor, 100.0, 0.01) < 0.001);
+    }
+
+    #[test]
+    fn test_sidechain_highpass_ignores_bass() {
+        let mut compressor = Compressor::new(48000);
+        compressor.set_threshold(-20.0);
+        compressor.set_ratio(8.0);
+
+        let squashed = settled_peak(&mut compressor, 40.0, 0.5);
+        assert!(squashed < 0.3);
+
+        compressor.set_sidechain_highpass(500.0);
+        assert!(settled_peak(&mut compressor, 40.0, 0.5) > 0.45);
+
+        compressor.set_sidechain_highpass(0.0);
+        assert_eq!(compressor.get_sidechain_highpass(), 0.0);
+    }
+}
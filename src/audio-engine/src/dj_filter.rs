@@ -0,0 +1,425 @@
+//! Sweepable DJ filter (combined low-pass / high-pass)
+//!
+//! A single bipolar knob: turning left sweeps a resonant low-pass down from
+//! the top of the spectrum, turning right sweeps a resonant high-pass up from
+//! the bottom. The centre position is a true bypass. Leaving the centre, or
+//! crossing it, fades the filter out and back in rather than restarting it,
+//! so the knob never clicks. Within one side the cutoff and resonance glide
+//! to new settings.
+//!
+//! Algorithm: Topology-preserving transform (trapezoidal) state-variable filter
+//! Reference: Zavalishin, "The Art of VA Filter Design" (2012)
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(1) per sample (~10 float operations, plus a tan and
+//!   two powers while the cutoff or resonance is gliding)
+//! - Memory: 2 floats of state per channel
+//! - Latency: none
+//!
+//! The TPT structure stays stable and free of zipper artifacts while the
+//! cutoff is swept at audio rate, unlike a direct-form biquad.
+
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+
+/// Knob positions closer to centre than this are bypassed
+const DEAD_ZONE: f32 = 0.02;
+
+/// Bottom of the cutoff sweep in Hz
+const MIN_CUTOFF: f32 = 40.0;
+
+/// Top of the cutoff sweep in Hz (further limited to 0.45 × sample rate)
+const MAX_CUTOFF: f32 = 16000.0;
+
+/// Q at full resonance
+const MAX_Q: f32 = 8.0;
+
+/// Fade between the input and the filter when it engages, disengages or
+/// changes sides
+const FADE_MS: f32 = 10.0;
+
+/// Filter response selected by the knob position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FilterResponse {
+    Bypass,
+    LowPass,
+    HighPass,
+}
+
+/// Integrator state of one SVF channel
+#[derive(Clone, Copy, Debug, Default)]
+struct SvfState {
+    ic1: f32,
+    ic2: f32,
+}
+
+/// Stereo resonant DJ filter
+///
+/// # Performance
+/// - Per-sample complexity: O(1) per channel
+/// - Memory: ~40 bytes per instance
+pub struct DjFilter {
+    sample_rate: u32,
+    position: f32,
+    resonance: f32,
+
+    // Response selected by the knob, and the one the SVF is running (the old
+    // side keeps running while it fades out)
+    response: FilterResponse,
+    active: FilterResponse,
+    wet: SmoothedParam,
+
+    cutoff: f32,
+    // Cutoff (log2 Hz) and resonance the coefficients are computed from,
+    // gliding towards the knob settings
+    glide_cutoff: SmoothedParam,
+    glide_resonance: SmoothedParam,
+    // TPT coefficients of the active response
+    g: f32,
+    k: f32,
+    a1: f32,
+    a2: f32,
+    a3: f32,
+
+    channels: [SvfState; 2],
+}
+
+impl DjFilter {
+    /// Creates a bypassed filter (knob centred, no resonance)
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn new(sample_rate: u32) -> Self {
+        let mut filter = DjFilter {
+            sample_rate,
+            position: 0.0,
+            resonance: 0.0,
+            response: FilterResponse::Bypass,
+            active: FilterResponse::Bypass,
+            wet: SmoothedParam::new(0.0, sample_rate, FADE_MS),
+            cutoff: 0.0,
+            glide_cutoff: SmoothedParam::new(MIN_CUTOFF.log2(), sample_rate, DEFAULT_SMOOTHING_MS),
+            glide_resonance: SmoothedParam::new(0.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            g: 0.0,
+            k: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+            a3: 0.0,
+            channels: [SvfState::default(); 2],
+        };
+        filter.update();
+        filter
+    }
+
+    /// Sets the knob position
+    ///
+    /// # Arguments
+    /// * `position` - Knob position (-1.0 to 1.0)
+    ///   - -1.0 = low-pass fully closed
+    ///   - 0.0 = bypass
+    ///   - 1.0 = high-pass fully closed
+    pub fn set_position(&mut self, position: f32) {
+        self.position = position.clamp(-1.0, 1.0);
+        self.update();
+    }
+
+    /// Gets the knob position
+    pub fn get_position(&self) -> f32 {
+        self.position
+    }
+
+    /// Sets the resonance
+    ///
+    /// # Arguments
+    /// * `resonance` - Resonance amount (0.0 to 1.0)
+    ///   - 0.0 = Butterworth (Q 0.707, no peak)
+    ///   - 1.0 = Q 8 (strong peak at the cutoff)
+    pub fn set_resonance(&mut self, resonance: f32) {
+        self.resonance = resonance.clamp(0.0, 1.0);
+        self.update();
+    }
+
+    /// Gets the resonance
+    pub fn get_resonance(&self) -> f32 {
+        self.resonance
+    }
+
+    /// Gets the current cutoff frequency in Hz (0 when bypassed)
+    pub fn get_cutoff(&self) -> f32 {
+        self.cutoff
+    }
+
+    /// Sets how long cutoff and resonance changes take to settle
+    ///
+    /// # Arguments
+    /// * `time_ms` - Smoothing time in milliseconds (0 = immediate)
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.glide_cutoff.set_smoothing_time(time_ms);
+        self.glide_resonance.set_smoothing_time(time_ms);
+    }
+
+    /// Whether the knob is in the centre bypass zone
+    pub fn is_bypassed(&self) -> bool {
+        self.response == FilterResponse::Bypass
+    }
+
+    /// Filters a stereo pair in place
+    ///
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if self.active == FilterResponse::Bypass {
+            return;
+        }
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            (*l, *r) = self.process_frame(*l, *r);
+        }
+    }
+
+    /// Filters a single stereo frame
+    ///
+    /// For use inside per-sample loops such as an effect feedback path.
+    ///
+    /// # Arguments
+    /// * `left` - Left input sample
+    /// * `right` - Right input sample
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if self.active == FilterResponse::Bypass {
+            return (left, right);
+        }
+
+        if self.glide_cutoff.is_smoothing() || self.glide_resonance.is_smoothing() {
+            self.glide_cutoff.next_value();
+            self.glide_resonance.next_value();
+            self.update_coefficients();
+        }
+
+        let wet = self.wet.next_value();
+        let [left_state, right_state] = &mut self.channels;
+        let (k, a1, a2, a3) = (self.k, self.a1, self.a2, self.a3);
+        let filtered_left = Self::tick(left_state, left, self.active, k, a1, a2, a3);
+        let filtered_right = Self::tick(right_state, right, self.active, k, a1, a2, a3);
+
+        // The old side has faded out: move over to the knob's side
+        if wet == 0.0 && self.response != self.active {
+            self.switch_response();
+        }
+
+        (
+            left + (filtered_left - left) * wet,
+            right + (filtered_right - right) * wet,
+        )
+    }
+
+    /// Clears the filter state and settles any fade
+    pub fn reset(&mut self) {
+        self.switch_response();
+        let wet = if self.active == FilterResponse::Bypass { 0.0 } else { 1.0 };
+        self.wet.set_immediate(wet);
+    }
+
+    /// One TPT SVF step, returning the selected response
+    #[inline]
+    fn tick(
+        state: &mut SvfState,
+        input: f32,
+        response: FilterResponse,
+        k: f32,
+        a1: f32,
+        a2: f32,
+        a3: f32,
+    ) -> f32 {
+        let v3 = input - state.ic2;
+        let v1 = a1 * state.ic1 + a2 * v3;
+        let v2 = state.ic2 + a2 * state.ic1 + a3 * v3;
+        state.ic1 = 2.0 * v1 - state.ic1;
+        state.ic2 = 2.0 * v2 - state.ic2;
+
+        match response {
+            FilterResponse::LowPass => v2,
+            FilterResponse::HighPass => input - k * v1 - v2,
+            FilterResponse::Bypass => input,
+        }
+    }
+
+    /// Recomputes cutoff and coefficients from the knob and resonance
+    ///
+    /// The cutoff moves logarithmically with the knob, so equal knob travel
+    /// covers equal musical intervals.
+    fn update(&mut self) {
+        let amount = self.position.abs();
+        self.response = if amount < DEAD_ZONE {
+            FilterResponse::Bypass
+        } else if self.position < 0.0 {
+            FilterResponse::LowPass
+        } else {
+            FilterResponse::HighPass
+        };
+
+        self.cutoff = if self.response == FilterResponse::Bypass {
+            0.0
+        } else {
+            let max_cutoff = MAX_CUTOFF.min(self.sample_rate as f32 * 0.45);
+            let t = (amount - DEAD_ZONE) / (1.0 - DEAD_ZONE);
+            let octaves = (max_cutoff / MIN_CUTOFF).log2();
+            match self.response {
+                FilterResponse::LowPass => max_cutoff * (-octaves * t).exp2(),
+                _ => MIN_CUTOFF * (octaves * t).exp2(),
+            }
+        };
+
+        if self.response == self.active {
+            if self.active != FilterResponse::Bypass {
+                let cutoff = self.cutoff.log2();
+                if self.glide_cutoff.target() != cutoff {
+                    self.glide_cutoff.set_target(cutoff);
+                }
+                if self.glide_resonance.target() != self.resonance {
+                    self.glide_resonance.set_target(self.resonance);
+                }
+            }
+            let wet = if self.active == FilterResponse::Bypass { 0.0 } else { 1.0 };
+            if self.wet.target() != wet {
+                self.wet.set_target(wet);
+            }
+        } else if self.wet.current() == 0.0 {
+            // Nothing of the old side is audible, so it can switch now
+            self.switch_response();
+        } else if self.wet.target() != 0.0 {
+            // Fade the old side out first; it keeps its coefficients
+            self.wet.set_target(0.0);
+        }
+    }
+
+    /// Starts the knob's response from silence and fades it in
+    fn switch_response(&mut self) {
+        self.channels = [SvfState::default(); 2];
+        self.active = self.response;
+        if self.active != FilterResponse::Bypass {
+            self.glide_cutoff.set_immediate(self.cutoff.log2());
+        }
+        self.glide_resonance.set_immediate(self.resonance);
+        self.update_coefficients();
+        if self.active != FilterResponse::Bypass {
+            self.wet.set_target(1.0);
+        }
+    }
+
+    /// Sets the TPT coefficients from the gliding cutoff and resonance
+    fn update_coefficients(&mut self) {
+        if self.active == FilterResponse::Bypass {
+            return;
+        }
+
+        let cutoff = self.glide_cutoff.current().exp2();
+        let q = FRAC_1_SQRT_2 * (MAX_Q / FRAC_1_SQRT_2).powf(self.glide_resonance.current());
+        self.g = (PI * cutoff / self.sample_rate as f32).tan();
+        self.k = 1.0 / q;
+        self.a1 = 1.0 / (1.0 + self.g * (self.g + self.k));
+        self.a2 = self.g * self.a1;
+        self.a3 = self.g * self.a2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone_peak(filter: &mut DjFilter, freq: f32) -> f32 {
+        filter.reset();
+        let mut left: Vec<f32> = (0..24000)
+            .map(|i| (2.0 * PI * freq * i as f32 / 48000.0).sin())
+            .collect();
+        let mut right = left.clone();
+        filter.process_stereo(&mut left, &mut right);
+        left[12000..].iter().fold(0.0f32, |m, s| m.max(s.abs()))
+    }
+
+    #[test]
+    fn test_centre_is_bypass() {
+        let mut filter = DjFilter::new(48000);
+        filter.set_resonance(1.0);
+        filter.set_position(0.01);
+        assert!(filter.is_bypassed());
+
+        let input: Vec<f32> = (0..512).map(|i| (i as f32 * 0.37).sin()).collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+        filter.process_stereo(&mut left, &mut right);
+        assert_eq!(left, input);
+        assert_eq!(right, input);
+    }
+
+    #[test]
+    fn test_logarithmic_sweep() {
+        let mut filter = DjFilter::new(48000);
+        filter.set_position(DEAD_ZONE);
+        assert!((filter.get_cutoff() - MIN_CUTOFF).abs() < 0.01);
+        filter.set_position(1.0);
+        assert!((filter.get_cutoff() - MAX_CUTOFF).abs() < 0.5);
+
+        // Halfway through the travel is the geometric mean of the range
+        filter.set_position(-(DEAD_ZONE + (1.0 - DEAD_ZONE) * 0.5));
+        let mean = (MIN_CUTOFF * MAX_CUTOFF).sqrt();
+        assert!((filter.get_cutoff() - mean).abs() < mean * 0.001);
+    }
+
+    #[test]
+    fn test_low_and_high_pass() {
+        let mut filter = DjFilter::new(48000);
+
+        filter.set_position(-0.6);
+        let cutoff = filter.get_cutoff();
+        assert!(tone_peak(&mut filter, cutoff / 8.0) > 0.95);
+        assert!(tone_peak(&mut filter, cutoff * 8.0) < 0.03);
+
+        filter.set_position(0.6);
+        let cutoff = filter.get_cutoff();
+        assert!(tone_peak(&mut filter, cutoff * 8.0) > 0.95);
+        assert!(tone_peak(&mut filter, cutoff / 8.0) < 0.03);
+    }
+
+    #[test]
+    fn test_engaging_and_crossing_centre_do_not_click() {
+        let mut filter = DjFilter::new(48000);
+        let input: Vec<f32> = (0..48000)
+            .map(|i| (2.0 * PI * 1000.0 * i as f32 / 48000.0).sin() * 0.5)
+            .collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+
+        // Off centre, across it, and back to bypass, mid-tone
+        for (block, (l, r)) in left.chunks_mut(128).zip(right.chunks_mut(128)).enumerate() {
+            match block {
+                50 => filter.set_position(0.6),
+                150 => filter.set_position(-0.6),
+                250 => filter.set_position(0.0),
+                _ => {}
+            }
+            filter.process_stereo(l, r);
+        }
+
+        // A 1 kHz sine at 0.5 moves by at most 0.066 per sample
+        let largest_step = left.windows(2).fold(0.0f32, |m, w| m.max((w[1] - w[0]).abs()));
+        assert!(largest_step < 0.08, "step {}", largest_step);
+        assert_eq!(left[33000..], input[33000..]);
+        assert!(filter.is_bypassed());
+    }
+
+    #[test]
+    fn test_resonance_peaks_at_cutoff() {
+        let mut filter = DjFilter::new(48000);
+        filter.set_position(-0.5);
+        let cutoff = filter.get_cutoff();
+        assert!((tone_peak(&mut filter, cutoff) - FRAC_1_SQRT_2).abs() < 0.02);
+
+        filter.set_resonance(1.0);
+        assert!((tone_peak(&mut filter, cutoff) - MAX_Q).abs() < MAX_Q * 0.05);
+
+        filter.set_resonance(3.0);
+        assert_eq!(filter.get_resonance(), 1.0);
+    }
+}
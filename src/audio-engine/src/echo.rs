@@ -0,0 +1,327 @@
+//! Tempo-synced echo
+//!
+//! Repeats the signal at a musical division of the beat, with feedback for
+//! decaying repeats, an optional ping-pong pattern bouncing between the
+//! ears, and the DJ filter knob in the feedback loop so each repeat gets
+//! darker (low-pass) or thinner (high-pass) than the one before.
+//!
+//! ```text
+//! Input ──┬──────────────────────────── Dry ──────────┐
+//!         └→ (+) → Delay (beats × 60 / BPM) ──┬─ Wet ─(+)→ Output
+//!             ↑                               │
+//!             └──── Feedback ← Filter ←───────┘
+//! ```
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(1) per sample
+//! - Memory: ~1.5MB at 48kHz (4 seconds of stereo delay)
+//! - Latency: none (dry signal passes straight through)
+
+use crate::delay_line::DelayLine;
+use crate::dj_filter::DjFilter;
+use crate::smoothing::{SmoothedParam, SmoothingMode, DEFAULT_SMOOTHING_MS};
+
+/// Longest echo time in seconds (4 beats at 60 BPM)
+const MAX_DELAY_SECONDS: f32 = 4.0;
+
+/// Shortest echo in beats (1/16 of a beat)
+pub const MIN_BEATS: f32 = 0.0625;
+
+/// Longest echo in beats (a bar of 4/4)
+pub const MAX_BEATS: f32 = 4.0;
+
+/// Highest feedback, keeping repeats from building up forever
+const MAX_FEEDBACK: f32 = 0.95;
+
+/// Glide time when the echo time changes (tape-style pitch bend, no click)
+const DELAY_GLIDE_MS: f32 = 100.0;
+
+/// Stereo beat-synced echo
+///
+/// Starts at 120 BPM, 3/4 beat, 50% feedback with the mix at 0 (bypass).
+///
+/// # Performance
+/// - Per-sample complexity: O(1)
+/// - Memory: ~1.5MB per instance at 48kHz
+pub struct Echo {
+    sample_rate: u32,
+    bpm: f32,
+    beats: f32,
+    feedback: f32,
+    ping_pong: bool,
+
+    delay_samples: SmoothedParam,
+    mix: SmoothedParam,
+    lines: [DelayLine; 2],
+    filter: DjFilter,
+    // Delay lines hold only silence (skips clearing them again while bypassed)
+    silent: bool,
+}
+
+impl Echo {
+    /// Creates a bypassed echo
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn new(sample_rate: u32) -> Self {
+        let max_delay = (MAX_DELAY_SECONDS * sample_rate as f32) as usize;
+        let mut delay_samples = SmoothedParam::new(0.0, sample_rate, DELAY_GLIDE_MS);
+        delay_samples.set_mode(SmoothingMode::OnePole);
+
+        let mut echo = Echo {
+            sample_rate,
+            bpm: 120.0,
+            beats: 0.75,
+            feedback: 0.5,
+            ping_pong: false,
+            delay_samples,
+            mix: SmoothedParam::new(0.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            lines: [DelayLine::new(max_delay), DelayLine::new(max_delay)],
+            filter: DjFilter::new(sample_rate),
+            silent: true,
+        };
+        echo.delay_samples.set_immediate(echo.target_delay());
+        echo
+    }
+
+    /// Sets the tempo the echo follows
+    ///
+    /// # Arguments
+    /// * `bpm` - Beats per minute (40-300)
+    pub fn set_bpm(&mut self, bpm: f32) {
+        self.bpm = bpm.clamp(40.0, 300.0);
+        self.delay_samples.set_target(self.target_delay());
+    }
+
+    /// Gets the tempo in BPM
+    pub fn get_bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Sets the echo time as a fraction of a beat
+    ///
+    /// Echo times beyond 4 seconds (long divisions at slow tempos) are
+    /// shortened to 4 seconds.
+    ///
+    /// # Arguments
+    /// * `beats` - Echo time in beats (0.0625 to 4.0)
+    ///   - 0.25 = 1/16 note, 0.5 = 1/8 note, 0.75 = dotted 1/8
+    ///   - 1.0 = one beat, 4.0 = one bar of 4/4
+    pub fn set_beats(&mut self, beats: f32) {
+        self.beats = beats.clamp(MIN_BEATS, MAX_BEATS);
+        self.delay_samples.set_target(self.target_delay());
+    }
+
+    /// Gets the echo time in beats
+    pub fn get_beats(&self) -> f32 {
+        self.beats
+    }
+
+    /// Current echo time in seconds
+    pub fn get_delay_seconds(&self) -> f32 {
+        self.target_delay() / self.sample_rate as f32
+    }
+
+    /// Sets how much of each repeat is fed back
+    ///
+    /// # Arguments
+    /// * `feedback` - Feedback amount (0.0 = single repeat to 0.95)
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback.clamp(0.0, MAX_FEEDBACK);
+    }
+
+    /// Gets the feedback amount
+    pub fn get_feedback(&self) -> f32 {
+        self.feedback
+    }
+
+    /// Sets the dry/wet mix
+    ///
+    /// The dry signal stays at full level up to the middle of the knob, and
+    /// the echo reaches full level there, so a DJ can ride the echo in
+    /// without the track dipping.
+    ///
+    /// # Arguments
+    /// * `mix` - Dry/wet mix (0.0 to 1.0)
+    ///   - 0.0 = dry only (bypass)
+    ///   - 0.5 = dry and echo at full level
+    ///   - 1.0 = echo only
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix.set_target(mix.clamp(0.0, 1.0));
+    }
+
+    /// Gets the dry/wet mix
+    pub fn get_mix(&self) -> f32 {
+        self.mix.target()
+    }
+
+    /// Enables or disables ping-pong (repeats alternate left and right)
+    pub fn set_ping_pong(&mut self, enabled: bool) {
+        self.ping_pong = enabled;
+    }
+
+    /// Gets whether ping-pong is on
+    pub fn get_ping_pong(&self) -> bool {
+        self.ping_pong
+    }
+
+    /// Sets the feedback filter knob
+    ///
+    /// # Arguments
+    /// * `position` - Filter knob (-1.0 to 1.0), as for
+    ///   [`DjFilter::set_position`]; 0.0 leaves the repeats unfiltered
+    pub fn set_filter(&mut self, position: f32) {
+        self.filter.set_position(position);
+    }
+
+    /// Gets the feedback filter knob
+    pub fn get_filter(&self) -> f32 {
+        self.filter.get_position()
+    }
+
+    /// Sets how long mix and feedback filter changes take to settle
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.mix.set_smoothing_time(time_ms);
+        self.filter.set_smoothing_time(time_ms);
+    }
+
+    /// Processes a stereo pair in place
+    ///
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if self.mix.target() == 0.0 && !self.mix.is_smoothing() {
+            if !self.silent {
+                self.reset();
+            }
+            return;
+        }
+        self.silent = false;
+
+        let [left_line, right_line] = &mut self.lines;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let delay = self.delay_samples.next_value();
+            let echo_left = left_line.read(delay);
+            let echo_right = right_line.read(delay);
+
+            let (filtered_left, filtered_right) = self.filter.process_frame(echo_left, echo_right);
+            let feedback_left = filtered_left * self.feedback;
+            let feedback_right = filtered_right * self.feedback;
+            if self.ping_pong {
+                left_line.write((*l + *r) * 0.5 + feedback_right);
+                right_line.write(feedback_left);
+            } else {
+                left_line.write(*l + feedback_left);
+                right_line.write(*r + feedback_right);
+            }
+
+            let mix = self.mix.next_value();
+            let dry = (2.0 - 2.0 * mix).min(1.0);
+            let wet = (2.0 * mix).min(1.0);
+            *l = *l * dry + echo_left * wet;
+            *r = *r * dry + echo_right * wet;
+        }
+    }
+
+    /// Clears the delay lines and filter
+    pub fn reset(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.reset();
+        }
+        self.filter.reset();
+        self.delay_samples.set_immediate(self.target_delay());
+        self.silent = true;
+    }
+
+    /// Echo time in samples for the current tempo and division
+    fn target_delay(&self) -> f32 {
+        let seconds = (self.beats * 60.0 / self.bpm).min(MAX_DELAY_SECONDS);
+        seconds * self.sample_rate as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs an impulse on both channels through the echo
+    fn impulse_response(echo: &mut Echo, len: usize) -> (Vec<f32>, Vec<f32>) {
+        let mut left = vec![0.0; len];
+        let mut right = vec![0.0; len];
+        left[0] = 1.0;
+        right[0] = 1.0;
+        echo.process_stereo(&mut left, &mut right);
+        (left, right)
+    }
+
+    #[test]
+    fn test_zero_mix_is_bypass() {
+        let mut echo = Echo::new(48000);
+        let input: Vec<f32> = (0..512).map(|i| (i as f32 * 0.2).sin()).collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+        echo.process_stereo(&mut left, &mut right);
+        assert_eq!(left, input);
+    }
+
+    #[test]
+    fn test_repeats_follow_tempo() {
+        let mut echo = Echo::new(48000);
+        echo.set_smoothing_time(0.0);
+        echo.set_mix(0.5);
+        echo.set_bpm(120.0);
+        echo.set_beats(0.5);
+        echo.reset();
+
+        // 1/2 beat at 120 BPM = 250 ms = 12000 samples
+        assert!((echo.get_delay_seconds() - 0.25).abs() < 1e-6);
+        let (left, _) = impulse_response(&mut echo, 40000);
+        assert_eq!(left[0], 1.0);
+        assert_eq!(left[12000], 1.0);
+        assert!((left[24000] - 0.5).abs() < 1e-6);
+        assert!((left[36000] - 0.25).abs() < 1e-6);
+        assert!(left[1..12000].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_ping_pong_alternates() {
+        let mut echo = Echo::new(48000);
+        echo.set_smoothing_time(0.0);
+        echo.set_mix(1.0);
+        echo.set_beats(0.25);
+        echo.set_ping_pong(true);
+        echo.reset();
+
+        // 1/4 beat at 120 BPM = 6000 samples; mono input starts on the left
+        let (left, right) = impulse_response(&mut echo, 20000);
+        assert_eq!(left[0], 0.0);
+        assert_eq!((left[6000], right[6000]), (1.0, 0.0));
+        assert_eq!((left[12000], right[12000]), (0.0, 0.5));
+        assert_eq!((left[18000], right[18000]), (0.25, 0.0));
+    }
+
+    #[test]
+    fn test_feedback_filter_darkens_repeats() {
+        let mut echo = Echo::new(48000);
+        echo.set_smoothing_time(0.0);
+        echo.set_mix(1.0);
+        echo.set_beats(0.25);
+        echo.set_feedback(0.9);
+        echo.set_filter(-0.7);
+        echo.reset();
+
+        // A Nyquist-rate click loses almost everything in the low-pass
+        let mut left: Vec<f32> = (0..6000).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 }).collect();
+        left.resize(13000, 0.0);
+        let mut right = left.clone();
+        echo.process_stereo(&mut left, &mut right);
+        let first = left[6000..6002].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        let second = left[12000..13000].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert_eq!(first, 1.0);
+        assert!(second < 0.01, "{}", second);
+
+        echo.set_feedback(2.0);
+        assert_eq!(echo.get_feedback(), MAX_FEEDBACK);
+    }
+}
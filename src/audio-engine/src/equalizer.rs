@@ -1,91 +1,307 @@
-//! Three-band parametric equalizer
+//! Three-band DJ equalizer
 //! 
-//! Implements a simple but effective 3-band EQ with separate control for
-//! high, mid, and low frequencies. Uses first-order IIR filters for efficiency.
+//! Implements a 3-band EQ with separate control for high, mid, and low
+//! frequencies, built from RBJ biquads: a low shelf, a peaking filter and
+//! a high shelf. Coefficients are computed from the sample rate and two
+//! adjustable crossover frequencies.
 //! 
-//! Band ranges:
-//! - Low: 0-250 Hz
-//! - Mid: 250-2000 Hz  
-//! - High: 2000+ Hz
+//! Default band ranges:
+//! - Low: 0-250 Hz (low shelf at the low crossover)
+//! - Mid: 250-2000 Hz (peak centred between the crossovers, spanning both)
+//! - High: 2000+ Hz (high shelf at the high crossover)
+//!
+//! # Isolator mode
+//! [`EqMode::Isolator`] replaces the shelves with a Linkwitz-Riley (LR4)
+//! 3-way band split at the same crossovers. The bands sum back to a flat,
+//! phase-coherent allpass, so each band can be turned down to silence
+//! (−∞ dB, or a kill switch) without comb filtering the others.
 
-use crate::buffer_manager::sample_utils::db_to_linear;
+use rustfft::num_complex::Complex;
+use wasm_bindgen::prelude::*;
 
-/// Simple first-order IIR filter for EQ bands
-/// 
-/// Implements a one-pole filter with minimal computational overhead.
-/// State is maintained per instance for proper filtering across frames.
-struct SimpleFilter {
-    gain: f32,
-    state: f32, // Previous sample for IIR filter
+use crate::biquad::{Biquad, BiquadCoefficients};
+use crate::buffer_manager::sample_utils::{db_to_linear, linear_to_db};
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+
+/// Default low/mid crossover in Hz
+const DEFAULT_LOW_CROSSOVER: f32 = 250.0;
+
+/// Default mid/high crossover in Hz
+const DEFAULT_HIGH_CROSSOVER: f32 = 2000.0;
+
+/// Q of the shelving filters and of each Butterworth half of an LR4 filter
+const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Maximum band boost in isolator mode (dB)
+const ISOLATOR_MAX_GAIN: f32 = 6.0;
+
+/// Samples between shelving coefficient updates while a gain is gliding
+const SMOOTHING_BLOCK: usize = 16;
+
+/// Equalizer topology
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EqMode {
+    /// Low shelf, mid peak and high shelf (±12 dB per band)
+    Shelving = 0,
+    /// Linkwitz-Riley 3-way split (−∞ to +6 dB per band, kill switches)
+    Isolator = 1,
+}
+
+/// Phase-coherent Linkwitz-Riley 3-way band split
+///
+/// ```text
+///        ┌─ LP4(low) ─ AP(high) ─────────── low
+/// in ────┤
+///        └─ HP4(low) ─┬─ LP4(high) ──────── mid
+///                     └─ HP4(high) ──────── high
+/// ```
+///
+/// The allpass gives the low band the same phase shift the high split
+/// adds to the other two, so the three bands sum to an allpass.
+struct Isolator {
+    low: [Biquad; 2],
+    low_allpass: Biquad,
+    rest: [Biquad; 2],
+    mid: [Biquad; 2],
+    high: [Biquad; 2],
 }
 
-impl SimpleFilter {
-    fn new(gain: f32) -> Self {
-        SimpleFilter {
-            gain: db_to_linear(gain),
-            state: 0.0,
+impl Isolator {
+    fn new() -> Self {
+        let identity = || Biquad::new(BiquadCoefficients::IDENTITY);
+        Isolator {
+            low: [identity(), identity()],
+            low_allpass: identity(),
+            rest: [identity(), identity()],
+            mid: [identity(), identity()],
+            high: [identity(), identity()],
         }
     }
 
-    /// Processes a buffer with first-order filter
-    /// 
-    /// Uses leaky integration for stability:
-    /// ```text
-    /// y[n] = gain * x[n] + alpha * y[n-1]
-    /// ```
-    fn process(&mut self, buffer: &mut [f32], alpha: f32) {
-        let gain = self.gain;
-        let mut state = self.state;
+    fn set_crossovers(&mut self, sample_rate: u32, low_hz: f32, high_hz: f32) {
+        let set = |filters: &mut [Biquad; 2], coefficients: BiquadCoefficients| {
+            for filter in filters.iter_mut() {
+                filter.set_coefficients(coefficients);
+            }
+        };
+        set(&mut self.low, BiquadCoefficients::lowpass(sample_rate, low_hz, BUTTERWORTH_Q));
+        set(&mut self.rest, BiquadCoefficients::highpass(sample_rate, low_hz, BUTTERWORTH_Q));
+        set(&mut self.mid, BiquadCoefficients::lowpass(sample_rate, high_hz, BUTTERWORTH_Q));
+        set(&mut self.high, BiquadCoefficients::highpass(sample_rate, high_hz, BUTTERWORTH_Q));
+        self.low_allpass
+            .set_coefficients(BiquadCoefficients::allpass(sample_rate, high_hz, BUTTERWORTH_Q));
+    }
 
-        for sample in buffer.iter_mut() {
-            *sample = *sample * gain;
-            state = state * alpha + *sample * (1.0 - alpha);
-            *sample = state;
+    /// Splits one sample and recombines the bands with linear `gains`
+    #[inline]
+    fn process_sample(&mut self, input: f32, gains: [f32; 3]) -> f32 {
+        let cascade = |filters: &mut [Biquad; 2], x: f32| {
+            let y = filters[0].process_sample(x);
+            filters[1].process_sample(y)
+        };
+
+        let low = self.low_allpass.process_sample(cascade(&mut self.low, input));
+        let rest = cascade(&mut self.rest, input);
+        let mid = cascade(&mut self.mid, rest);
+        let high = cascade(&mut self.high, rest);
+
+        low * gains[0] + mid * gains[1] + high * gains[2]
+    }
+
+    /// Complex response of the recombined bands
+    fn response_at(&self, freq: f32, sample_rate: u32, gains: [f32; 3]) -> Complex<f32> {
+        let response = |filter: &Biquad| filter.coefficients().response_at(freq, sample_rate);
+        let squared = |filters: &[Biquad; 2]| response(&filters[0]) * response(&filters[1]);
+
+        let low = squared(&self.low) * response(&self.low_allpass);
+        let rest = squared(&self.rest);
+        let mid = rest * squared(&self.mid);
+        let high = rest * squared(&self.high);
+
+        low * gains[0] + mid * gains[1] + high * gains[2]
+    }
+
+    fn reset(&mut self) {
+        for filter in self
+            .low
+            .iter_mut()
+            .chain(self.rest.iter_mut())
+            .chain(self.mid.iter_mut())
+            .chain(self.high.iter_mut())
+        {
+            filter.reset();
         }
+        self.low_allpass.reset();
+    }
+}
 
-        self.state = state;
+/// Filter state for one audio channel
+///
+/// Each channel owns its filter memories so that stereo processing never
+/// carries one channel's IIR state into the other.
+struct EqChannel {
+    low_band: Biquad,
+    mid_band: Biquad,
+    high_band: Biquad,
+    isolator: Isolator,
+}
+
+impl EqChannel {
+    fn new() -> Self {
+        EqChannel {
+            low_band: Biquad::new(BiquadCoefficients::IDENTITY),
+            mid_band: Biquad::new(BiquadCoefficients::IDENTITY),
+            high_band: Biquad::new(BiquadCoefficients::IDENTITY),
+            isolator: Isolator::new(),
+        }
     }
 
-    fn set_gain(&mut self, db: f32) {
-        self.gain = db_to_linear(db);
+    /// Filters `buffer`; isolator band gains ramp linearly from `from` to `to`
+    fn process(&mut self, buffer: &mut [f32], mode: EqMode, from: [f32; 3], to: [f32; 3]) {
+        match mode {
+            EqMode::Shelving => {
+                for sample in buffer.iter_mut() {
+                    let low = self.low_band.process_sample(*sample);
+                    let mid = self.mid_band.process_sample(low);
+                    *sample = self.high_band.process_sample(mid);
+                }
+            }
+            EqMode::Isolator => {
+                let len = buffer.len() as f32;
+                for (i, sample) in buffer.iter_mut().enumerate() {
+                    let t = (i + 1) as f32 / len;
+                    let gains = [0, 1, 2].map(|band| from[band] + (to[band] - from[band]) * t);
+                    *sample = self.isolator.process_sample(*sample, gains);
+                }
+            }
+        }
+    }
+
+    /// Filters `buffer` through both topologies, fading from the other one into `mode`
+    /// 
+    /// The incoming topology's weight ramps linearly from `fade.0` to `fade.1`.
+    /// An outgoing isolator keeps the linear band gains `outgoing`; outgoing
+    /// shelves keep the coefficients they had when the mode changed.
+    fn process_crossfade(
+        &mut self,
+        buffer: &mut [f32],
+        mode: EqMode,
+        (from, to): ([f32; 3], [f32; 3]),
+        outgoing: [f32; 3],
+        fade: (f32, f32),
+    ) {
+        let len = buffer.len() as f32;
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let t = (i + 1) as f32 / len;
+            let gains = [0, 1, 2].map(|band| from[band] + (to[band] - from[band]) * t);
+            let isolator_gains = if mode == EqMode::Isolator { gains } else { outgoing };
+
+            let low = self.low_band.process_sample(*sample);
+            let mid = self.mid_band.process_sample(low);
+            let shelved = self.high_band.process_sample(mid);
+            let isolated = self.isolator.process_sample(*sample, isolator_gains);
+
+            let (incoming, outgoing) = match mode {
+                EqMode::Shelving => (shelved, isolated),
+                EqMode::Isolator => (isolated, shelved),
+            };
+            let weight = fade.0 + (fade.1 - fade.0) * t;
+            *sample = outgoing + (incoming - outgoing) * weight;
+        }
+    }
+
+    /// Clears the filter state of one topology
+    fn reset(&mut self, mode: EqMode) {
+        match mode {
+            EqMode::Shelving => {
+                self.low_band.reset();
+                self.mid_band.reset();
+                self.high_band.reset();
+            }
+            EqMode::Isolator => self.isolator.reset(),
+        }
     }
 }
 
-/// Three-band parametric equalizer
+/// Three-band equalizer
 /// 
 /// Provides separate gain control for low, mid, and high frequencies.
 /// Each band can be boosted or cut by up to ±12dB.
 /// 
+/// Left and right channels share coefficients but keep independent filter
+/// state; use [`Equalizer::process_stereo`] for stereo audio.
+/// 
+/// Gain changes are smoothed (see [`SmoothedParam`]): isolator gains ramp
+/// per sample, shelving coefficients are recomputed every 16 samples while
+/// a gain is moving. Mode switches crossfade from the old topology to the
+/// new one over the same smoothing time.
+/// 
 /// # Performance
 /// - Per-sample complexity: O(n) where n = frame size
-/// - Memory: ~12 bytes per instance
-/// - DSP cost: ~6 float operations per sample
+/// - Memory: ~400 bytes per instance
+/// - DSP cost per sample per channel: 3 biquads (~27 float operations) in
+///   shelving mode, 9 biquads (~80) in isolator mode, both during a mode
+///   crossfade
 pub struct Equalizer {
-    low_band: SimpleFilter,
-    mid_band: SimpleFilter,
-    high_band: SimpleFilter,
+    sample_rate: u32,
+    mode: EqMode,
+    low_crossover: f32,
+    high_crossover: f32,
+
+    channels: [EqChannel; 2],
     
     low_gain_db: f32,
     mid_gain_db: f32,
     high_gain_db: f32,
+
+    // Isolator kill switches (low, mid, high)
+    kills: [bool; 3],
+
+    // Linear band gains actually applied, gliding towards the settings
+    band_gains: [SmoothedParam; 3],
+
+    // Weight of the current topology while a mode switch crossfades (0-1)
+    fade: SmoothedParam,
+    // Linear band gains the outgoing topology keeps during the crossfade
+    outgoing_gains: [f32; 3],
 }
 
 impl Equalizer {
     /// Creates a new equalizer with flat response (0dB all bands)
-    pub fn new() -> Self {
-        Equalizer {
-            low_band: SimpleFilter::new(0.0),
-            mid_band: SimpleFilter::new(0.0),
-            high_band: SimpleFilter::new(0.0),
+    /// 
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn new(sample_rate: u32) -> Self {
+        let gain = || SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS);
+        let mut eq = Equalizer {
+            sample_rate,
+            mode: EqMode::Shelving,
+            low_crossover: DEFAULT_LOW_CROSSOVER,
+            high_crossover: DEFAULT_HIGH_CROSSOVER,
+
+            channels: [EqChannel::new(), EqChannel::new()],
             
             low_gain_db: 0.0,
             mid_gain_db: 0.0,
             high_gain_db: 0.0,
-        }
+
+            kills: [false; 3],
+
+            band_gains: [gain(), gain(), gain()],
+
+            fade: gain(),
+            outgoing_gains: [1.0; 3],
+        };
+        eq.update_all();
+        eq
     }
 
     /// Processes a mono buffer through all EQ bands
     /// 
+    /// Uses the left channel's filter state; do not mix with
+    /// [`Equalizer::process_stereo`] on the same stream.
+    /// 
     /// # Arguments
     /// * `buffer` - Audio samples to process (modified in-place)
     /// 
@@ -93,26 +309,261 @@ impl Equalizer {
     /// - ~2µs per 256 samples on modern CPU
     /// - Single-pass processing
     pub fn process(&mut self, buffer: &mut [f32]) {
-        if buffer.is_empty() {
+        self.run(buffer, None);
+    }
+
+    /// Processes a stereo pair with independent per-channel filter state
+    /// 
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        self.run(left, Some(right));
+    }
+
+    /// Sets how long gain changes take to settle
+    /// 
+    /// # Arguments
+    /// * `time_ms` - Smoothing time in milliseconds (0 = immediate)
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        for gain in self.band_gains.iter_mut() {
+            gain.set_smoothing_time(time_ms);
+        }
+        self.fade.set_smoothing_time(time_ms);
+    }
+
+    /// Switches between shelving EQ and isolator
+    /// 
+    /// Band gains are re-clamped to the new mode's range. The old topology
+    /// keeps running and crossfades into the new one over the smoothing time.
+    pub fn set_mode(&mut self, mode: EqMode) {
+        if mode == self.mode {
             return;
         }
 
-        // Apply each band with different filter coefficients
-        // Different alpha values for different frequency responses
-        self.low_band.process(buffer, 0.95);  // Low-pass characteristic
-        self.mid_band.process(buffer, 0.9);   // Band-pass characteristic
-        self.high_band.process(buffer, 0.85); // High-pass characteristic
+        self.mode = mode;
+        let (min, max) = self.gain_range();
+        self.low_gain_db = self.low_gain_db.clamp(min, max);
+        self.mid_gain_db = self.mid_gain_db.clamp(min, max);
+        self.high_gain_db = self.high_gain_db.clamp(min, max);
+
+        let current = self.band_gains.clone().map(|gain| gain.current());
+        if self.fade.is_smoothing() {
+            // Switching back mid-fade: the incoming topology is still running
+            // with the gains it was fading out with
+            self.fade.set_immediate(1.0 - self.fade.current());
+            for (gain, &from) in self.band_gains.iter_mut().zip(&self.outgoing_gains) {
+                gain.set_immediate(from);
+            }
+        } else {
+            // The incoming topology fades in from silence, so its gains can snap
+            for channel in self.channels.iter_mut() {
+                channel.reset(mode);
+            }
+            self.fade.set_immediate(0.0);
+            let targets = self.gain_targets();
+            for (gain, target) in self.band_gains.iter_mut().zip(targets) {
+                gain.set_immediate(target);
+            }
+        }
+        self.outgoing_gains = current;
+        self.fade.set_target(1.0);
+        self.update_all();
+    }
+
+    /// Gets the current EQ topology
+    pub fn get_mode(&self) -> EqMode {
+        self.mode
+    }
+
+    /// Sets the low band kill switch (isolator mode only)
+    pub fn set_low_kill(&mut self, killed: bool) {
+        self.kills[0] = killed;
+        self.update_targets();
+    }
+
+    /// Gets the low band kill switch
+    pub fn get_low_kill(&self) -> bool {
+        self.kills[0]
+    }
+
+    /// Sets the mid band kill switch (isolator mode only)
+    pub fn set_mid_kill(&mut self, killed: bool) {
+        self.kills[1] = killed;
+        self.update_targets();
+    }
+
+    /// Gets the mid band kill switch
+    pub fn get_mid_kill(&self) -> bool {
+        self.kills[1]
+    }
+
+    /// Sets the high band kill switch (isolator mode only)
+    pub fn set_high_kill(&mut self, killed: bool) {
+        self.kills[2] = killed;
+        self.update_targets();
+    }
+
+    /// Gets the high band kill switch
+    pub fn get_high_kill(&self) -> bool {
+        self.kills[2]
+    }
+
+    /// Sets the crossover frequencies between the bands
+    /// 
+    /// # Arguments
+    /// * `low_hz` - Low/mid crossover (20-1000 Hz)
+    /// * `high_hz` - Mid/high crossover (at least one octave above `low_hz`,
+    ///   up to 16 kHz or 0.45 × sample rate)
+    pub fn set_crossovers(&mut self, low_hz: f32, high_hz: f32) {
+        let max_high = (self.sample_rate as f32 * 0.45).min(16000.0);
+        self.low_crossover = low_hz.clamp(20.0, 1000.0);
+        self.high_crossover = high_hz.clamp(self.low_crossover * 2.0, max_high.max(self.low_crossover * 2.0));
+
+        self.update_all();
+    }
+
+    /// Gets the low/mid crossover frequency in Hz
+    pub fn get_low_crossover(&self) -> f32 {
+        self.low_crossover
+    }
+
+    /// Gets the mid/high crossover frequency in Hz
+    pub fn get_high_crossover(&self) -> f32 {
+        self.high_crossover
+    }
+
+    /// Linear magnitude response of the whole EQ at `freq`
+    /// 
+    /// Reflects the gain settings, not the smoothed gains currently applied.
+    pub fn magnitude_at(&self, freq: f32) -> f32 {
+        match self.mode {
+            EqMode::Shelving => self
+                .shelving_coefficients([self.low_gain_db, self.mid_gain_db, self.high_gain_db])
+                .iter()
+                .map(|band| band.magnitude_at(freq, self.sample_rate))
+                .product(),
+            EqMode::Isolator => self.channels[0]
+                .isolator
+                .response_at(freq, self.sample_rate, self.gain_targets())
+                .norm(),
+        }
+    }
+
+    /// Runs the EQ in sub-blocks so smoothed gains can update the filters
+    fn run(&mut self, left: &mut [f32], mut right: Option<&mut [f32]>) {
+        let mut start = 0;
+        while start < left.len() {
+            let moving = self.fade.is_smoothing() || self.band_gains.iter().any(SmoothedParam::is_smoothing);
+            let end = if moving {
+                (start + SMOOTHING_BLOCK).min(left.len())
+            } else {
+                left.len()
+            };
+
+            let from = self.band_gains.clone().map(|gain| gain.current());
+            let to = self.band_gains.each_mut().map(|gain| gain.skip(end - start));
+            if self.mode == EqMode::Shelving && from != to {
+                self.update_shelving(to);
+            }
+
+            let fade = (self.fade.current(), self.fade.skip(end - start));
+            if fade.0 < 1.0 {
+                let outgoing = self.outgoing_gains;
+                self.channels[0].process_crossfade(&mut left[start..end], self.mode, (from, to), outgoing, fade);
+                if let Some(right) = right.as_deref_mut() {
+                    self.channels[1].process_crossfade(&mut right[start..end], self.mode, (from, to), outgoing, fade);
+                }
+            } else {
+                self.channels[0].process(&mut left[start..end], self.mode, from, to);
+                if let Some(right) = right.as_deref_mut() {
+                    self.channels[1].process(&mut right[start..end], self.mode, from, to);
+                }
+            }
+            start = end;
+        }
+    }
+
+    /// Gain range of a band in the current mode (dB)
+    fn gain_range(&self) -> (f32, f32) {
+        match self.mode {
+            EqMode::Shelving => (-12.0, 12.0),
+            EqMode::Isolator => (f32::NEG_INFINITY, ISOLATOR_MAX_GAIN),
+        }
+    }
+
+    /// Linear band gains the smoothing glides towards
+    /// 
+    /// Kill switches only apply in isolator mode.
+    fn gain_targets(&self) -> [f32; 3] {
+        let gains = [self.low_gain_db, self.mid_gain_db, self.high_gain_db];
+        let isolator = self.mode == EqMode::Isolator;
+        let mut linear = [0.0; 3];
+        for ((out, &db), &killed) in linear.iter_mut().zip(&gains).zip(&self.kills) {
+            *out = if isolator && killed { 0.0 } else { db_to_linear(db) };
+        }
+        linear
+    }
+
+    fn update_targets(&mut self) {
+        let targets = self.gain_targets();
+        for (gain, target) in self.band_gains.iter_mut().zip(targets) {
+            gain.set_target(target);
+        }
+    }
+
+    /// Recomputes every filter from the current settings and smoothed gains
+    /// 
+    /// In isolator mode the shelves keep the gains they fade out with.
+    fn update_all(&mut self) {
+        let shelving = match self.mode {
+            EqMode::Shelving => self.band_gains.clone().map(|gain| gain.current()),
+            EqMode::Isolator => self.outgoing_gains,
+        };
+        self.update_shelving(shelving);
+        for channel in self.channels.iter_mut() {
+            channel
+                .isolator
+                .set_crossovers(self.sample_rate, self.low_crossover, self.high_crossover);
+        }
+        self.update_targets();
+    }
+
+    /// Sets the shelving filters to the given linear band gains
+    fn update_shelving(&mut self, gains: [f32; 3]) {
+        let [low, mid, high] = self.shelving_coefficients(gains.map(linear_to_db));
+        for channel in self.channels.iter_mut() {
+            channel.low_band.set_coefficients(low);
+            channel.mid_band.set_coefficients(mid);
+            channel.high_band.set_coefficients(high);
+        }
+    }
+
+    /// Low shelf, mid peak and high shelf coefficients for band gains in dB
+    /// 
+    /// The mid peak sits at the geometric centre of the crossovers with a
+    /// bandwidth that spans them.
+    fn shelving_coefficients(&self, gains_db: [f32; 3]) -> [BiquadCoefficients; 3] {
+        let centre = (self.low_crossover * self.high_crossover).sqrt();
+        let span = self.high_crossover / self.low_crossover;
+        let mid_q = span.sqrt() / (span - 1.0);
+
+        [
+            BiquadCoefficients::low_shelf(self.sample_rate, self.low_crossover, BUTTERWORTH_Q, gains_db[0]),
+            BiquadCoefficients::peaking(self.sample_rate, centre, mid_q, gains_db[1]),
+            BiquadCoefficients::high_shelf(self.sample_rate, self.high_crossover, BUTTERWORTH_Q, gains_db[2]),
+        ]
     }
 
     /// Sets the low-frequency band gain
     /// 
     /// # Arguments
-    /// * `db` - Gain in decibels (-12.0 to +12.0)
+    /// * `db` - Gain in decibels (-12.0 to +12.0; −∞ to +6.0 in isolator mode)
     #[inline]
     pub fn set_low_gain(&mut self, db: f32) {
-        let db = db.clamp(-12.0, 12.0);
-        self.low_gain_db = db;
-        self.low_band.set_gain(db);
+        let (min, max) = self.gain_range();
+        self.low_gain_db = db.clamp(min, max);
+        self.update_targets();
     }
 
     /// Gets the current low-frequency band gain
@@ -124,12 +575,12 @@ impl Equalizer {
     /// Sets the mid-frequency band gain
     /// 
     /// # Arguments
-    /// * `db` - Gain in decibels (-12.0 to +12.0)
+    /// * `db` - Gain in decibels (-12.0 to +12.0; −∞ to +6.0 in isolator mode)
     #[inline]
     pub fn set_mid_gain(&mut self, db: f32) {
-        let db = db.clamp(-12.0, 12.0);
-        self.mid_gain_db = db;
-        self.mid_band.set_gain(db);
+        let (min, max) = self.gain_range();
+        self.mid_gain_db = db.clamp(min, max);
+        self.update_targets();
     }
 
     /// Gets the current mid-frequency band gain
@@ -141,12 +592,12 @@ impl Equalizer {
     /// Sets the high-frequency band gain
     /// 
     /// # Arguments
-    /// * `db` - Gain in decibels (-12.0 to +12.0)
+    /// * `db` - Gain in decibels (-12.0 to +12.0; −∞ to +6.0 in isolator mode)
     #[inline]
     pub fn set_high_gain(&mut self, db: f32) {
-        let db = db.clamp(-12.0, 12.0);
-        self.high_gain_db = db;
-        self.high_band.set_gain(db);
+        let (min, max) = self.gain_range();
+        self.high_gain_db = db.clamp(min, max);
+        self.update_targets();
     }
 
     /// Gets the current high-frequency band gain
@@ -155,17 +606,13 @@ impl Equalizer {
         self.high_gain_db
     }
 
-    /// Resets all bands to flat response (0dB)
+    /// Resets all bands to flat response (0dB) and releases the kill switches
     pub fn reset(&mut self) {
         self.set_low_gain(0.0);
         self.set_mid_gain(0.0);
         self.set_high_gain(0.0);
-    }
-}
-
-impl Default for Equalizer {
-    fn default() -> Self {
-        Self::new()
+        self.kills = [false; 3];
+        self.update_targets();
     }
 }
 
@@ -175,7 +622,7 @@ mod tests {
 
     #[test]
     fn test_equalizer_creation() {
-        let eq = Equalizer::new();
+        let eq = Equalizer::new(48000);
         assert_eq!(eq.get_low_gain(), 0.0);
         assert_eq!(eq.get_mid_gain(), 0.0);
         assert_eq!(eq.get_high_gain(), 0.0);
@@ -183,7 +630,7 @@ mod tests {
 
     #[test]
     fn test_gain_control() {
-        let mut eq = Equalizer::new();
+        let mut eq = Equalizer::new(48000);
         
         eq.set_low_gain(6.0);
         assert_eq!(eq.get_low_gain(), 6.0);
@@ -197,7 +644,7 @@ mod tests {
 
     #[test]
     fn test_gain_clamping() {
-        let mut eq = Equalizer::new();
+        let mut eq = Equalizer::new(48000);
         
         // Should clamp to max
         eq.set_low_gain(20.0);
@@ -208,15 +655,184 @@ mod tests {
         assert_eq!(eq.get_low_gain(), -12.0);
     }
 
+    #[test]
+    fn test_band_response() {
+        let mut eq = Equalizer::new(48000);
+        assert!((eq.magnitude_at(1000.0) - 1.0).abs() < 1e-4);
+
+        let db = |eq: &Equalizer, freq: f32| 20.0 * eq.magnitude_at(freq).log10();
+
+        eq.set_low_gain(-12.0);
+        assert!((db(&eq, 30.0) + 12.0).abs() < 0.5);
+        assert!(db(&eq, 8000.0).abs() < 0.5);
+
+        eq.set_low_gain(0.0);
+        eq.set_high_gain(12.0);
+        assert!((db(&eq, 16000.0) - 12.0).abs() < 0.5);
+        assert!(db(&eq, 50.0).abs() < 0.5);
+
+        eq.set_high_gain(0.0);
+        eq.set_mid_gain(-12.0);
+        assert!((db(&eq, 707.0) + 12.0).abs() < 0.1);
+        assert!(db(&eq, 20.0).abs() < 1.0);
+        assert!(db(&eq, 18000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_crossovers() {
+        let mut eq = Equalizer::new(48000);
+        eq.set_crossovers(100.0, 5000.0);
+        assert_eq!(eq.get_low_crossover(), 100.0);
+        assert_eq!(eq.get_high_crossover(), 5000.0);
+
+        // Low shelf is at half its gain at the crossover
+        eq.set_low_gain(-12.0);
+        assert!((20.0 * eq.magnitude_at(100.0).log10() + 6.0).abs() < 0.5);
+
+        // Crossovers stay at least an octave apart
+        eq.set_crossovers(800.0, 900.0);
+        assert_eq!(eq.get_high_crossover(), 1600.0);
+    }
+
+    #[test]
+    fn test_isolator_sums_flat() {
+        let mut eq = Equalizer::new(48000);
+        eq.set_mode(EqMode::Isolator);
+        assert_eq!(eq.get_mode(), EqMode::Isolator);
+
+        for &freq in &[20.0, 100.0, 250.0, 700.0, 2000.0, 5000.0, 15000.0] {
+            assert!((eq.magnitude_at(freq) - 1.0).abs() < 1e-3, "{} Hz", freq);
+        }
+    }
+
+    #[test]
+    fn test_isolator_kill() {
+        let mut eq = Equalizer::new(48000);
+        eq.set_mode(EqMode::Isolator);
+
+        eq.set_low_gain(f32::NEG_INFINITY);
+        assert_eq!(eq.get_low_gain(), f32::NEG_INFINITY);
+        assert!(eq.magnitude_at(40.0) < 0.01);
+        assert!((eq.magnitude_at(5000.0) - 1.0).abs() < 0.01);
+
+        eq.set_low_gain(20.0);
+        assert_eq!(eq.get_low_gain(), 6.0);
+
+        eq.set_low_gain(0.0);
+        eq.set_mid_kill(true);
+        assert!(eq.get_mid_kill());
+        assert!(eq.magnitude_at(700.0) < 0.1);
+        assert!((eq.magnitude_at(30.0) - 1.0).abs() < 0.01);
+
+        // Switching back to shelving re-clamps the gains and ignores kills
+        eq.set_low_gain(f32::NEG_INFINITY);
+        eq.set_mode(EqMode::Shelving);
+        assert_eq!(eq.get_low_gain(), -12.0);
+        assert!((eq.magnitude_at(700.0) - 1.0).abs() < 0.1);
+    }
+
     #[test]
     fn test_process() {
-        let mut eq = Equalizer::new();
-        let mut buffer = vec![0.1; 256];
-        
-        eq.set_low_gain(6.0);
+        let mut eq = Equalizer::new(48000);
+        eq.set_low_gain(-12.0);
+
+        // A 50 Hz tone is cut by ~12 dB, a 5 kHz tone passes
+        for &(freq, expected) in &[(50.0, 0.25), (5000.0, 1.0)] {
+            let mut buffer: Vec<f32> = (0..48000)
+                .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / 48000.0).sin())
+                .collect();
+            eq.process(&mut buffer);
+
+            let peak = buffer[24000..].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+            assert!((peak - expected).abs() < expected * 0.1, "{} Hz: {}", freq, peak);
+        }
+    }
+    #[test]
+    fn test_gain_changes_are_smoothed() {
+        // DC passes the low band at its full gain in both modes
+        for &(mode, db, settled) in &[(EqMode::Shelving, -12.0, db_to_linear(-12.0)), (EqMode::Isolator, f32::NEG_INFINITY, 0.0)] {
+            let mut eq = Equalizer::new(48000);
+            eq.set_mode(mode);
+            let mut warmup = vec![1.0; 4800];
+            eq.process(&mut warmup);
+
+            eq.set_low_gain(db);
+            let mut buffer = vec![1.0; 4800];
+            for block in buffer.chunks_mut(128) {
+                eq.process(block);
+            }
+
+            // A 20 ms glide instead of a step
+            let max_step = buffer.windows(2).fold(0.0f32, |m, w| m.max((w[1] - w[0]).abs()));
+            assert!(max_step < 0.002, "{:?}: {}", mode, max_step);
+            assert!((buffer[4799] - settled).abs() < 1e-3, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_mode_switches_crossfade() {
+        let mut eq = Equalizer::new(48000);
+        eq.set_low_gain(-12.0);
+        eq.set_low_kill(true);
+        let mut warmup = vec![1.0; 4800];
+        eq.process(&mut warmup);
+
+        // DC sits in the low band: -12 dB on the shelf, killed by the isolator
+        let mut previous = warmup[4799];
+        for &(mode, settled) in &[(EqMode::Isolator, 0.0), (EqMode::Shelving, db_to_linear(-12.0))] {
+            eq.set_mode(mode);
+            let mut buffer = vec![1.0; 4800];
+            for block in buffer.chunks_mut(128) {
+                eq.process(block);
+            }
+
+            let max_step = buffer.iter().fold((previous, 0.0f32), |(last, m), &s| (s, m.max((s - last).abs()))).1;
+            assert!(max_step < 0.01, "{:?}: {}", mode, max_step);
+            assert!((buffer[4799] - settled).abs() < 1e-3, "{:?}: {}", mode, buffer[4799]);
+            previous = buffer[4799];
+        }
+
+        // Switching back mid-fade picks up where the fade left off
+        eq.set_mode(EqMode::Isolator);
+        let mut buffer = vec![1.0; 480];
         eq.process(&mut buffer);
-        
-        // Should have some effect (not exact due to filter)
-        assert!(buffer.len() == 256);
+        eq.set_mode(EqMode::Shelving);
+        let mut reversed = vec![1.0; 4800];
+        eq.process(&mut reversed);
+        assert!((reversed[0] - buffer[479]).abs() < 0.01, "{} -> {}", buffer[479], reversed[0]);
+        assert!((reversed[4799] - db_to_linear(-12.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_stereo_channels_are_independent() {
+        for &mode in &[EqMode::Shelving, EqMode::Isolator] {
+            let mut stereo = Equalizer::new(48000);
+            let mut mono = Equalizer::new(48000);
+            for eq in [&mut stereo, &mut mono] {
+                eq.set_mode(mode);
+                eq.set_low_gain(6.0);
+                eq.set_high_gain(-9.0);
+            }
+
+            let signal: Vec<f32> = (0..4800)
+                .map(|i| (2.0 * std::f32::consts::PI * 80.0 * i as f32 / 48000.0).sin())
+                .collect();
+            let mut left = signal.clone();
+            let mut right = vec![0.0; signal.len()];
+            let mut reference = signal;
+
+            for ((l, r), m) in left
+                .chunks_mut(128)
+                .zip(right.chunks_mut(128))
+                .zip(reference.chunks_mut(128))
+            {
+                stereo.process_stereo(l, r);
+                mono.process(m);
+            }
+
+            // Silence stays silent and the left channel is unaffected by the right
+            assert!(right.iter().all(|&s| s == 0.0), "{:?}", mode);
+            assert_eq!(left, reference, "{:?}", mode);
+        }
     }
 }
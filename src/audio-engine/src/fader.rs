@@ -1,32 +1,77 @@
-//! Stereo fader with left/right balance control
+//! Crossfader with selectable crossfade curves
 //!
-//! Implements constant-power crossfade between left and right channels
-//! for smooth stereo mixing and DJ-style crossfading effects.
+//! Fades between two sources ("left" = A side, "right" = B side) along a
+//! selectable curve, from constant-power blends for long mixes to sharp
+//! cuts for scratching. Stereo placement within a deck is handled by
+//! [`Panner`](crate::panner::Panner).
 
-/// Stereo fader with constant-power crossfade
-///
-/// Provides smooth left/right balance control using constant-power curves
-/// to maintain consistent perceived loudness throughout the fade range.
+use std::f32::consts::FRAC_PI_2;
+
+use wasm_bindgen::prelude::*;
+
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+
+/// Fraction of the travel over which the sharp-cut curve fades a side in
+const CUT_WIDTH: f32 = 0.05;
+
+/// Crossfade curve shape
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaderCurve {
+    /// Straight-line gains; dips -6 dB per side (half power) at centre
+    Linear = 0,
+    /// sin/cos gains; -3 dB per side, constant power across the travel
+    ConstantPower = 1,
+    /// Both sides at full level except the outer 5% of the travel (scratch)
+    SharpCut = 2,
+    /// Continuously adjustable between constant power and sharp cut
+    /// (see [`Fader::set_curve_slope`])
+    Custom = 3,
+}
+
+/// Stereo fader with selectable crossfade curve
 ///
-/// Crossfade law:
+/// With `t` = (position + 1) / 2, the curves are:
 /// ```text
-/// Left gain:  cos(π * position / 2) where position ∈ [-1, 1]
-/// Right gain: sin(π * position / 2)
+/// Linear:         L = 1 - t                      R = t
+/// ConstantPower:  L = cos(π t / 2)               R = sin(π t / 2)
+/// SharpCut/Custom: L = sin(π/2 · min(1, (1 - t) / w))
+///                 R = sin(π/2 · min(1, t / w))
 /// ```
-/// 
-/// This ensures power remains constant during the crossfade.
+/// where `w` is the fraction of the travel a side takes to fade in fully:
+/// 1 for constant power, 0.05 for sharp cut, and 0.05^slope for the custom
+/// curve. Every curve except linear keeps L² + R² ≥ 1, so the mix never
+/// dips in loudness.
+///
+/// Channel gains glide to their new values after a position or curve change
+/// (see [`SmoothedParam`]), so fast fader moves do not click.
 pub struct Fader {
     position: f32, // -1.0 to 1.0
+    curve: FaderCurve,
+    slope: f32,
+    left_gain: SmoothedParam,
+    right_gain: SmoothedParam,
 }
 
 impl Fader {
-    /// Creates a new fader in the center position
-    pub fn new() -> Self {
-        Fader { position: 0.0 }
+    /// Creates a new constant-power fader in the center position
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz (for gain smoothing)
+    pub fn new(sample_rate: u32) -> Self {
+        let mut fader = Fader {
+            position: 0.0,
+            curve: FaderCurve::ConstantPower,
+            slope: 0.5,
+            left_gain: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            right_gain: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+        };
+        fader.reset();
+        fader
     }
 
     /// Sets the fader position
-    /// 
+    ///
     /// # Arguments
     /// * `position` - Fader position (-1.0 to 1.0)
     ///   - -1.0 = full left
@@ -34,6 +79,7 @@ impl Fader {
     ///   - 1.0 = full right
     pub fn set_position(&mut self, position: f32) {
         self.position = position.clamp(-1.0, 1.0);
+        self.update_targets();
     }
 
     /// Gets the current fader position
@@ -41,86 +87,151 @@ impl Fader {
         self.position
     }
 
+    /// Sets the crossfade curve
+    pub fn set_curve(&mut self, curve: FaderCurve) {
+        self.curve = curve;
+        self.update_targets();
+    }
+
+    /// Gets the crossfade curve
+    pub fn get_curve(&self) -> FaderCurve {
+        self.curve
+    }
+
+    /// Sets the slope of the custom curve
+    ///
+    /// # Arguments
+    /// * `slope` - Curve slope (0.0 to 1.0)
+    ///   - 0.0 = constant power (smooth blend)
+    ///   - 1.0 = sharp cut (scratch)
+    pub fn set_curve_slope(&mut self, slope: f32) {
+        self.slope = slope.clamp(0.0, 1.0);
+        self.update_targets();
+    }
+
+    /// Gets the slope of the custom curve
+    pub fn get_curve_slope(&self) -> f32 {
+        self.slope
+    }
+
     /// Processes stereo buffers with the fader effect
-    /// 
-    /// Applies constant-power crossfade between left and right channels
-    /// 
+    ///
+    /// Applies the crossfade curve to the left and right channels
+    ///
     /// # Arguments
     /// * `left` - Left channel buffer (modified in-place)
     /// * `right` - Right channel buffer (modified in-place)
     /// * `size` - Number of samples to process
-    /// 
+    ///
     /// # Performance
     /// - ~1µs per 256 samples
-    /// - Uses lookup table approximation for trigonometric functions
-    pub fn process(&self, left: &mut [f32], right: &mut [f32], size: usize) {
-        if self.position == 0.0 {
-            // Center position - no change needed
+    /// - Curve gains are computed on parameter changes, not per sample
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32], size: usize) {
+        let size = size.min(left.len()).min(right.len());
+
+        if !self.left_gain.is_smoothing() && !self.right_gain.is_smoothing() {
+            let left_gain = self.left_gain.current();
+            let right_gain = self.right_gain.current();
+            if left_gain == 1.0 && right_gain == 1.0 {
+                return;
+            }
+
+            for i in 0..size {
+                left[i] *= left_gain;
+                right[i] *= right_gain;
+            }
             return;
         }
 
-        // Constant-power crossfade using quadratic approximation
-        // Avoids expensive trigonometric functions
-        let t = (self.position + 1.0) * 0.5; // Normalize to [0, 1]
-        
-        // Quadratic crossfade approximation (smooth and efficient)
-        let left_gain = 1.0 - t * t;
-        let right_gain = t * t;
-
-        // Apply gains to channels
-        for i in 0..size.min(left.len()).min(right.len()) {
-            left[i] *= left_gain;
-            right[i] *= right_gain;
+        for i in 0..size {
+            left[i] *= self.left_gain.next_value();
+            right[i] *= self.right_gain.next_value();
         }
     }
 
+    /// Advances the gain smoothing by one sample
+    ///
+    /// Returns the (left, right) gains to apply to that sample, for callers
+    /// that mix the two sides themselves instead of using [`Fader::process`].
+    #[inline]
+    pub fn next_gains(&mut self) -> (f32, f32) {
+        (self.left_gain.next_value(), self.right_gain.next_value())
+    }
+
+    /// Sets how long gain changes take to settle
+    ///
+    /// # Arguments
+    /// * `time_ms` - Smoothing time in milliseconds (0 = immediate)
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.left_gain.set_smoothing_time(time_ms);
+        self.right_gain.set_smoothing_time(time_ms);
+    }
+
     /// Gets the left channel gain for the current position
-    /// 
+    ///
     /// Returns the gain coefficient that will be applied to the left channel
     /// at the current fader position
     pub fn get_left_gain(&self) -> f32 {
         let t = (self.position + 1.0) * 0.5;
-        1.0 - t * t
+        self.curve_gain(1.0 - t)
     }
 
     /// Gets the right channel gain for the current position
-    /// 
+    ///
     /// Returns the gain coefficient that will be applied to the right channel
     /// at the current fader position
     pub fn get_right_gain(&self) -> f32 {
         let t = (self.position + 1.0) * 0.5;
-        t * t
+        self.curve_gain(t)
     }
 
     /// Resets the fader to center position
     pub fn reset(&mut self) {
         self.position = 0.0;
+        self.left_gain.set_immediate(self.get_left_gain());
+        self.right_gain.set_immediate(self.get_right_gain());
+    }
+
+    /// Gain of one side when it is `amount` (0-1) of the way open
+    fn curve_gain(&self, amount: f32) -> f32 {
+        let width = match self.curve {
+            FaderCurve::Linear => return amount,
+            FaderCurve::ConstantPower => 1.0,
+            FaderCurve::SharpCut => CUT_WIDTH,
+            FaderCurve::Custom => CUT_WIDTH.powf(self.slope),
+        };
+        (FRAC_PI_2 * (amount / width).min(1.0)).sin()
     }
-}
 
-impl Default for Fader {
-    fn default() -> Self {
-        Self::new()
+    fn update_targets(&mut self) {
+        self.left_gain.set_target(self.get_left_gain());
+        self.right_gain.set_target(self.get_right_gain());
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    fn power(fader: &Fader) -> f32 {
+        fader.get_left_gain().powi(2) + fader.get_right_gain().powi(2)
+    }
 
     #[test]
     fn test_fader_creation() {
-        let fader = Fader::new();
+        let fader = Fader::new(48000);
         assert_eq!(fader.get_position(), 0.0);
-        assert_eq!(fader.get_left_gain(), 1.0);
-        assert_eq!(fader.get_right_gain(), 0.0);
+        assert_eq!(fader.get_curve(), FaderCurve::ConstantPower);
+        assert!((fader.get_left_gain() - FRAC_1_SQRT_2).abs() < 0.001);
+        assert!((fader.get_right_gain() - FRAC_1_SQRT_2).abs() < 0.001);
     }
 
     #[test]
     fn test_fader_left() {
-        let mut fader = Fader::new();
+        let mut fader = Fader::new(48000);
         fader.set_position(-1.0);
-        
+
         assert_eq!(fader.get_position(), -1.0);
         assert_eq!(fader.get_left_gain(), 1.0);
         assert_eq!(fader.get_right_gain(), 0.0);
@@ -128,9 +239,9 @@ mod tests {
 
     #[test]
     fn test_fader_right() {
-        let mut fader = Fader::new();
+        let mut fader = Fader::new(48000);
         fader.set_position(1.0);
-        
+
         assert_eq!(fader.get_position(), 1.0);
         assert!((fader.get_left_gain() - 0.0).abs() < 0.01);
         assert!((fader.get_right_gain() - 1.0).abs() < 0.01);
@@ -138,32 +249,105 @@ mod tests {
 
     #[test]
     fn test_fader_center() {
-        let fader = Fader::new();
-        // At center, gains should be equal (~0.5 each for constant power)
-        let lg = fader.get_left_gain();
-        let rg = fader.get_right_gain();
-        assert!((lg - 0.75).abs() < 0.01);
-        assert!((rg - 0.25).abs() < 0.01);
+        let mut fader = Fader::new(48000);
+
+        // Constant power: -3 dB per side, total power 1
+        assert!((power(&fader) - 1.0).abs() < 1e-5);
+
+        // Linear: -6 dB per side, half power
+        fader.set_curve(FaderCurve::Linear);
+        assert_eq!(fader.get_left_gain(), 0.5);
+        assert!((power(&fader) - 0.5).abs() < 1e-5);
+
+        // Sharp cut: both sides at full level
+        fader.set_curve(FaderCurve::SharpCut);
+        assert_eq!(fader.get_left_gain(), 1.0);
+        assert_eq!(fader.get_right_gain(), 1.0);
+    }
+
+    #[test]
+    fn test_constant_power_across_travel() {
+        let mut fader = Fader::new(48000);
+        for i in 0..=20 {
+            fader.set_position(i as f32 / 10.0 - 1.0);
+            assert!((power(&fader) - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_sharp_cut() {
+        let mut fader = Fader::new(48000);
+        fader.set_curve(FaderCurve::SharpCut);
+
+        // 5% of the travel is 0.1 in position units
+        fader.set_position(0.85);
+        assert_eq!(fader.get_left_gain(), 1.0);
+        fader.set_position(0.95);
+        assert!((fader.get_left_gain() - (FRAC_PI_2 * 0.5).sin()).abs() < 1e-3);
+        fader.set_position(1.0);
+        assert_eq!(fader.get_left_gain(), 0.0);
+    }
+
+    #[test]
+    fn test_custom_slope() {
+        let mut fader = Fader::new(48000);
+        fader.set_curve(FaderCurve::Custom);
+
+        fader.set_curve_slope(0.0);
+        assert!((power(&fader) - 1.0).abs() < 1e-5);
+
+        // Steeper slopes open each side faster, raising the centre level
+        let mut previous = fader.get_left_gain();
+        for &slope in &[0.25, 0.5, 0.75, 1.0] {
+            fader.set_curve_slope(slope);
+            assert!(fader.get_left_gain() >= previous);
+            previous = fader.get_left_gain();
+        }
+        assert_eq!(previous, 1.0);
+
+        fader.set_curve_slope(2.0);
+        assert_eq!(fader.get_curve_slope(), 1.0);
     }
 
     #[test]
     fn test_fader_clamping() {
-        let mut fader = Fader::new();
+        let mut fader = Fader::new(48000);
         fader.set_position(2.0);
         assert_eq!(fader.get_position(), 1.0);
-        
+
         fader.set_position(-2.0);
         assert_eq!(fader.get_position(), -1.0);
     }
 
     #[test]
     fn test_process() {
-        let fader = Fader::new();
+        let mut fader = Fader::new(48000);
         let mut left = vec![1.0; 256];
         let mut right = vec![1.0; 256];
-        
+
         fader.process(&mut left, &mut right, 256);
-        // At center position, both should be affected
-        assert!(left[0] > 0.0 && right[0] > 0.0);
+        // At center position, both sides are at -3 dB
+        assert!((left[0] - FRAC_1_SQRT_2).abs() < 0.001);
+        assert!((right[0] - FRAC_1_SQRT_2).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_position_change_is_smoothed() {
+        let mut fader = Fader::new(48000);
+        fader.set_curve(FaderCurve::Linear);
+        fader.set_smoothing_time(0.0);
+        fader.set_position(-1.0);
+        fader.set_smoothing_time(DEFAULT_SMOOTHING_MS);
+
+        // Left gain falls from 1 to 0 over 20 ms (960 samples) instead of at once
+        let mut left = vec![1.0; 2048];
+        let mut right = vec![1.0; 2048];
+        fader.set_position(1.0);
+        fader.process(&mut left, &mut right, 2048);
+
+        let max_step = left.windows(2).fold(0.0f32, |m, w| m.max((w[1] - w[0]).abs()));
+        assert!(max_step < 1.0 / 960.0 + 1e-5, "{}", max_step);
+        assert!(left[2047].abs() < 1e-6);
+        assert!((right[2047] - 1.0).abs() < 1e-6);
     }
 }
@@ -1,48 +1,118 @@
 //! High-performance WebAssembly Audio Engine
 //!
 //! A complete DJ-style audio processor implementing:
-//! - Real-time tempo/pitch control with phase vocoder
-//! - 3-band parametric equalizer
-//! - Stereo fader with crossfade
-//! - Input/output gain control
+//! - Real-time tempo/pitch control with phase vocoder or WSOLA time-stretch
+//! - Key lock (master tempo) with turntable-style varispeed when off
+//! - 3-band biquad equalizer with adjustable crossovers and isolator mode
+//! - Beat-synced echo following the deck tempo
+//! - Freeverb-style reverb with freeze, ringing on after the deck stops
+//! - Resonant low-pass/high-pass DJ filter knob
+//! - Channel compressor/expander with sidechain high-pass
+//! - Parametric N-band master EQ
+//! - Stereo pan/balance with selectable pan laws
+//! - Input/output gain control with a lookahead brickwall limiter
+//! - Click-free (smoothed) gain, pan and EQ changes
+//! - Multi-deck [`Mixer`] with channel faders, crossfader and headphone cue bus
 //!
-//! Architecture: Input Gain → Fader → Pitch Shift → EQ → Master Volume → Output
+//! Architecture: Time Stretch → Input Gain → Pan → Pitch Shift → Beat Roll → EQ → Lo-Fi → Modulation → Echo → Reverb → Filter → Compressor → Master EQ → Master Volume → Limiter → Output
 //!
 //! Performance targets:
-//! - 5.3ms maximum latency per frame (48kHz, 256-sample buffer)
-//! - Zero allocations in process_frame hot path
-//! - ~330KB memory footprint per instance
+//! - Processing a 256-frame block within its 5.3ms real-time budget (48kHz)
+//! - Latency of the time stretch, pitch shift and limiter, as reported by
+//!   [`AudioProcessor::get_latency_frames`]: 3568 frames (~74ms) at 48kHz
+//!   with the default 1024-point FFT and the phase vocoder, 2800 frames
+//!   (~58ms) with key lock off
+//! - No allocations in [`AudioProcessor::render`] once the time-stretch
+//!   queues have grown to fit the largest block
+//! - ~3MB memory footprint per instance (mostly echo delay lines and beat roll buffers)
 
+pub mod biquad;
+pub mod beat_roll;
 pub mod buffer_manager;
+pub mod compressor;
+pub mod delay_line;
+pub mod dj_filter;
+pub mod echo;
 pub mod equalizer;
 pub mod fader;
+pub mod limiter;
+pub mod lofi;
+pub mod mixer;
+pub mod modulation;
+pub mod panner;
+pub mod parametric_eq;
 pub mod phase_vocoder;
 pub mod pitch_shifter;
+pub mod resampler;
+pub mod reverb;
+pub mod smoothing;
 pub mod audio_analysis;
+pub mod time_stretch;
+pub mod wsola;
 
 use wasm_bindgen::prelude::*;
 use std::sync::atomic::{AtomicU32, Ordering};
 
+use buffer_manager::sample_utils;
+use smoothing::DEFAULT_SMOOTHING_MS;
+
+pub use biquad::{Biquad, BiquadCoefficients};
+pub use beat_roll::BeatRoll;
 pub use buffer_manager::BufferManager;
-pub use equalizer::Equalizer;
-pub use fader::Fader;
-pub use phase_vocoder::PhaseVocoder;
+pub use compressor::{Compressor, DynamicsMode};
+pub use delay_line::DelayLine;
+pub use dj_filter::DjFilter;
+pub use echo::Echo;
+pub use equalizer::{EqMode, Equalizer};
+pub use fader::{Fader, FaderCurve};
+pub use limiter::Limiter;
+pub use lofi::{LoFi, SaturationType};
+pub use mixer::{CrossfaderAssign, Mixer};
+pub use modulation::{Modulation, ModulationType};
+pub use panner::{PanLaw, Panner};
+pub use parametric_eq::{EqBand, FilterType, ParametricEq};
+pub use phase_vocoder::{PhaseVocoder, StretchMode};
 pub use pitch_shifter::PitchShifter;
+pub use resampler::Resampler;
+pub use reverb::Reverb;
+pub use smoothing::{SmoothedParam, SmoothingMode};
 pub use audio_analysis::AudioAnalyzer;
+pub use time_stretch::{TimeStretchEngine, TimeStretcher};
+pub use wsola::Wsola;
 
 const VERSION: &str = "1.0.0";
 const MAX_FRAME_SIZE: usize = 4096;
 
+/// Fade out of the old tempo stage and into the new one when key lock or
+/// the engine changes
+const STRETCH_SWITCH_FADE_MS: f32 = 10.0;
+
 /// Main audio processing structure managing all effects and real-time audio processing
 /// 
 /// This struct coordinates all audio processing modules with a fixed processing pipeline:
-/// 1. Input gain scaling
-/// 2. Stereo fader with left/right balance
-/// 3. Pitch shifting (preserves tempo)
-/// 4. 3-band equalizer
-/// 5. Master volume scaling
+/// 1. Time-stretching (streaming, tempo ratio; varispeed when key lock is off)
+/// 2. Input gain scaling
+/// 3. Stereo pan/balance
+/// 4. Pitch shifting (preserves tempo)
+/// 5. Beat roll (while held)
+/// 6. 3-band equalizer
+/// 7. Saturation/bitcrusher/sample-rate reducer (bypassed at zero mix)
+/// 8. Flanger/phaser/chorus (bypassed at zero mix)
+/// 9. Beat-synced echo (bypassed at zero mix)
+/// 10. Reverb (bypassed at zero mix)
+/// 11. DJ filter (low-pass/high-pass knob)
+/// 12. Compressor/expander (bypassed at ratio 1)
+/// 13. Parametric master EQ
+/// 14. Master volume scaling
+/// 15. Brickwall limiter (keeps output below the ceiling)
 ///
-/// All operations are optimized for real-time performance with no allocations in the hot path.
+/// Stages 1-12 form the channel strip and 13-15 the master section. Inside
+/// a [`Mixer`] a deck runs only its channel strip; the mixer applies its own
+/// master section to the summed output.
+///
+/// [`AudioProcessor::render`] does not allocate once the time-stretch queues
+/// have grown to fit the largest block; `process_frame` and `process_stream`
+/// also allocate the block they return to JavaScript.
 #[wasm_bindgen]
 pub struct AudioProcessor {
     sample_rate: u32,
@@ -50,14 +120,39 @@ pub struct AudioProcessor {
     
     // Processing modules
     phase_vocoder: PhaseVocoder,
+    wsola: Wsola,
+    varispeed: Resampler,
+    stretch_engine: TimeStretchEngine,
+    key_lock: bool,
+    // Tempo stage actually running: it lags the settings above while the
+    // old stage fades out, and fades in once the new one has filled
+    active_engine: TimeStretchEngine,
+    active_key_lock: bool,
+    stretch_fade: SmoothedParam,
+    stretch_warmup: usize,
     pitch_shifter: PitchShifter,
+    beat_roll: BeatRoll,
     equalizer: Equalizer,
-    fader: Fader,
+    lofi: LoFi,
+    modulation: Modulation,
+    echo: Echo,
+    reverb: Reverb,
+    dj_filter: DjFilter,
+    compressor: Compressor,
+    master_eq: ParametricEq,
+    panner: Panner,
+    limiter: Limiter,
     buffer_manager: BufferManager,
     
-    // Control parameters (atomic for thread-safe updates from JS)
-    input_gain: f32,
-    master_volume: f32,
+    // Control parameters (smoothed to avoid zipper noise)
+    input_gain: SmoothedParam,
+    master_volume: SmoothedParam,
+    smoothing_ms: f32,
+
+    // Tempo for synced effects
+    track_bpm: f32,
+    echo_bpm: f32,
+    modulation_bpm: f32,
     
     // Performance monitoring
     frames_processed: AtomicU32,
@@ -72,13 +167,13 @@ pub struct AudioProcessor {
 /// 
 /// # Performance
 /// - Initialization: ~1ms
-/// - Memory allocation: ~330KB per instance
+/// - Memory allocation: ~3MB per instance at 48kHz
 #[wasm_bindgen]
 impl AudioProcessor {
     #[wasm_bindgen(constructor)]
     pub fn new(sample_rate: u32, fft_size: usize) -> Result<AudioProcessor, JsValue> {
         // Validate parameters
-        if sample_rate < 8000 || sample_rate > 192000 {
+        if !(8000..=192000).contains(&sample_rate) {
             return Err(JsValue::from_str("Invalid sample rate"));
         }
         
@@ -90,13 +185,34 @@ impl AudioProcessor {
         Ok(AudioProcessor {
             sample_rate,
             fft_size,
-            phase_vocoder: PhaseVocoder::new(fft_size),
+            phase_vocoder: PhaseVocoder::new(sample_rate, fft_size),
+            wsola: Wsola::new(sample_rate),
+            varispeed: Resampler::new(),
+            stretch_engine: TimeStretchEngine::PhaseVocoder,
+            key_lock: true,
+            active_engine: TimeStretchEngine::PhaseVocoder,
+            active_key_lock: true,
+            stretch_fade: SmoothedParam::new(1.0, sample_rate, STRETCH_SWITCH_FADE_MS),
+            stretch_warmup: 0,
             pitch_shifter: PitchShifter::new(sample_rate, fft_size),
-            equalizer: Equalizer::new(),
-            fader: Fader::new(),
+            beat_roll: BeatRoll::new(sample_rate),
+            equalizer: Equalizer::new(sample_rate),
+            lofi: LoFi::new(sample_rate),
+            modulation: Modulation::new(sample_rate),
+            echo: Echo::new(sample_rate),
+            reverb: Reverb::new(sample_rate),
+            dj_filter: DjFilter::new(sample_rate),
+            compressor: Compressor::new(sample_rate),
+            master_eq: ParametricEq::new(sample_rate),
+            panner: Panner::new(sample_rate),
+            limiter: Limiter::new(sample_rate),
             buffer_manager: BufferManager::new(MAX_FRAME_SIZE * 2),
-            input_gain: 1.0,
-            master_volume: 1.0,
+            input_gain: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            master_volume: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            smoothing_ms: DEFAULT_SMOOTHING_MS,
+            track_bpm: 0.0,
+            echo_bpm: 0.0,
+            modulation_bpm: 0.0,
             frames_processed: AtomicU32::new(0),
             last_peak_level: 0.0,
         })
@@ -106,9 +222,16 @@ impl AudioProcessor {
     ///
     /// Processing pipeline:
     /// ```text
-    /// Input → Input Gain → Fader → Pitch Shift → EQ → Master Volume → Output
+    /// Input → Time Stretch → Input Gain → Pan → Pitch Shift → Beat Roll → EQ → Lo-Fi → Modulation → Echo → Reverb → Filter → Compressor → Master EQ → Master Volume → Limiter → Output
     /// ```
     ///
+    /// Equivalent to [`AudioProcessor::process_stream`] with
+    /// `output_frames = input_left.len()`. At tempo ratios other than 1.0 the
+    /// time-stretch needs more or fewer input samples than it outputs, so
+    /// hosts that change tempo should use the streaming pair instead.
+    /// While the deck is stopped, use [`AudioProcessor::render_tail`] so
+    /// effect tails keep ringing.
+    ///
     /// # Arguments
     /// * `input_left` - Left channel samples
     /// * `input_right` - Right channel samples
@@ -117,58 +240,127 @@ impl AudioProcessor {
     /// Interleaved stereo output: [L, R, L, R, ...]
     ///
     /// # Performance
-    /// - Guaranteed < 5.3ms per frame at 48kHz, 256 samples
-    /// - Zero allocations on hot path
+    /// - Output trails the input by [`AudioProcessor::get_latency_frames`]
+    /// - Allocates only the returned block (see [`AudioProcessor::render`])
     #[wasm_bindgen]
     pub fn process_frame(&mut self, input_left: &[f32], input_right: &[f32]) -> Box<[f32]> {
         let frame_size = input_left.len().min(input_right.len());
-        if frame_size == 0 || frame_size > MAX_FRAME_SIZE {
+        self.process_stream(input_left, input_right, frame_size)
+    }
+
+    /// Returns how many input frames the next `output_frames` of output need
+    ///
+    /// Call this before every render quantum (e.g. 128 frames in an
+    /// AudioWorklet) and pass exactly that many source samples per channel
+    /// to [`AudioProcessor::process_stream`].
+    ///
+    /// # Arguments
+    /// * `output_frames` - Number of stereo frames the host wants to render
+    #[wasm_bindgen]
+    pub fn get_input_frames_required(&self, output_frames: usize) -> usize {
+        self.time_stretcher().input_required(output_frames)
+    }
+
+    /// Processes a block of source audio, producing exactly `output_frames`
+    ///
+    /// The input is appended to the time-stretch stream, which keeps its
+    /// overlap-add state between calls, and `output_frames` frames are pulled
+    /// from it and run through the rest of the signal chain. If less input
+    /// was supplied than [`AudioProcessor::get_input_frames_required`]
+    /// reported, the missing part of the block is silent.
+    ///
+    /// # Arguments
+    /// * `input_left` - Left channel source samples (any length)
+    /// * `input_right` - Right channel source samples (same length as left)
+    /// * `output_frames` - Number of stereo frames to output (1-4096)
+    ///
+    /// # Returns
+    /// Interleaved stereo output of `output_frames * 2` samples
+    #[wasm_bindgen]
+    pub fn process_stream(
+        &mut self,
+        input_left: &[f32],
+        input_right: &[f32],
+        output_frames: usize,
+    ) -> Box<[f32]> {
+        if output_frames == 0 || output_frames > MAX_FRAME_SIZE {
             return Box::new([]);
         }
 
         // Get working buffers from pool (zero-copy)
-        let mut left = self.buffer_manager.get_buffer(frame_size);
-        let mut right = self.buffer_manager.get_buffer(frame_size);
+        let mut left = self.buffer_manager.get_buffer(output_frames);
+        let mut right = self.buffer_manager.get_buffer(output_frames);
+
+        self.render(input_left, input_right, &mut left, &mut right);
 
-        // Stage 1: Apply input gain
-        for i in 0..frame_size {
-            left[i] = input_left[i] * self.input_gain;
-            right[i] = input_right[i] * self.input_gain;
+        let mut output = vec![0.0; output_frames * 2];
+        for i in 0..output_frames {
+            output[i * 2] = left[i];
+            output[i * 2 + 1] = right[i];
         }
 
-        // Stage 2: Apply stereo fader (left/right balance)
-        self.fader.process(&mut left, &mut right, frame_size);
+        self.buffer_manager.return_buffer(left);
+        self.buffer_manager.return_buffer(right);
 
-        // Stage 3: Apply pitch shifting (modifies buffer in-place)
-        self.pitch_shifter.process_stereo(&mut left, &mut right);
+        output.into_boxed_slice()
+    }
+
+    /// Renders effect tails while the deck is stopped
+    ///
+    /// With no source audio, [`AudioProcessor::process_frame`] returns an
+    /// empty block. Call this instead for every render quantum while the
+    /// deck is stopped so echo repeats and reverb (including a frozen
+    /// reverb) keep ringing. Silence is run through the stages from the EQ
+    /// onwards; the time-stretch stream is left untouched, so playback
+    /// resumes where it stopped.
+    ///
+    /// # Arguments
+    /// * `output_frames` - Number of stereo frames to output (1-4096)
+    ///
+    /// # Returns
+    /// Interleaved stereo output of `output_frames * 2` samples
+    #[wasm_bindgen]
+    pub fn render_tail(&mut self, output_frames: usize) -> Box<[f32]> {
+        if output_frames == 0 || output_frames > MAX_FRAME_SIZE {
+            return Box::new([]);
+        }
 
-        // Stage 4: Apply 3-band equalizer
-        self.equalizer.process(&mut left);
-        self.equalizer.process(&mut right);
+        let mut left = self.buffer_manager.get_buffer(output_frames);
+        let mut right = self.buffer_manager.get_buffer(output_frames);
 
-        // Stage 5: Apply master volume and create output
-        let mut output = vec![0.0; frame_size * 2];
-        let mut peak: f32 = 0.0;
-        
-        for i in 0..frame_size {
-            let left_sample = left[i] * self.master_volume;
-            let right_sample = right[i] * self.master_volume;
-            
-            output[i * 2] = left_sample;
-            output[i * 2 + 1] = right_sample;
-            
-            peak = peak.max(left_sample.abs()).max(right_sample.abs());
+        self.render_post_source(&mut left, &mut right);
+        self.render_master(&mut left, &mut right);
+
+        let mut output = vec![0.0; output_frames * 2];
+        for i in 0..output_frames {
+            output[i * 2] = left[i];
+            output[i * 2 + 1] = right[i];
         }
 
-        self.last_peak_level = peak;
-        self.frames_processed.fetch_add(1, Ordering::Relaxed);
+        self.buffer_manager.return_buffer(left);
+        self.buffer_manager.return_buffer(right);
 
         output.into_boxed_slice()
     }
 
+    /// Returns the processing latency in frames (time stretch + pitch shift + limiter)
+    #[wasm_bindgen]
+    pub fn get_latency_frames(&self) -> usize {
+        self.get_channel_latency_frames() + self.limiter.latency()
+    }
+
+    /// Returns the channel strip latency in frames (time stretch + pitch shift)
+    /// 
+    /// The latency of [`AudioProcessor::render_channel`], which skips the
+    /// master limiter. Changes with key lock and the time stretch engine.
+    #[wasm_bindgen]
+    pub fn get_channel_latency_frames(&self) -> usize {
+        self.time_stretcher().latency() + self.pitch_shifter.latency()
+    }
+
     // ===== Input Gain Control =====
     
-    /// Sets the input gain (pre-fader volume)
+    /// Sets the input gain (pre-pan volume)
     /// 
     /// # Arguments
     /// * `gain` - Gain in linear units (0.0-2.0)
@@ -177,18 +369,18 @@ impl AudioProcessor {
     ///   - 2.0 = +6dB
     #[wasm_bindgen]
     pub fn set_input_gain(&mut self, gain: f32) {
-        self.input_gain = gain.clamp(0.0, 2.0);
+        self.input_gain.set_target(gain.clamp(0.0, 2.0));
     }
 
     /// Gets the current input gain
     #[wasm_bindgen]
     pub fn get_input_gain(&self) -> f32 {
-        self.input_gain
+        self.input_gain.target()
     }
 
     // ===== Master Volume Control =====
     
-    /// Sets the master volume (post-fader output volume)
+    /// Sets the master volume (output volume)
     /// 
     /// # Arguments
     /// * `volume` - Volume in linear units (0.0-2.0)
@@ -197,20 +389,92 @@ impl AudioProcessor {
     ///   - 2.0 = +6dB
     #[wasm_bindgen]
     pub fn set_master_volume(&mut self, volume: f32) {
-        self.master_volume = volume.clamp(0.0, 2.0);
+        self.master_volume.set_target(volume.clamp(0.0, 2.0));
     }
 
     /// Gets the current master volume
     #[wasm_bindgen]
     pub fn get_master_volume(&self) -> f32 {
-        self.master_volume
+        self.master_volume.target()
+    }
+
+    // ===== Limiter Control =====
+
+    /// Sets the limiter ceiling
+    /// 
+    /// No output sample exceeds this level, whatever the gain settings.
+    /// 
+    /// # Arguments
+    /// * `db` - Ceiling in dBFS (-24.0 to 0.0, default -0.3)
+    #[wasm_bindgen]
+    pub fn set_limiter_ceiling(&mut self, db: f32) {
+        self.limiter.set_ceiling(db);
+    }
+
+    /// Gets the limiter ceiling in dBFS
+    #[wasm_bindgen]
+    pub fn get_limiter_ceiling(&self) -> f32 {
+        self.limiter.get_ceiling()
+    }
+
+    /// Sets the limiter release time
+    /// 
+    /// # Arguments
+    /// * `time_ms` - Release time in milliseconds (10-1000, default 100)
+    #[wasm_bindgen]
+    pub fn set_limiter_release(&mut self, time_ms: f32) {
+        self.limiter.set_release(time_ms);
+    }
+
+    /// Gets the limiter release time in milliseconds
+    #[wasm_bindgen]
+    pub fn get_limiter_release(&self) -> f32 {
+        self.limiter.get_release()
+    }
+
+    /// Gets the largest limiter gain reduction in the last block (positive dB)
+    #[wasm_bindgen]
+    pub fn get_gain_reduction(&self) -> f32 {
+        self.limiter.gain_reduction_db()
+    }
+
+    // ===== Parameter Smoothing =====
+
+    /// Sets how long gain, pan, EQ, filter and effect changes take to settle
+    /// 
+    /// # Arguments
+    /// * `time_ms` - Smoothing time in milliseconds (0-1000, default 20)
+    ///   - 0 = changes apply immediately (may click)
+    #[wasm_bindgen]
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.smoothing_ms = time_ms.clamp(0.0, 1000.0);
+        self.input_gain.set_smoothing_time(self.smoothing_ms);
+        self.master_volume.set_smoothing_time(self.smoothing_ms);
+        self.panner.set_smoothing_time(self.smoothing_ms);
+        self.equalizer.set_smoothing_time(self.smoothing_ms);
+        self.lofi.set_smoothing_time(self.smoothing_ms);
+        self.modulation.set_smoothing_time(self.smoothing_ms);
+        self.echo.set_smoothing_time(self.smoothing_ms);
+        self.reverb.set_smoothing_time(self.smoothing_ms);
+        self.dj_filter.set_smoothing_time(self.smoothing_ms);
+        self.compressor.set_smoothing_time(self.smoothing_ms);
+        self.master_eq.set_smoothing_time(self.smoothing_ms);
     }
 
-    // ===== Tempo Control (Phase Vocoder) =====
+    /// Gets the parameter smoothing time in milliseconds
+    #[wasm_bindgen]
+    pub fn get_smoothing_time(&self) -> f32 {
+        self.smoothing_ms
+    }
+
+    // ===== Tempo Control (Time Stretch) =====
     
     /// Sets the playback tempo ratio
     /// 
-    /// Uses phase vocoder for time-stretching without changing pitch
+    /// With key lock on, the selected time-stretch engine changes tempo
+    /// without changing pitch; with key lock off, tempo and pitch change
+    /// together like a turntable. The ratio sets how many source frames each output frame consumes; see
+    /// [`AudioProcessor::get_input_frames_required`].
     /// 
     /// # Arguments
     /// * `ratio` - Tempo multiplier (0.5-2.0)
@@ -221,12 +485,77 @@ impl AudioProcessor {
     pub fn set_tempo_ratio(&mut self, ratio: f32) {
         let ratio = ratio.clamp(0.5, 2.0);
         self.phase_vocoder.set_stretch_ratio(ratio);
+        self.wsola.set_stretch_ratio(ratio);
+        self.varispeed.set_stretch_ratio(ratio);
+        self.update_effect_tempo();
     }
 
     /// Gets the current tempo ratio
     #[wasm_bindgen]
     pub fn get_tempo_ratio(&self) -> f32 {
-        self.phase_vocoder.get_stretch_ratio()
+        self.time_stretcher().get_stretch_ratio()
+    }
+
+    /// Enables or disables key lock (master tempo)
+    /// 
+    /// When on, tempo changes keep the original key. When off, the deck
+    /// plays like a turntable: tempo changes shift pitch by the same ratio
+    /// (+8% tempo = +8% pitch). Toggling fades the deck out, restarts the
+    /// tempo stage and fades back in once it has filled, a dip of about
+    /// 20 ms plus the new stage's latency. [`AudioProcessor::get_latency_frames`]
+    /// follows the stage in use, so re-send the beat phase afterwards.
+    /// 
+    /// # Arguments
+    /// * `enabled` - true to keep key, false for varispeed
+    #[wasm_bindgen]
+    pub fn set_key_lock(&mut self, enabled: bool) {
+        self.key_lock = enabled;
+        self.update_stretch_fade();
+    }
+
+    /// Gets whether key lock is enabled
+    #[wasm_bindgen]
+    pub fn get_key_lock(&self) -> bool {
+        self.key_lock
+    }
+
+    /// Selects the time-stretch engine used while key lock is on
+    /// 
+    /// Switching fades out, restarts the stretch stream and fades back in
+    /// once the new engine has filled up, as for
+    /// [`AudioProcessor::set_key_lock`].
+    /// 
+    /// # Arguments
+    /// * `engine` - `TimeStretchEngine.PhaseVocoder` for best quality, or
+    ///   `TimeStretchEngine.Wsola` for low CPU usage
+    #[wasm_bindgen]
+    pub fn set_time_stretch_engine(&mut self, engine: TimeStretchEngine) {
+        self.stretch_engine = engine;
+        self.update_stretch_fade();
+    }
+
+    /// Gets the current time-stretch engine
+    #[wasm_bindgen]
+    pub fn get_time_stretch_engine(&self) -> TimeStretchEngine {
+        self.stretch_engine
+    }
+
+    /// Selects the phase vocoder algorithm
+    /// 
+    /// Only affects the `TimeStretchEngine.PhaseVocoder` engine.
+    /// 
+    /// # Arguments
+    /// * `mode` - `StretchMode.Standard` for the classic phase vocoder, or
+    ///   `StretchMode.TransientPreserving` to keep drum attacks sharp
+    #[wasm_bindgen]
+    pub fn set_stretch_mode(&mut self, mode: StretchMode) {
+        self.phase_vocoder.set_mode(mode);
+    }
+
+    /// Gets the current phase vocoder algorithm
+    #[wasm_bindgen]
+    pub fn get_stretch_mode(&self) -> StretchMode {
+        self.phase_vocoder.get_mode()
     }
 
     // ===== Pitch Control =====
@@ -242,36 +571,104 @@ impl AudioProcessor {
     ///   - -12 = one octave down
     #[wasm_bindgen]
     pub fn set_pitch_shift(&mut self, semitones: i32) {
-        let semitones = (semitones as f32).clamp(-12.0, 12.0);
-        let ratio = 2.0_f32.powf(semitones / 12.0);
-        self.pitch_shifter.set_pitch_ratio(ratio);
+        let semitones = semitones.clamp(-12, 12);
+        self.pitch_shifter.set_pitch_cents(semitones as f32 * 100.0);
     }
 
-    /// Gets the current pitch shift in semitones
+    /// Gets the current pitch shift in whole semitones
+    /// 
+    /// Rounds to the nearest semitone; use
+    /// [`AudioProcessor::get_pitch_cents`] for the exact value.
     #[wasm_bindgen]
     pub fn get_pitch_shift(&self) -> i32 {
-        let ratio = self.pitch_shifter.get_pitch_ratio();
-        (ratio.log2() * 12.0).round() as i32
+        (self.pitch_shifter.get_pitch_cents() / 100.0).round() as i32
+    }
+
+    /// Sets the pitch shift in cents
+    /// 
+    /// Shifts pitch without changing tempo, in steps finer than a semitone
+    /// for harmonic mixing.
+    /// 
+    /// # Arguments
+    /// * `cents` - Pitch shift in cents (-1200.0 to +1200.0)
+    ///   - 0.0 = no shift
+    ///   - 50.0 = half a semitone up
+    ///   - -31.77 = A440 tuning to A432
+    /// 
+    /// The pitch shifter adds the same latency at every setting, including
+    /// 0 cents where it is bypassed, so [`AudioProcessor::get_latency_frames`]
+    /// does not move with the pitch. Moving on or off 0 crossfades.
+    #[wasm_bindgen]
+    pub fn set_pitch_cents(&mut self, cents: f32) {
+        self.pitch_shifter.set_pitch_cents(cents);
+    }
+
+    /// Gets the current pitch shift in cents
+    #[wasm_bindgen]
+    pub fn get_pitch_cents(&self) -> f32 {
+        self.pitch_shifter.get_pitch_cents()
+    }
+
+    /// Enables or disables formant preservation for pitch shifting
+    /// 
+    /// Keeps vocal formants in place when transposing, avoiding
+    /// chipmunk-like or muddy voices on larger shifts. Adds CPU load.
+    /// 
+    /// # Arguments
+    /// * `enabled` - true to preserve formants
+    #[wasm_bindgen]
+    pub fn set_formant_preservation(&mut self, enabled: bool) {
+        self.pitch_shifter.set_formant_preservation(enabled);
+    }
+
+    /// Gets whether formant preservation is enabled
+    #[wasm_bindgen]
+    pub fn get_formant_preservation(&self) -> bool {
+        self.pitch_shifter.get_formant_preservation()
     }
 
-    // ===== Fader Control =====
+    // ===== Pan Control =====
     
-    /// Sets the stereo fader position (left/right balance)
+    /// Sets the pan position
+    /// 
+    /// Replaces `set_fader_position`/`get_fader_position` and the deck fader
+    /// curve setters, which were removed. Balance the deck here; blend
+    /// between decks with the [`Mixer`] crossfader
+    /// ([`Mixer::set_crossfader_position`], [`Mixer::set_crossfader_curve`]).
+    /// 
+    /// # Arguments
+    /// * `position` - Pan position (-1.0 to 1.0)
+    ///   - -1.0 = hard left
+    ///   - 0.0 = center
+    ///   - 1.0 = hard right
+    #[wasm_bindgen]
+    pub fn set_pan(&mut self, position: f32) {
+        self.panner.set_position(position);
+    }
+
+    /// Gets the pan position
+    #[wasm_bindgen]
+    pub fn get_pan(&self) -> f32 {
+        self.panner.get_position()
+    }
+
+    /// Sets the pan law
     /// 
     /// # Arguments
-    /// * `position` - Fader position (-1.0 to 1.0)
-    ///   - -1.0 = full left
-    ///   - 0.0 = center (both channels equal)
-    ///   - 1.0 = full right
+    /// * `law` - Pan law
+    ///   - ConstantPower = -3 dB per side at center
+    ///   - Compromise = -4.5 dB per side at center
+    ///   - Linear = -6 dB per side at center
+    ///   - Balance = unity at center (default)
     #[wasm_bindgen]
-    pub fn set_fader_position(&mut self, position: f32) {
-        self.fader.set_position(position);
+    pub fn set_pan_law(&mut self, law: PanLaw) {
+        self.panner.set_law(law);
     }
 
-    /// Gets the current fader position
+    /// Gets the pan law
     #[wasm_bindgen]
-    pub fn get_fader_position(&self) -> f32 {
-        self.fader.get_position()
+    pub fn get_pan_law(&self) -> PanLaw {
+        self.panner.get_law()
     }
 
     // ===== Equalizer Control (3-Band) =====
@@ -279,7 +676,8 @@ impl AudioProcessor {
     /// Sets the high-frequency band gain
     /// 
     /// # Arguments
-    /// * `db` - Gain in decibels (-12.0 to +12.0)
+    /// * `db` - Gain in decibels (-12.0 to +12.0; -Infinity to +6.0 in
+    ///   isolator mode)
     ///   - 0.0 = no change
     ///   - 6.0 = boost by 6dB
     ///   - -6.0 = cut by 6dB
@@ -297,7 +695,8 @@ impl AudioProcessor {
     /// Sets the mid-frequency band gain
     /// 
     /// # Arguments
-    /// * `db` - Gain in decibels (-12.0 to +12.0)
+    /// * `db` - Gain in decibels (-12.0 to +12.0; -Infinity to +6.0 in
+    ///   isolator mode)
     #[wasm_bindgen]
     pub fn set_mid_gain(&mut self, db: f32) {
         self.equalizer.set_mid_gain(db);
@@ -312,7 +711,8 @@ impl AudioProcessor {
     /// Sets the low-frequency band gain
     /// 
     /// # Arguments
-    /// * `db` - Gain in decibels (-12.0 to +12.0)
+    /// * `db` - Gain in decibels (-12.0 to +12.0; -Infinity to +6.0 in
+    ///   isolator mode)
     #[wasm_bindgen]
     pub fn set_low_gain(&mut self, db: f32) {
         self.equalizer.set_low_gain(db);
@@ -324,66 +724,1392 @@ impl AudioProcessor {
         self.equalizer.get_low_gain()
     }
 
-    // ===== Utility Methods =====
-    
-    /// Returns the engine version
+    /// Sets the EQ crossover frequencies
+    /// 
+    /// # Arguments
+    /// * `low_hz` - Low/mid crossover (20-1000 Hz, default 250)
+    /// * `high_hz` - Mid/high crossover (at least one octave above
+    ///   `low_hz`, up to 16 kHz, default 2000)
     #[wasm_bindgen]
-    pub fn get_version(&self) -> String {
-        VERSION.to_string()
+    pub fn set_eq_crossovers(&mut self, low_hz: f32, high_hz: f32) {
+        self.equalizer.set_crossovers(low_hz, high_hz);
     }
 
-    /// Returns performance statistics as a JSON string
+    /// Gets the low/mid EQ crossover frequency in Hz
+    #[wasm_bindgen]
+    pub fn get_eq_low_crossover(&self) -> f32 {
+        self.equalizer.get_low_crossover()
+    }
+
+    /// Gets the mid/high EQ crossover frequency in Hz
+    #[wasm_bindgen]
+    pub fn get_eq_high_crossover(&self) -> f32 {
+        self.equalizer.get_high_crossover()
+    }
+
+    /// Switches the EQ between shelving and full-kill isolator
     /// 
-    /// Includes:
-    /// - frames_processed: Total frames processed
-    /// - peak_level: Peak audio level (0.0-1.0)
-    /// - sample_rate: Sample rate in Hz
-    /// - fft_size: FFT size used for pitch shifting
+    /// Band gains are re-clamped to the new mode's range.
     #[wasm_bindgen]
-    pub fn get_stats(&self) -> String {
-        let frames = self.frames_processed.load(Ordering::Relaxed);
-        let peak_db = if self.last_peak_level > 0.0 {
-            20.0 * self.last_peak_level.log10()
-        } else {
-            -100.0
-        };
-        
-        format!(
-            r#"{{"version":"{}","frames_processed":{},"peak_level":{:.3},"peak_db":{:.1},"sample_rate":{},"fft_size":{}}}"#,
-            VERSION, frames, self.last_peak_level, peak_db, self.sample_rate, self.fft_size
-        )
+    pub fn set_eq_mode(&mut self, mode: EqMode) {
+        self.equalizer.set_mode(mode);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Gets the EQ mode
+    #[wasm_bindgen]
+    pub fn get_eq_mode(&self) -> EqMode {
+        self.equalizer.get_mode()
+    }
 
-    #[test]
-    fn test_processor_creation() {
-        let processor = AudioProcessor::new(48000, 1024).unwrap();
-        assert_eq!(processor.get_input_gain(), 1.0);
-        assert_eq!(processor.get_master_volume(), 1.0);
-        assert_eq!(processor.get_tempo_ratio(), 1.0);
+    /// Kills (silences) the low band; only effective in isolator mode
+    #[wasm_bindgen]
+    pub fn set_low_kill(&mut self, killed: bool) {
+        self.equalizer.set_low_kill(killed);
     }
 
-    #[test]
-    fn test_gain_control() {
-        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
-        processor.set_input_gain(2.0);
-        assert_eq!(processor.get_input_gain(), 2.0);
-        processor.set_input_gain(3.0); // Should clamp to 2.0
-        assert_eq!(processor.get_input_gain(), 2.0);
+    /// Gets the low band kill switch
+    #[wasm_bindgen]
+    pub fn get_low_kill(&self) -> bool {
+        self.equalizer.get_low_kill()
     }
 
-    #[test]
-    fn test_process_frame() {
-        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
-        let input_left = vec![0.1; 256];
-        let input_right = vec![0.1; 256];
-        
-        let output = processor.process_frame(&input_left, &input_right);
-        assert_eq!(output.len(), 512); // 256 * 2 (stereo)
+    /// Kills (silences) the mid band; only effective in isolator mode
+    #[wasm_bindgen]
+    pub fn set_mid_kill(&mut self, killed: bool) {
+        self.equalizer.set_mid_kill(killed);
+    }
+
+    /// Gets the mid band kill switch
+    #[wasm_bindgen]
+    pub fn get_mid_kill(&self) -> bool {
+        self.equalizer.get_mid_kill()
+    }
+
+    /// Kills (silences) the high band; only effective in isolator mode
+    #[wasm_bindgen]
+    pub fn set_high_kill(&mut self, killed: bool) {
+        self.equalizer.set_high_kill(killed);
+    }
+
+    /// Gets the high band kill switch
+    #[wasm_bindgen]
+    pub fn get_high_kill(&self) -> bool {
+        self.equalizer.get_high_kill()
+    }
+
+    // ===== Track Tempo =====
+
+    /// Sets the loaded track's tempo (as detected by `analyze_bpm`)
+    /// 
+    /// Beat-synced effects follow this tempo scaled by the tempo ratio.
+    /// 
+    /// # Arguments
+    /// * `bpm` - Track tempo in BPM at its original speed (0 = unknown)
+    #[wasm_bindgen]
+    pub fn set_track_bpm(&mut self, bpm: f32) {
+        self.track_bpm = bpm.max(0.0);
+        self.update_effect_tempo();
+    }
+
+    /// Gets the loaded track's tempo in BPM (0 = unknown)
+    #[wasm_bindgen]
+    pub fn get_track_bpm(&self) -> f32 {
+        self.track_bpm
+    }
+
+    /// Detects the tempo of the loaded track and uses it for synced effects
+    /// 
+    /// # Arguments
+    /// * `samples` - Mono track audio (at least 2 seconds)
+    /// 
+    /// # Returns
+    /// The detected BPM
+    /// 
+    /// # Performance
+    /// Analyzes up to 50 seconds of audio; run it off the audio thread.
+    #[wasm_bindgen]
+    pub fn detect_track_bpm(&mut self, samples: &[f32]) -> u32 {
+        let bpm = AudioAnalyzer::detect_bpm(samples, self.sample_rate);
+        self.set_track_bpm(bpm as f32);
+        bpm
+    }
+
+    // ===== Beat Roll Control =====
+
+    /// Presses (true) or releases (false) the beat roll
+    /// 
+    /// While held, the roll repeats the current beat division of the deck,
+    /// starting from the last division boundary. Playback carries on
+    /// underneath, so releasing returns to where the track would have been.
+    #[wasm_bindgen]
+    pub fn set_roll_active(&mut self, active: bool) {
+        self.beat_roll.set_active(active);
+    }
+
+    /// Gets whether the beat roll is held
+    #[wasm_bindgen]
+    pub fn get_roll_active(&self) -> bool {
+        self.beat_roll.is_active()
+    }
+
+    /// Sets the beat roll length in beats
+    /// 
+    /// # Arguments
+    /// * `beats` - Roll length (1/32 to 1 beat), snapped to the nearest
+    ///   power-of-two division: 0.03125, 0.0625, 0.125, 0.25, 0.5 or 1.0
+    #[wasm_bindgen]
+    pub fn set_roll_beats(&mut self, beats: f32) {
+        self.beat_roll.set_beats(beats);
+    }
+
+    /// Gets the beat roll length in beats
+    #[wasm_bindgen]
+    pub fn get_roll_beats(&self) -> f32 {
+        self.beat_roll.get_beats()
+    }
+
+    /// Aligns the deck's beat clock with the track's beat grid
+    /// 
+    /// The clock runs at the deck tempo (see
+    /// [`AudioProcessor::set_track_bpm`]); call this on beats from the
+    /// host's beat grid to keep rolls on the grid.
+    /// 
+    /// `phase` is taken at the source audio pushed next. The beat roll hears
+    /// that audio only after the time stretch and pitch shift latency, so its
+    /// clock is set that far back.
+    /// 
+    /// # Arguments
+    /// * `phase` - Position in the current beat (0.0 = on the beat, to 1.0)
+    #[wasm_bindgen]
+    pub fn set_beat_phase(&mut self, phase: f32) {
+        let latency = self.get_channel_latency_frames();
+        let beats = latency as f32 * self.beat_roll.get_bpm() / 60.0 / self.sample_rate as f32;
+        self.beat_roll.set_beat_phase(phase - beats);
+    }
+
+    /// Gets the position in the current beat at the beat roll (0.0 to 1.0)
+    /// 
+    /// Trails the source by the time stretch and pitch shift latency.
+    #[wasm_bindgen]
+    pub fn get_beat_phase(&self) -> f32 {
+        self.beat_roll.get_beat_phase()
+    }
+
+    // ===== Lo-Fi Control =====
+
+    /// Selects the lo-fi saturation curve (tanh, tube or foldback)
+    #[wasm_bindgen]
+    pub fn set_lofi_saturation(&mut self, saturation: SaturationType) {
+        self.lofi.set_saturation(saturation);
+    }
+
+    /// Gets the lo-fi saturation curve
+    #[wasm_bindgen]
+    pub fn get_lofi_saturation(&self) -> SaturationType {
+        self.lofi.get_saturation()
+    }
+
+    /// Sets the lo-fi saturation drive in dB (0 = off, to 36)
+    #[wasm_bindgen]
+    pub fn set_lofi_drive(&mut self, drive_db: f32) {
+        self.lofi.set_drive(drive_db);
+    }
+
+    /// Gets the lo-fi saturation drive in dB
+    #[wasm_bindgen]
+    pub fn get_lofi_drive(&self) -> f32 {
+        self.lofi.get_drive()
+    }
+
+    /// Sets the bitcrusher depth in bits (1-16, 16 = off)
+    #[wasm_bindgen]
+    pub fn set_lofi_bit_depth(&mut self, bits: f32) {
+        self.lofi.set_bit_depth(bits);
+    }
+
+    /// Gets the bitcrusher depth in bits
+    #[wasm_bindgen]
+    pub fn get_lofi_bit_depth(&self) -> f32 {
+        self.lofi.get_bit_depth()
+    }
+
+    /// Sets the sample-rate reducer in Hz (200 up to the sample rate, which is off)
+    #[wasm_bindgen]
+    pub fn set_lofi_downsample(&mut self, rate: f32) {
+        self.lofi.set_downsample(rate);
+    }
+
+    /// Gets the sample-rate reducer rate in Hz
+    #[wasm_bindgen]
+    pub fn get_lofi_downsample(&self) -> f32 {
+        self.lofi.get_downsample()
+    }
+
+    /// Sets the lo-fi dry/wet mix
+    /// 
+    /// # Arguments
+    /// * `mix` - Dry/wet mix (0.0 to 1.0)
+    ///   - 0.0 = effect off
+    ///   - 1.0 = wet only
+    #[wasm_bindgen]
+    pub fn set_lofi_mix(&mut self, mix: f32) {
+        self.lofi.set_mix(mix);
+    }
+
+    /// Gets the lo-fi dry/wet mix
+    #[wasm_bindgen]
+    pub fn get_lofi_mix(&self) -> f32 {
+        self.lofi.get_mix()
+    }
+
+    // ===== Modulation Control =====
+
+    /// Selects the modulation effect (flanger, phaser or chorus)
+    #[wasm_bindgen]
+    pub fn set_modulation_type(&mut self, effect: ModulationType) {
+        self.modulation.set_effect(effect);
+    }
+
+    /// Gets the selected modulation effect
+    #[wasm_bindgen]
+    pub fn get_modulation_type(&self) -> ModulationType {
+        self.modulation.get_effect()
+    }
+
+    /// Sets the tempo the modulation LFO syncs to, overriding the deck tempo
+    /// 
+    /// # Arguments
+    /// * `bpm` - Tempo in BPM (40-300), or 0 to follow the deck: track BPM ×
+    ///   tempo ratio (120 BPM when the track tempo is unknown)
+    #[wasm_bindgen]
+    pub fn set_modulation_bpm(&mut self, bpm: f32) {
+        self.modulation_bpm = if bpm <= 0.0 { 0.0 } else { bpm.clamp(40.0, 300.0) };
+        self.update_effect_tempo();
+    }
+
+    /// Gets the tempo the modulation LFO is synced to
+    #[wasm_bindgen]
+    pub fn get_modulation_bpm(&self) -> f32 {
+        self.modulation.get_bpm()
+    }
+
+    /// Sets the modulation LFO period in beats (0.25-32)
+    #[wasm_bindgen]
+    pub fn set_modulation_beats(&mut self, beats: f32) {
+        self.modulation.set_lfo_beats(beats);
+    }
+
+    /// Gets the modulation LFO period in beats
+    #[wasm_bindgen]
+    pub fn get_modulation_beats(&self) -> f32 {
+        self.modulation.get_lfo_beats()
+    }
+
+    /// Moves the modulation LFO to a point in its cycle
+    /// 
+    /// Call on a host beat (e.g. phase 0 on the downbeat) to line the sweep
+    /// up with the beat grid.
+    /// 
+    /// # Arguments
+    /// * `phase` - Position in the cycle (0.0 to 1.0)
+    #[wasm_bindgen]
+    pub fn set_modulation_phase(&mut self, phase: f32) {
+        self.modulation.set_lfo_phase(phase);
+    }
+
+    /// Gets the modulation LFO position in its cycle (0.0 to 1.0)
+    #[wasm_bindgen]
+    pub fn get_modulation_phase(&self) -> f32 {
+        self.modulation.get_lfo_phase()
+    }
+
+    /// Sets the modulation sweep depth (0.0 = static to 1.0 = full sweep)
+    #[wasm_bindgen]
+    pub fn set_modulation_depth(&mut self, depth: f32) {
+        self.modulation.set_depth(depth);
+    }
+
+    /// Gets the modulation sweep depth
+    #[wasm_bindgen]
+    pub fn get_modulation_depth(&self) -> f32 {
+        self.modulation.get_depth()
+    }
+
+    /// Sets the modulation dry/wet mix
+    /// 
+    /// # Arguments
+    /// * `mix` - Dry/wet mix (0.0 to 1.0)
+    ///   - 0.0 = effect off
+    ///   - 0.5 = equal dry and wet (deepest flanger/phaser notches)
+    ///   - 1.0 = wet only
+    #[wasm_bindgen]
+    pub fn set_modulation_mix(&mut self, mix: f32) {
+        self.modulation.set_mix(mix);
+    }
+
+    /// Gets the modulation dry/wet mix
+    #[wasm_bindgen]
+    pub fn get_modulation_mix(&self) -> f32 {
+        self.modulation.get_mix()
+    }
+
+    /// Sets the number of phaser stages (4, 8 or 12)
+    #[wasm_bindgen]
+    pub fn set_phaser_stages(&mut self, stages: u32) {
+        self.modulation.set_phaser_stages(stages);
+    }
+
+    /// Gets the number of phaser stages
+    #[wasm_bindgen]
+    pub fn get_phaser_stages(&self) -> u32 {
+        self.modulation.get_phaser_stages()
+    }
+
+    // ===== Echo Control =====
+
+    /// Sets the tempo the echo syncs to, overriding the deck tempo
+    /// 
+    /// # Arguments
+    /// * `bpm` - Tempo in BPM (40-300), or 0 to follow the deck: track BPM ×
+    ///   tempo ratio (120 BPM when the track tempo is unknown)
+    #[wasm_bindgen]
+    pub fn set_echo_bpm(&mut self, bpm: f32) {
+        self.echo_bpm = if bpm <= 0.0 { 0.0 } else { bpm.clamp(40.0, 300.0) };
+        self.update_effect_tempo();
+    }
+
+    /// Gets the tempo the echo is synced to
+    #[wasm_bindgen]
+    pub fn get_echo_bpm(&self) -> f32 {
+        self.echo.get_bpm()
+    }
+
+    /// Sets the echo time in beats (0.0625 = 1/16 beat to 4.0 = one bar)
+    #[wasm_bindgen]
+    pub fn set_echo_beats(&mut self, beats: f32) {
+        self.echo.set_beats(beats);
+    }
+
+    /// Gets the echo time in beats
+    #[wasm_bindgen]
+    pub fn get_echo_beats(&self) -> f32 {
+        self.echo.get_beats()
+    }
+
+    /// Sets the echo feedback (0.0-0.95)
+    #[wasm_bindgen]
+    pub fn set_echo_feedback(&mut self, feedback: f32) {
+        self.echo.set_feedback(feedback);
+    }
+
+    /// Gets the echo feedback
+    #[wasm_bindgen]
+    pub fn get_echo_feedback(&self) -> f32 {
+        self.echo.get_feedback()
+    }
+
+    /// Sets the echo dry/wet mix
+    /// 
+    /// # Arguments
+    /// * `mix` - Dry/wet mix (0.0 to 1.0)
+    ///   - 0.0 = echo off
+    ///   - 0.5 = dry and echo at full level
+    ///   - 1.0 = echo only
+    #[wasm_bindgen]
+    pub fn set_echo_mix(&mut self, mix: f32) {
+        self.echo.set_mix(mix);
+    }
+
+    /// Gets the echo dry/wet mix
+    #[wasm_bindgen]
+    pub fn get_echo_mix(&self) -> f32 {
+        self.echo.get_mix()
+    }
+
+    /// Enables or disables ping-pong echo
+    #[wasm_bindgen]
+    pub fn set_echo_ping_pong(&mut self, enabled: bool) {
+        self.echo.set_ping_pong(enabled);
+    }
+
+    /// Gets whether ping-pong echo is on
+    #[wasm_bindgen]
+    pub fn get_echo_ping_pong(&self) -> bool {
+        self.echo.get_ping_pong()
+    }
+
+    /// Sets the filter in the echo feedback loop (-1.0 low-pass to 1.0 high-pass, 0 = off)
+    #[wasm_bindgen]
+    pub fn set_echo_filter(&mut self, position: f32) {
+        self.echo.set_filter(position);
+    }
+
+    /// Gets the echo feedback filter knob
+    #[wasm_bindgen]
+    pub fn get_echo_filter(&self) -> f32 {
+        self.echo.get_filter()
+    }
+
+    // ===== Reverb Control =====
+
+    /// Sets the reverb room size (0.0 = small room to 1.0 = hall)
+    #[wasm_bindgen]
+    pub fn set_reverb_size(&mut self, size: f32) {
+        self.reverb.set_size(size);
+    }
+
+    /// Gets the reverb room size
+    #[wasm_bindgen]
+    pub fn get_reverb_size(&self) -> f32 {
+        self.reverb.get_size()
+    }
+
+    /// Sets the reverb damping (0.0 = bright to 1.0 = dark)
+    #[wasm_bindgen]
+    pub fn set_reverb_damping(&mut self, damping: f32) {
+        self.reverb.set_damping(damping);
+    }
+
+    /// Gets the reverb damping
+    #[wasm_bindgen]
+    pub fn get_reverb_damping(&self) -> f32 {
+        self.reverb.get_damping()
+    }
+
+    /// Sets the reverb pre-delay in milliseconds (0-200)
+    #[wasm_bindgen]
+    pub fn set_reverb_pre_delay(&mut self, time_ms: f32) {
+        self.reverb.set_pre_delay(time_ms);
+    }
+
+    /// Gets the reverb pre-delay in milliseconds
+    #[wasm_bindgen]
+    pub fn get_reverb_pre_delay(&self) -> f32 {
+        self.reverb.get_pre_delay()
+    }
+
+    /// Sets the reverb stereo width (0.0 = mono to 1.0 = full stereo)
+    #[wasm_bindgen]
+    pub fn set_reverb_width(&mut self, width: f32) {
+        self.reverb.set_width(width);
+    }
+
+    /// Gets the reverb stereo width
+    #[wasm_bindgen]
+    pub fn get_reverb_width(&self) -> f32 {
+        self.reverb.get_width()
+    }
+
+    /// Sets the reverb dry/wet mix
+    /// 
+    /// # Arguments
+    /// * `mix` - Dry/wet mix (0.0 to 1.0)
+    ///   - 0.0 = reverb off
+    ///   - 0.5 = dry and reverb at full level
+    ///   - 1.0 = reverb only
+    #[wasm_bindgen]
+    pub fn set_reverb_mix(&mut self, mix: f32) {
+        self.reverb.set_mix(mix);
+    }
+
+    /// Gets the reverb dry/wet mix
+    #[wasm_bindgen]
+    pub fn get_reverb_mix(&self) -> f32 {
+        self.reverb.get_mix()
+    }
+
+    /// Freezes or releases the reverb tail
+    /// 
+    /// While frozen the tail sustains indefinitely and new audio is not
+    /// added to it.
+    #[wasm_bindgen]
+    pub fn set_reverb_freeze(&mut self, frozen: bool) {
+        self.reverb.set_freeze(frozen);
+    }
+
+    /// Gets whether the reverb tail is frozen
+    #[wasm_bindgen]
+    pub fn get_reverb_freeze(&self) -> bool {
+        self.reverb.get_freeze()
+    }
+
+    // ===== DJ Filter Control =====
+
+    /// Sets the filter knob position
+    /// 
+    /// # Arguments
+    /// * `position` - Knob position (-1.0 to 1.0)
+    ///   - Negative = low-pass, cutoff sweeping down from 16 kHz to 40 Hz
+    ///   - 0.0 = bypass
+    ///   - Positive = high-pass, cutoff sweeping up from 40 Hz to 16 kHz
+    #[wasm_bindgen]
+    pub fn set_filter_position(&mut self, position: f32) {
+        self.dj_filter.set_position(position);
+    }
+
+    /// Gets the filter knob position
+    #[wasm_bindgen]
+    pub fn get_filter_position(&self) -> f32 {
+        self.dj_filter.get_position()
+    }
+
+    /// Sets the filter resonance
+    /// 
+    /// # Arguments
+    /// * `resonance` - Resonance amount (0.0 = none to 1.0 = Q 8)
+    #[wasm_bindgen]
+    pub fn set_filter_resonance(&mut self, resonance: f32) {
+        self.dj_filter.set_resonance(resonance);
+    }
+
+    /// Gets the filter resonance
+    #[wasm_bindgen]
+    pub fn get_filter_resonance(&self) -> f32 {
+        self.dj_filter.get_resonance()
+    }
+
+    // ===== Compressor Control =====
+
+    /// Sets compression (above threshold) or expansion (below threshold)
+    #[wasm_bindgen]
+    pub fn set_compressor_mode(&mut self, mode: DynamicsMode) {
+        self.compressor.set_mode(mode);
+    }
+
+    /// Gets the compressor mode
+    #[wasm_bindgen]
+    pub fn get_compressor_mode(&self) -> DynamicsMode {
+        self.compressor.get_mode()
+    }
+
+    /// Sets the compressor threshold
+    /// 
+    /// # Arguments
+    /// * `db` - Threshold in dBFS (-60.0 to 0.0, default -18)
+    #[wasm_bindgen]
+    pub fn set_compressor_threshold(&mut self, db: f32) {
+        self.compressor.set_threshold(db);
+    }
+
+    /// Gets the compressor threshold in dBFS
+    #[wasm_bindgen]
+    pub fn get_compressor_threshold(&self) -> f32 {
+        self.compressor.get_threshold()
+    }
+
+    /// Sets the compressor ratio
+    /// 
+    /// # Arguments
+    /// * `ratio` - Ratio (1.0-20.0, default 1.0 = compressor off)
+    #[wasm_bindgen]
+    pub fn set_compressor_ratio(&mut self, ratio: f32) {
+        self.compressor.set_ratio(ratio);
+    }
+
+    /// Gets the compressor ratio
+    #[wasm_bindgen]
+    pub fn get_compressor_ratio(&self) -> f32 {
+        self.compressor.get_ratio()
+    }
+
+    /// Sets the compressor knee width in dB (0.0-24.0, default 6)
+    #[wasm_bindgen]
+    pub fn set_compressor_knee(&mut self, db: f32) {
+        self.compressor.set_knee(db);
+    }
+
+    /// Gets the compressor knee width in dB
+    #[wasm_bindgen]
+    pub fn get_compressor_knee(&self) -> f32 {
+        self.compressor.get_knee()
+    }
+
+    /// Sets the compressor attack time in milliseconds (0.1-100, default 10)
+    #[wasm_bindgen]
+    pub fn set_compressor_attack(&mut self, time_ms: f32) {
+        self.compressor.set_attack(time_ms);
+    }
+
+    /// Gets the compressor attack time in milliseconds
+    #[wasm_bindgen]
+    pub fn get_compressor_attack(&self) -> f32 {
+        self.compressor.get_attack()
+    }
+
+    /// Sets the compressor release time in milliseconds (10-2000, default 100)
+    #[wasm_bindgen]
+    pub fn set_compressor_release(&mut self, time_ms: f32) {
+        self.compressor.set_release(time_ms);
+    }
+
+    /// Gets the compressor release time in milliseconds
+    #[wasm_bindgen]
+    pub fn get_compressor_release(&self) -> f32 {
+        self.compressor.get_release()
+    }
+
+    /// Sets the compressor makeup gain in dB (0.0-24.0)
+    #[wasm_bindgen]
+    pub fn set_compressor_makeup(&mut self, db: f32) {
+        self.compressor.set_makeup_gain(db);
+    }
+
+    /// Gets the compressor makeup gain in dB
+    #[wasm_bindgen]
+    pub fn get_compressor_makeup(&self) -> f32 {
+        self.compressor.get_makeup_gain()
+    }
+
+    /// Sets the compressor sidechain high-pass in Hz (20-1000, 0 = off)
+    #[wasm_bindgen]
+    pub fn set_compressor_sidechain_highpass(&mut self, freq: f32) {
+        self.compressor.set_sidechain_highpass(freq);
+    }
+
+    /// Gets the compressor sidechain high-pass in Hz (0 = off)
+    #[wasm_bindgen]
+    pub fn get_compressor_sidechain_highpass(&self) -> f32 {
+        self.compressor.get_sidechain_highpass()
+    }
+
+    /// Gets the largest compressor gain reduction in the last block (positive dB)
+    #[wasm_bindgen]
+    pub fn get_compressor_gain_reduction(&self) -> f32 {
+        self.compressor.gain_reduction_db()
+    }
+
+    // ===== Master EQ Control (Parametric) =====
+
+    /// Adds a master EQ band
+    /// 
+    /// # Arguments
+    /// * `filter_type` - Band shape
+    /// * `frequency` - Centre/corner frequency in Hz
+    /// * `q` - Quality factor (0.1-20)
+    /// * `gain_db` - Gain in decibels (-24 to +24; ignored by notch and pass types)
+    /// 
+    /// Returns the band index, or `undefined` if all 16 bands are in use.
+    #[wasm_bindgen]
+    pub fn add_master_eq_band(&mut self, filter_type: FilterType, frequency: f32, q: f32, gain_db: f32) -> Option<usize> {
+        self.master_eq.add_band(EqBand::new(filter_type, frequency, q, gain_db))
+    }
+
+    /// Updates master EQ band `index`
+    /// 
+    /// Returns `false` if there is no such band.
+    #[wasm_bindgen]
+    pub fn set_master_eq_band(&mut self, index: usize, filter_type: FilterType, frequency: f32, q: f32, gain_db: f32) -> bool {
+        self.master_eq.set_band(index, EqBand::new(filter_type, frequency, q, gain_db))
+    }
+
+    /// Removes master EQ band `index`; later bands move down by one
+    #[wasm_bindgen]
+    pub fn remove_master_eq_band(&mut self, index: usize) -> bool {
+        self.master_eq.remove_band(index).is_some()
+    }
+
+    /// Removes all master EQ bands
+    #[wasm_bindgen]
+    pub fn clear_master_eq(&mut self) {
+        self.master_eq.clear();
+    }
+
+    /// Gets the number of master EQ bands
+    #[wasm_bindgen]
+    pub fn get_master_eq_band_count(&self) -> usize {
+        self.master_eq.band_count()
+    }
+
+    /// Gets the combined master EQ response in dB at each frequency
+    /// 
+    /// Intended for drawing the EQ curve.
+    #[wasm_bindgen]
+    pub fn get_master_eq_response(&self, frequencies: &[f32]) -> Box<[f32]> {
+        self.master_eq.magnitude_response(frequencies).into_boxed_slice()
+    }
+
+    // ===== Utility Methods =====
+    
+    /// Returns the engine version
+    #[wasm_bindgen]
+    pub fn get_version(&self) -> String {
+        VERSION.to_string()
+    }
+
+    /// Returns performance statistics as a JSON string
+    /// 
+    /// Includes:
+    /// - frames_processed: Total frames processed
+    /// - peak_level: Peak audio level (0.0-1.0)
+    /// - gain_reduction_db: Largest limiter gain reduction in the last block
+    /// - compressor_reduction_db: Largest compressor gain reduction in the last block
+    /// - sample_rate: Sample rate in Hz
+    /// - fft_size: FFT size used for pitch shifting
+    #[wasm_bindgen]
+    pub fn get_stats(&self) -> String {
+        let frames = self.frames_processed.load(Ordering::Relaxed);
+        let peak_db = if self.last_peak_level > 0.0 {
+            20.0 * self.last_peak_level.log10()
+        } else {
+            -100.0
+        };
+        
+        format!(
+            r#"{{"version":"{}","frames_processed":{},"peak_level":{:.3},"peak_db":{:.1},"gain_reduction_db":{:.1},"compressor_reduction_db":{:.1},"sample_rate":{},"fft_size":{}}}"#,
+            VERSION, frames, self.last_peak_level, peak_db, self.limiter.gain_reduction_db(),
+            self.compressor.gain_reduction_db(), self.sample_rate, self.fft_size
+        )
+    }
+}
+
+impl AudioProcessor {
+    /// Runs the signal chain into planar output buffers
+    ///
+    /// The non-allocating core of [`AudioProcessor::process_stream`]: pushes
+    /// the input into the time-stretch stream and fills `left` and `right`
+    /// (equal lengths, at most 4096 frames) with processed output.
+    pub fn render(&mut self, input_left: &[f32], input_right: &[f32], left: &mut [f32], right: &mut [f32]) {
+        let output_frames = left.len().min(right.len());
+        let (left, right) = (&mut left[..output_frames], &mut right[..output_frames]);
+
+        self.render_channel(input_left, input_right, left, right);
+        self.render_master(left, right);
+    }
+
+    /// Runs the channel strip (time stretch through the compressor) into
+    /// planar output buffers
+    ///
+    /// Like [`AudioProcessor::render`] without the master EQ, master volume
+    /// and limiter, for hosts such as [`Mixer`] that sum several decks
+    /// through a master section of their own.
+    pub fn render_channel(&mut self, input_left: &[f32], input_right: &[f32], left: &mut [f32], right: &mut [f32]) {
+        let output_frames = left.len().min(right.len());
+        let (left, right) = (&mut left[..output_frames], &mut right[..output_frames]);
+
+        // Stage 1: Time-stretch source audio to the requested block size
+        let stretcher = self.time_stretcher_mut();
+        stretcher.push_input(input_left, input_right);
+        stretcher.pull_output(left, right);
+        self.apply_stretch_fade(left, right);
+
+        // Stage 2: Apply input gain
+        for i in 0..output_frames {
+            let gain = self.input_gain.next_value();
+            left[i] *= gain;
+            right[i] *= gain;
+        }
+
+        // Stage 3: Apply pan/balance
+        self.panner.process(left, right);
+
+        // Stage 4: Apply pitch shifting (modifies buffer in-place)
+        self.pitch_shifter.process_stereo(left, right);
+
+        // Stage 5: Apply beat roll (passes through unless held)
+        self.beat_roll.process_stereo(left, right);
+
+        self.render_post_source(left, right);
+    }
+
+    /// Runs the channel strip stages after the source (EQ to compressor) in place
+    ///
+    /// Shared by [`AudioProcessor::render_channel`] and
+    /// [`AudioProcessor::render_tail`].
+    fn render_post_source(&mut self, left: &mut [f32], right: &mut [f32]) {
+        // Stage 6: Apply 3-band equalizer
+        self.equalizer.process_stereo(left, right);
+
+        // Stage 7: Apply lo-fi (no-op at zero mix)
+        self.lofi.process_stereo(left, right);
+
+        // Stage 8: Apply flanger/phaser/chorus (no-op at zero mix)
+        self.modulation.process_stereo(left, right);
+
+        // Stage 9: Apply echo (no-op at zero mix)
+        self.echo.process_stereo(left, right);
+
+        // Stage 10: Apply reverb (no-op at zero mix)
+        self.reverb.process_stereo(left, right);
+
+        // Stage 11: Apply DJ filter (no-op when the knob is centred)
+        self.dj_filter.process_stereo(left, right);
+
+        // Stage 12: Apply dynamics (no-op at ratio 1)
+        self.compressor.process_stereo(left, right);
+
+        self.last_peak_level = sample_utils::calculate_peak(left).max(sample_utils::calculate_peak(right));
+        self.frames_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Runs the master section (master EQ, volume and limiter) in place
+    fn render_master(&mut self, left: &mut [f32], right: &mut [f32]) {
+        // Stage 13: Apply parametric master EQ (no-op without bands)
+        self.master_eq.process_stereo(left, right);
+
+        // Stage 14: Apply master volume
+        for i in 0..left.len() {
+            let volume = self.master_volume.next_value();
+            left[i] *= volume;
+            right[i] *= volume;
+        }
+
+        // Stage 15: Limit to the ceiling
+        self.limiter.process_stereo(left, right);
+
+        self.last_peak_level = sample_utils::calculate_peak(left).max(sample_utils::calculate_peak(right));
+    }
+
+    /// Points the synced effects at their host tempo, or the deck's playing tempo
+    fn update_effect_tempo(&mut self) {
+        let deck_bpm = if self.track_bpm > 0.0 {
+            self.track_bpm * self.get_tempo_ratio()
+        } else {
+            120.0
+        };
+        let pick = |host_bpm: f32| if host_bpm > 0.0 { host_bpm } else { deck_bpm };
+        self.echo.set_bpm(pick(self.echo_bpm));
+        self.modulation.set_bpm(pick(self.modulation_bpm));
+        self.beat_roll.set_bpm(deck_bpm);
+    }
+
+    /// Whether the settings ask for a different tempo stage than the one running
+    fn stretch_switch_pending(&self) -> bool {
+        self.key_lock != self.active_key_lock
+            || (self.key_lock && self.stretch_engine != self.active_engine)
+    }
+
+    /// Fades the tempo stage out for a pending switch, or back in if the
+    /// settings have returned to the running stage
+    fn update_stretch_fade(&mut self) {
+        // Nothing has played yet, so there is nothing to fade
+        if self.frames_processed.load(Ordering::Relaxed) == 0 {
+            self.active_key_lock = self.key_lock;
+            self.active_engine = self.stretch_engine;
+            return;
+        }
+
+        let target = if self.stretch_switch_pending() { 0.0 } else { 1.0 };
+        if self.stretch_fade.target() != target && self.stretch_warmup == 0 {
+            self.stretch_fade.set_target(target);
+        }
+    }
+
+    /// Applies the switch fade to the tempo stage output, swapping stages
+    /// once the old one is silent
+    fn apply_stretch_fade(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if self.stretch_warmup == 0 && !self.stretch_fade.is_smoothing() && self.stretch_fade.current() == 1.0 {
+            return;
+        }
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let gain = if self.stretch_warmup > 0 {
+                self.stretch_warmup -= 1;
+                if self.stretch_warmup == 0 {
+                    self.update_stretch_fade();
+                }
+                0.0
+            } else {
+                self.stretch_fade.next_value()
+            };
+            *l *= gain;
+            *r *= gain;
+        }
+
+        // The rest of the block is silent; the new stage starts with the
+        // next one and fades in after its latency
+        if self.stretch_fade.current() == 0.0 && self.stretch_switch_pending() {
+            self.active_key_lock = self.key_lock;
+            self.active_engine = self.stretch_engine;
+            self.time_stretcher_mut().reset();
+            self.stretch_warmup = self.time_stretcher().latency();
+            if self.stretch_warmup == 0 {
+                self.update_stretch_fade();
+            }
+        }
+    }
+
+    /// The tempo stage currently in the signal chain
+    /// Largest channel strip latency any key lock or engine setting gives
+    pub(crate) fn max_channel_latency_frames(&self) -> usize {
+        let stretch = [self.phase_vocoder.latency(), self.wsola.latency(), self.varispeed.latency()];
+        stretch.into_iter().max().unwrap_or(0) + self.pitch_shifter.latency()
+    }
+
+    fn time_stretcher(&self) -> &dyn TimeStretcher {
+        if !self.active_key_lock {
+            return &self.varispeed;
+        }
+
+        match self.active_engine {
+            TimeStretchEngine::PhaseVocoder => &self.phase_vocoder,
+            TimeStretchEngine::Wsola => &self.wsola,
+        }
+    }
+
+    fn time_stretcher_mut(&mut self) -> &mut dyn TimeStretcher {
+        if !self.active_key_lock {
+            return &mut self.varispeed;
+        }
+
+        match self.active_engine {
+            TimeStretchEngine::PhaseVocoder => &mut self.phase_vocoder,
+            TimeStretchEngine::Wsola => &mut self.wsola,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_processor_creation() {
+        let processor = AudioProcessor::new(48000, 1024).unwrap();
+        assert_eq!(processor.get_input_gain(), 1.0);
+        assert_eq!(processor.get_master_volume(), 1.0);
+        assert_eq!(processor.get_tempo_ratio(), 1.0);
+    }
+
+    #[test]
+    fn test_gain_control() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_input_gain(2.0);
+        assert_eq!(processor.get_input_gain(), 2.0);
+        processor.set_input_gain(3.0); // Should clamp to 2.0
+        assert_eq!(processor.get_input_gain(), 2.0);
+    }
+
+    #[test]
+    fn test_pitch_cents_control() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_pitch_cents(-50.0);
+        assert_eq!(processor.get_pitch_cents(), -50.0);
+        assert_eq!(processor.get_pitch_shift(), -1);
+
+        processor.set_pitch_shift(7);
+        assert_eq!(processor.get_pitch_cents(), 700.0);
+        assert_eq!(processor.get_pitch_shift(), 7);
+
+        assert!(!processor.get_formant_preservation());
+        processor.set_formant_preservation(true);
+        assert!(processor.get_formant_preservation());
+    }
+
+    #[test]
+    fn test_filter_control() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_filter_position(-2.0);
+        assert_eq!(processor.get_filter_position(), -1.0);
+        processor.set_filter_resonance(0.5);
+        assert_eq!(processor.get_filter_resonance(), 0.5);
+
+        // Fully closed low-pass silences a 5 kHz tone once the knob has settled
+        let mut output = Box::default();
+        for frame in 0..40 {
+            let input: Vec<f32> = (frame * 256..(frame + 1) * 256)
+                .map(|i| (2.0 * std::f32::consts::PI * 5000.0 * i as f32 / 48000.0).sin() * 0.5)
+                .collect();
+            output = processor.process_frame(&input, &input);
+        }
+        assert!(output.iter().all(|s| s.abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_pan_control() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        assert_eq!(processor.get_pan_law(), PanLaw::Balance);
+        processor.set_smoothing_time(0.0);
+        processor.set_pan_law(PanLaw::Linear);
+        processor.set_pan(-2.0);
+        assert_eq!(processor.get_pan(), -1.0);
+
+        // Hard left silences the right channel
+        let input = vec![0.5; 256];
+        let output = processor.process_frame(&input, &input);
+        assert!(output.iter().skip(1).step_by(2).all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_master_eq_control() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        assert_eq!(processor.add_master_eq_band(FilterType::Peak, 1000.0, 1.0, 6.0), Some(0));
+        assert_eq!(processor.add_master_eq_band(FilterType::HighPass, 40.0, 0.707, 0.0), Some(1));
+        assert_eq!(processor.get_master_eq_band_count(), 2);
+
+        let response = processor.get_master_eq_response(&[1000.0, 10.0]);
+        assert!((response[0] - 6.0).abs() < 0.05);
+        assert!(response[1] < -20.0);
+
+        assert!(processor.set_master_eq_band(0, FilterType::Peak, 1000.0, 1.0, -3.0));
+        assert!((processor.get_master_eq_response(&[1000.0])[0] + 3.0).abs() < 0.05);
+        assert!(processor.remove_master_eq_band(1));
+        assert!(!processor.remove_master_eq_band(1));
+        processor.clear_master_eq();
+        assert_eq!(processor.get_master_eq_band_count(), 0);
+    }
+
+    #[test]
+    fn test_process_frame() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        let input_left = vec![0.1; 256];
+        let input_right = vec![0.1; 256];
+        
+        let output = processor.process_frame(&input_left, &input_right);
+        assert_eq!(output.len(), 512); // 256 * 2 (stereo)
+    }
+
+    #[test]
+    fn test_channels_do_not_bleed() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_low_gain(9.0);
+        processor.set_high_gain(-6.0);
+
+        let left: Vec<f32> = (0..256)
+            .map(|i| (2.0 * std::f32::consts::PI * 100.0 * i as f32 / 48000.0).sin() * 0.5)
+            .collect();
+        let right = vec![0.0; 256];
+
+        for _ in 0..8 {
+            let output = processor.process_frame(&left, &right);
+            assert!(output.iter().skip(1).step_by(2).all(|&s| s == 0.0));
+        }
+    }
+
+    #[test]
+    fn test_echo_follows_deck_tempo() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        assert_eq!(processor.get_echo_bpm(), 120.0);
+
+        processor.set_track_bpm(128.0);
+        processor.set_tempo_ratio(1.25);
+        assert_eq!(processor.get_echo_bpm(), 160.0);
+
+        // Host tempo wins until cleared
+        processor.set_echo_bpm(100.0);
+        processor.set_tempo_ratio(1.0);
+        assert_eq!(processor.get_echo_bpm(), 100.0);
+        processor.set_echo_bpm(0.0);
+        assert_eq!(processor.get_echo_bpm(), 128.0);
+
+        // Too little audio to analyze falls back to the default tempo
+        assert_eq!(processor.detect_track_bpm(&[0.0; 1024]), 120);
+        assert_eq!(processor.get_track_bpm(), 120.0);
+    }
+
+    #[test]
+    fn test_beat_roll_repeats_and_releases() {
+        let deck = || {
+            let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+            processor.set_key_lock(false);
+            processor.set_track_bpm(187.5);
+            processor.set_roll_beats(0.125);
+            processor
+        };
+        let mut processor = deck();
+        let mut reference = deck();
+        let limiter = processor.limiter.latency();
+
+        // Pressed once the deck has filled up, released 24 blocks later
+        let input: Vec<f32> = (0..48 * 256).map(|i| (i as f32 * 0.01).sin() * 0.5).collect();
+        let mut output = Vec::new();
+        let mut live = Vec::new();
+        for (i, block) in input.chunks(256).enumerate() {
+            if i == 16 {
+                processor.set_beat_phase(0.0);
+                processor.set_roll_active(true);
+                assert!(processor.get_roll_active());
+            } else if i == 40 {
+                processor.set_roll_active(false);
+            }
+            output.extend(processor.process_frame(block, block).iter().step_by(2).copied());
+            live.extend(reference.process_frame(block, block).iter().step_by(2).copied());
+        }
+
+        // 1/8 beat at 187.5 BPM = 1920 samples, repeated while held
+        let start = 16 * 256 + limiter;
+        for i in 0..1920 {
+            let second = output[start + 1920 + i];
+            let third = output[start + 3840 + i];
+            assert!((second - third).abs() < 1e-6, "{}", i);
+        }
+        assert!((output[start + 3840] - live[start + 3840]).abs() > 0.1);
+
+        // Released: back where the track would have been
+        assert_eq!(output[42 * 256..], live[42 * 256..]);
+        assert!(!processor.get_roll_active());
+    }
+
+    #[test]
+    fn test_beat_phase_accounts_for_latency() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_track_bpm(120.0);
+
+        // The roll hears the source a phase vocoder latency later
+        processor.set_beat_phase(0.0);
+        let latency = processor.get_latency_frames() - processor.limiter.latency();
+        assert!(latency > 0);
+        let expected = 1.0 - latency as f32 * 2.0 / 48000.0;
+        assert!((processor.get_beat_phase() - expected).abs() < 1e-4);
+
+        // Varispeed still goes through the (bypassed) pitch shifter delay
+        processor.set_key_lock(false);
+        processor.set_beat_phase(0.25);
+        let latency = processor.pitch_shifter.latency();
+        let expected = 0.25 - latency as f32 * 2.0 / 48000.0;
+        assert!((processor.get_beat_phase() - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_lofi_control() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_smoothing_time(0.0);
+        processor.set_lofi_saturation(SaturationType::Foldback);
+        processor.set_lofi_drive(12.0);
+        processor.set_lofi_bit_depth(4.0);
+        processor.set_lofi_downsample(8000.0);
+        processor.set_lofi_mix(1.0);
+        assert_eq!(processor.get_lofi_saturation(), SaturationType::Foldback);
+        assert_eq!(processor.get_lofi_downsample(), 8000.0);
+
+        let input: Vec<f32> = (0..256).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let mut output = Box::default();
+        for _ in 0..20 {
+            output = processor.process_frame(&input, &input);
+        }
+        assert!(output.iter().all(|s| s.is_finite()));
+        assert!(output.iter().any(|&s| s != 0.0));
+    }
+
+    #[test]
+    fn test_modulation_follows_deck_tempo() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_track_bpm(120.0);
+        processor.set_tempo_ratio(1.5);
+        assert_eq!(processor.get_modulation_bpm(), 180.0);
+
+        processor.set_modulation_bpm(90.0);
+        assert_eq!(processor.get_modulation_bpm(), 90.0);
+        assert_eq!(processor.get_echo_bpm(), 180.0);
+        processor.set_modulation_bpm(0.0);
+        assert_eq!(processor.get_modulation_bpm(), 180.0);
+
+        processor.set_modulation_type(ModulationType::Flanger);
+        processor.set_modulation_mix(0.5);
+        let input: Vec<f32> = (0..256).map(|i| (i as f32 * 0.3).sin() * 0.5).collect();
+        let output = processor.process_frame(&input, &input);
+        assert_eq!(output.len(), 512);
+        assert!(output.iter().all(|s| s.is_finite()));
+    }
+
+    #[test]
+    fn test_reverb_tail_rings_after_stop() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_reverb_mix(0.5);
+        processor.set_reverb_size(0.9);
+
+        let input: Vec<f32> = (0..256).map(|i| (i as f32 * 0.7).sin() * 0.5).collect();
+        for _ in 0..40 {
+            processor.process_frame(&input, &input);
+        }
+
+        // Deck stopped: no source audio, but the tail keeps ringing
+        assert!(processor.process_frame(&[], &[]).is_empty());
+        let tail = processor.render_tail(256);
+        assert_eq!(tail.len(), 512);
+        assert!(tail.iter().any(|s| s.abs() > 1e-3));
+
+        // Frozen, it is still there seconds later
+        processor.set_reverb_freeze(true);
+        let mut last = Box::default();
+        for _ in 0..400 {
+            last = processor.render_tail(256);
+        }
+        assert!(last.iter().any(|s| s.abs() > 1e-3));
+        assert!(processor.render_tail(0).is_empty());
+    }
+
+    #[test]
+    fn test_compressor_control() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_compressor_threshold(-24.0);
+        processor.set_compressor_ratio(10.0);
+        processor.set_compressor_attack(1.0);
+        assert_eq!(processor.get_compressor_ratio(), 10.0);
+
+        let input: Vec<f32> = (0..256)
+            .map(|i| (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin() * 0.5)
+            .collect();
+        for _ in 0..20 {
+            processor.process_frame(&input, &input);
+        }
+        assert!(processor.get_compressor_gain_reduction() > 10.0);
+        assert!(processor.get_stats().contains("\"compressor_reduction_db\""));
+    }
+
+    #[test]
+    fn test_output_never_exceeds_ceiling() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_master_volume(2.0);
+        processor.set_low_gain(12.0);
+        processor.set_limiter_ceiling(-1.0);
+        assert_eq!(processor.get_limiter_ceiling(), -1.0);
+
+        let ceiling = sample_utils::db_to_linear(-1.0);
+        let input: Vec<f32> = (0..256 * 60)
+            .map(|i| (2.0 * std::f32::consts::PI * 80.0 * i as f32 / 48000.0).sin() * 0.9)
+            .collect();
+        for block in input.chunks(256) {
+            let output = processor.process_frame(block, block);
+            assert!(output.iter().all(|s| s.abs() <= ceiling));
+        }
+
+        assert!(processor.get_gain_reduction() > 6.0);
+        assert!(processor.get_stats().contains("\"gain_reduction_db\""));
+    }
+
+    #[test]
+    fn test_gain_changes_are_smoothed() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_smoothing_time(10.0);
+        assert_eq!(processor.get_smoothing_time(), 10.0);
+
+        // DC with the EQ and pan at rest passes unchanged
+        let input = vec![0.5; 256];
+        for _ in 0..40 {
+            processor.process_frame(&input, &input);
+        }
+
+        // Master volume ramps first, then input gain; both are delayed by
+        // the limiter lookahead
+        processor.set_master_volume(1.5);
+        let mut left: Vec<f32> = Vec::new();
+        for frame in 0..40 {
+            if frame == 4 {
+                processor.set_input_gain(0.0);
+            }
+            let output = processor.process_frame(&input, &input);
+            left.extend(output.iter().step_by(2));
+        }
+
+        // 10 ms = 480 samples per ramp; never a step
+        assert!((left[479 + processor.limiter.latency()] - 0.75).abs() < 1e-3);
+        let max_step = left.windows(2).fold(0.0f32, |m, w| m.max((w[1] - w[0]).abs()));
+        assert!(max_step < 0.01, "{}", max_step);
+        assert!(left[left.len() - 1].abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_process_stream_follows_tempo() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_tempo_ratio(1.5);
+
+        let source = vec![0.1; 48000];
+        let mut consumed = 0;
+        for _ in 0..200 {
+            let needed = processor.get_input_frames_required(128);
+            let chunk = &source[consumed..consumed + needed];
+            let output = processor.process_stream(chunk, chunk, 128);
+            assert_eq!(output.len(), 256);
+            consumed += needed;
+        }
+
+        // 200 quanta of 128 frames at 1.5x consume ~38400 source frames
+        assert!((consumed as f32 - 38400.0).abs() < 1024.0);
+    }
+
+    #[test]
+    fn test_switch_time_stretch_engine() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        processor.set_tempo_ratio(0.75);
+        processor.set_time_stretch_engine(TimeStretchEngine::Wsola);
+        assert_eq!(processor.get_time_stretch_engine(), TimeStretchEngine::Wsola);
+        assert_eq!(processor.get_tempo_ratio(), 0.75);
+        assert_eq!(
+            processor.get_latency_frames(),
+            480 + processor.pitch_shifter.latency() + processor.limiter.latency()
+        );
+
+        let source = vec![0.1; 48000];
+        let mut consumed = 0;
+        for _ in 0..200 {
+            let needed = processor.get_input_frames_required(128);
+            let chunk = &source[consumed..consumed + needed];
+            processor.process_stream(chunk, chunk, 128);
+            consumed += needed;
+        }
+        assert!((consumed as f32 - 19200.0).abs() < 1024.0);
+    }
+
+    #[test]
+    fn test_stage_switches_fade() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        let source: Vec<f32> = (0..96000)
+            .map(|i| (2.0 * std::f32::consts::PI * 220.0 * i as f32 / 48000.0).sin() * 0.5)
+            .collect();
+
+        let mut left: Vec<f32> = Vec::new();
+        let mut consumed = 0;
+        for quantum in 0..600 {
+            match quantum {
+                100 => processor.set_key_lock(false),
+                250 => processor.set_key_lock(true),
+                400 => processor.set_time_stretch_engine(TimeStretchEngine::Wsola),
+                _ => {}
+            }
+            let needed = processor.get_input_frames_required(128);
+            let chunk = &source[consumed..consumed + needed];
+            let output = processor.process_stream(chunk, chunk, 128);
+            left.extend(output.iter().step_by(2));
+            consumed += needed;
+        }
+
+        // Each switch dips the level without a step (220 Hz at 0.5 moves by
+        // at most 0.015 per sample) and comes back
+        let played = &left[64 * 128..];
+        let max_step = played.windows(2).fold(0.0f32, |m, w| m.max((w[1] - w[0]).abs()));
+        assert!(max_step < 0.02, "{}", max_step);
+        for end in [250, 400, 600] {
+            let block = &left[(end - 20) * 128..end * 128];
+            let peak = block.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+            assert!(peak > 0.45, "peak {} before quantum {}", peak, end);
+        }
+        assert_eq!(
+            processor.get_latency_frames(),
+            480 + processor.pitch_shifter.latency() + processor.limiter.latency()
+        );
+    }
+
+    #[test]
+    fn test_key_lock_off_is_varispeed() {
+        let mut processor = AudioProcessor::new(48000, 1024).unwrap();
+        assert!(processor.get_key_lock());
+        processor.set_key_lock(false);
+        processor.set_tempo_ratio(2.0);
+        assert_eq!(
+            processor.get_latency_frames(),
+            processor.pitch_shifter.latency() + processor.limiter.latency()
+        );
+
+        // 120 Hz sine plays back at 240 Hz: period of 200 samples
+        let source: Vec<f32> = (0..16384)
+            .map(|i| (2.0 * std::f32::consts::PI * 120.0 * i as f32 / 48000.0).sin() * 0.5)
+            .collect();
+        let needed = processor.get_input_frames_required(1024);
+        assert_eq!(needed, 2049);
+
+        let mut consumed = 0;
+        let mut output = Box::default();
+        for _ in 0..6 {
+            let needed = processor.get_input_frames_required(1024);
+            let chunk = &source[consumed..consumed + needed];
+            output = processor.process_stream(chunk, chunk, 1024);
+            consumed += needed;
+        }
+
+        assert!(output.iter().any(|s| s.abs() > 0.4));
+        for i in 400..800 {
+            assert!((output[i * 2] - output[(i + 200) * 2]).abs() < 1e-2);
+        }
     }
 }
 
@@ -0,0 +1,291 @@
+//! Lookahead brickwall limiter for the master output
+//!
+//! Keeps every output sample at or below a ceiling without clipping
+//! distortion. The input is delayed by a short lookahead so gain reduction
+//! can ramp down *before* a peak arrives instead of reacting to it.
+//!
+//! Algorithm:
+//! 1. Required gain per sample: `min(1, ceiling / peak(|L|, |R|))` (stereo linked)
+//! 2. Sliding minimum of the required gain over the lookahead window
+//! 3. Moving average over the same window: a smooth attack ramp that is
+//!    still never above the required gain of the delayed sample
+//! 4. One-pole release back towards unity
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(1) amortized per sample
+//! - Memory: ~3 floats per lookahead sample
+//! - Latency: 5ms (the lookahead)
+
+use std::collections::VecDeque;
+
+use crate::buffer_manager::sample_utils::{db_to_linear, linear_to_db};
+
+/// Lookahead time in milliseconds
+const LOOKAHEAD_MS: f32 = 5.0;
+
+/// Default ceiling in dBFS
+pub const DEFAULT_CEILING_DB: f32 = -0.3;
+
+/// Default release time in milliseconds
+pub const DEFAULT_RELEASE_MS: f32 = 100.0;
+
+/// Stereo-linked lookahead peak limiter
+///
+/// # Performance
+/// - Per-sample complexity: O(1) amortized
+/// - Memory: ~3KB at 48kHz
+pub struct Limiter {
+    sample_rate: u32,
+    ceiling_db: f32,
+    ceiling: f32,
+    release_ms: f32,
+    release_coefficient: f32,
+
+    // Delay line holding the lookahead (per channel)
+    lookahead: usize,
+    delay: [Vec<f32>; 2],
+    delay_pos: usize,
+
+    // Sliding minimum of the required gain: (sample index, gain), increasing
+    // gains. The index wraps (after ~24.8 hours at 48kHz on wasm32), so ages
+    // are taken with wrapping arithmetic.
+    window: VecDeque<(usize, f32)>,
+    sample_index: usize,
+
+    // Moving average of the windowed minimum
+    average: Vec<f32>,
+    average_pos: usize,
+    average_sum: f64,
+
+    envelope: f32,
+    min_gain: f32,
+}
+
+impl Limiter {
+    /// Creates a limiter with the default ceiling (-0.3 dBFS) and release (100ms)
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn new(sample_rate: u32) -> Self {
+        let lookahead = ((LOOKAHEAD_MS * 0.001 * sample_rate as f32) as usize).max(1);
+        let window = lookahead + 1;
+
+        let mut limiter = Limiter {
+            sample_rate,
+            ceiling_db: 0.0,
+            ceiling: 1.0,
+            release_ms: 0.0,
+            release_coefficient: 1.0,
+            lookahead,
+            delay: [vec![0.0; lookahead], vec![0.0; lookahead]],
+            delay_pos: 0,
+            window: VecDeque::with_capacity(window),
+            sample_index: 0,
+            average: vec![1.0; window],
+            average_pos: 0,
+            average_sum: window as f64,
+            envelope: 1.0,
+            min_gain: 1.0,
+        };
+        limiter.set_ceiling(DEFAULT_CEILING_DB);
+        limiter.set_release(DEFAULT_RELEASE_MS);
+        limiter
+    }
+
+    /// Sets the output ceiling
+    ///
+    /// # Arguments
+    /// * `db` - Ceiling in dBFS (-24.0 to 0.0)
+    pub fn set_ceiling(&mut self, db: f32) {
+        self.ceiling_db = db.clamp(-24.0, 0.0);
+        self.ceiling = db_to_linear(self.ceiling_db);
+    }
+
+    /// Gets the output ceiling in dBFS
+    pub fn get_ceiling(&self) -> f32 {
+        self.ceiling_db
+    }
+
+    /// Sets the release time
+    ///
+    /// # Arguments
+    /// * `time_ms` - Time constant of the return to unity gain (10-1000ms)
+    pub fn set_release(&mut self, time_ms: f32) {
+        self.release_ms = time_ms.clamp(10.0, 1000.0);
+        let samples = self.release_ms * 0.001 * self.sample_rate as f32;
+        self.release_coefficient = 1.0 - (-1.0 / samples).exp();
+    }
+
+    /// Gets the release time in milliseconds
+    pub fn get_release(&self) -> f32 {
+        self.release_ms
+    }
+
+    /// Latency in frames (the lookahead)
+    pub fn latency(&self) -> usize {
+        self.lookahead
+    }
+
+    /// Largest gain reduction during the last [`Limiter::process_stereo`] call
+    ///
+    /// Returns a positive number of dB (0 when the limiter was idle).
+    pub fn gain_reduction_db(&self) -> f32 {
+        -linear_to_db(self.min_gain)
+    }
+
+    /// Limits a stereo pair in place
+    ///
+    /// Output is the input delayed by [`Limiter::latency`] frames, scaled so
+    /// that no sample exceeds the ceiling.
+    ///
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let window = self.average.len();
+        self.min_gain = 1.0;
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let peak = l.abs().max(r.abs());
+            let required = if peak > self.ceiling { self.ceiling / peak } else { 1.0 };
+
+            // Sliding minimum over the last `window` samples
+            while self.window.back().is_some_and(|&(_, gain)| gain >= required) {
+                self.window.pop_back();
+            }
+            self.window.push_back((self.sample_index, required));
+            if self.sample_index.wrapping_sub(self.window[0].0) >= window {
+                self.window.pop_front();
+            }
+            let held = self.window[0].1;
+            self.sample_index = self.sample_index.wrapping_add(1);
+
+            // Attack ramp
+            self.average_sum += (held - self.average[self.average_pos]) as f64;
+            self.average[self.average_pos] = held;
+            self.average_pos = (self.average_pos + 1) % window;
+            let attack = (self.average_sum / window as f64) as f32;
+
+            // Release
+            self.envelope = if attack < self.envelope {
+                attack
+            } else {
+                self.envelope + (attack - self.envelope) * self.release_coefficient
+            };
+            self.min_gain = self.min_gain.min(self.envelope);
+
+            let [delay_left, delay_right] = &mut self.delay;
+            let delayed_left = std::mem::replace(&mut delay_left[self.delay_pos], *l);
+            let delayed_right = std::mem::replace(&mut delay_right[self.delay_pos], *r);
+            self.delay_pos = (self.delay_pos + 1) % self.lookahead;
+
+            // The clamp only catches float rounding in the running average
+            *l = (delayed_left * self.envelope).clamp(-self.ceiling, self.ceiling);
+            *r = (delayed_right * self.envelope).clamp(-self.ceiling, self.ceiling);
+        }
+    }
+
+    /// Clears the lookahead and returns to unity gain
+    pub fn reset(&mut self) {
+        for channel in self.delay.iter_mut() {
+            channel.fill(0.0);
+        }
+        self.delay_pos = 0;
+        self.window.clear();
+        self.sample_index = 0;
+        self.average.fill(1.0);
+        self.average_pos = 0;
+        self.average_sum = self.average.len() as f64;
+        self.envelope = 1.0;
+        self.min_gain = 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn sine(freq: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * PI * freq * i as f32 / 48000.0).sin() * amplitude)
+            .collect()
+    }
+
+    #[test]
+    fn test_never_exceeds_ceiling() {
+        let mut limiter = Limiter::new(48000);
+        limiter.set_ceiling(-1.0);
+        let ceiling = db_to_linear(-1.0);
+
+        // Bursts, a sharp click and a loud sustained tone, in odd-sized blocks
+        let mut input = sine(100.0, 0.5, 4800);
+        input.extend(sine(3000.0, 4.0, 480));
+        input.extend([8.0, -8.0, 0.0, 0.0]);
+        input.extend(sine(50.0, 0.9, 4800));
+        input.extend(sine(7000.0, 2.5, 9600));
+
+        let mut left = input.clone();
+        let mut right: Vec<f32> = input.iter().map(|s| -s * 0.5).collect();
+        for (l, r) in left.chunks_mut(333).zip(right.chunks_mut(333)) {
+            limiter.process_stereo(l, r);
+        }
+
+        let peak = left.iter().chain(right.iter()).fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak <= ceiling, "{}", peak);
+    }
+
+    #[test]
+    fn test_quiet_signal_is_only_delayed() {
+        let mut limiter = Limiter::new(48000);
+        let input = sine(440.0, 0.5, 2048);
+        let mut left = input.clone();
+        let mut right = input.clone();
+        limiter.process_stereo(&mut left, &mut right);
+
+        let latency = limiter.latency();
+        assert_eq!(latency, 240);
+        assert!(left[..latency].iter().all(|&s| s == 0.0));
+        assert_eq!(left[latency..], input[..2048 - latency]);
+        assert_eq!(limiter.gain_reduction_db(), 0.0);
+    }
+
+    #[test]
+    fn test_gain_reduction_and_release() {
+        let mut limiter = Limiter::new(48000);
+        limiter.set_ceiling(0.0);
+        limiter.set_release(50.0);
+
+        // A +6 dB tone needs 6 dB of reduction
+        let mut left = sine(1000.0, 2.0, 4800);
+        let mut right = left.clone();
+        limiter.process_stereo(&mut left, &mut right);
+        assert!((limiter.gain_reduction_db() - 6.02).abs() < 0.05);
+
+        // Unity gain returns within a few release time constants
+        let mut left = sine(1000.0, 0.5, 24000);
+        let mut right = left.clone();
+        limiter.process_stereo(&mut left, &mut right);
+        let tail = left[19200..].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((tail - 0.5).abs() < 0.01);
+
+        limiter.set_release(5000.0);
+        assert_eq!(limiter.get_release(), 1000.0);
+    }
+
+    #[test]
+    fn test_releases_across_sample_index_wrap() {
+        let mut limiter = Limiter::new(48000);
+        limiter.set_ceiling(0.0);
+        limiter.set_release(50.0);
+        limiter.sample_index = usize::MAX - 1000;
+
+        // A peak just before the index wraps still leaves the window after it
+        let mut left = sine(1000.0, 2.0, 960);
+        left.extend(sine(1000.0, 0.5, 24000));
+        let mut right = left.clone();
+        limiter.process_stereo(&mut left, &mut right);
+        let tail = left[20000..].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((tail - 0.5).abs() < 0.01, "{}", tail);
+    }
+}
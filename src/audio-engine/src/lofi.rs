@@ -0,0 +1,510 @@
+//! Lo-fi effects: saturation, bitcrusher and sample-rate reducer
+//!
+//! ```text
+//! Input ──┬→ Delay (while driven) ───────────────────────── Dry ──┐
+//!         └→ Drive → Shaper (4× oversampled) → Bit depth → S&H ─ Wet ─(+)→ Output
+//! ```
+//!
+//! The shaper (tanh, tube or foldback from [`sample_utils`]) runs at four
+//! times the sample rate between 8th-order Butterworth filters, so the
+//! harmonics it adds above Nyquist are filtered out instead of folding back
+//! as inharmonic aliases. The bitcrusher and sample-and-hold alias on
+//! purpose; that is their sound.
+//!
+//! The oversampling filters delay the wet signal by about four samples.
+//! While the shaper is on, the dry signal is delayed to match, so partial
+//! mixes do not comb-filter. Turning the drive up from 0 dB fades the shaper
+//! and the dry delay in together (and back out on the way down), starting
+//! the filters from silence.
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(1) per sample (32 biquads per channel while driven)
+//! - Memory: <1KB
+//! - Latency: none; about 4 samples while the shaper is on
+//!
+//! [`sample_utils`]: crate::buffer_manager::sample_utils
+
+use std::f32::consts::TAU;
+
+use wasm_bindgen::prelude::*;
+
+use crate::biquad::{Biquad, BiquadCoefficients};
+use crate::buffer_manager::sample_utils::{db_to_linear, foldback, soft_clip, tube_clip};
+use crate::delay_line::DelayLine;
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+
+/// Oversampling factor around the shaper
+const OVERSAMPLE: usize = 4;
+
+/// Q of each section of an 8th-order Butterworth filter
+const BUTTERWORTH_Q: [f32; 4] = [0.5098, 0.6013, 0.9000, 2.5629];
+
+/// Oversampling filter cutoff as a fraction of the base sample rate
+const FILTER_CUTOFF: f32 = 0.42;
+
+/// Highest drive in dB
+const MAX_DRIVE_DB: f32 = 36.0;
+
+/// Bit depth at and above which the bitcrusher is off
+const MAX_BITS: f32 = 16.0;
+
+/// DC blocker corner in Hz
+const DC_BLOCK_HZ: f32 = 10.0;
+
+/// Longest dry delay in samples (comfortably above the filter delay)
+const MAX_DRY_DELAY: usize = 16;
+
+/// Fade of the shaper and dry delay as the drive leaves or returns to 0 dB
+const SHAPER_FADE_MS: f32 = 10.0;
+
+/// Waveshaper curve
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SaturationType {
+    /// Smooth symmetric saturation (odd harmonics)
+    Tanh = 0,
+    /// Asymmetric, tube-style saturation (even and odd harmonics)
+    Tube = 1,
+    /// Signal folds back on itself past full scale (harsh, metallic)
+    Foldback = 2,
+}
+
+impl SaturationType {
+    fn shape(self, sample: f32) -> f32 {
+        match self {
+            SaturationType::Tanh => soft_clip(sample),
+            SaturationType::Tube => tube_clip(sample),
+            SaturationType::Foldback => foldback(sample, 1.0),
+        }
+    }
+}
+
+/// Per-channel oversampling, dry delay, hold and DC blocker state
+#[derive(Clone, Debug)]
+struct LoFiChannel {
+    upsample: [Biquad; 4],
+    downsample: [Biquad; 4],
+    dry_delay: DelayLine,
+    held: f32,
+    dc_input: f32,
+    dc_output: f32,
+}
+
+impl LoFiChannel {
+    fn new(sample_rate: u32) -> Self {
+        let filters = BUTTERWORTH_Q.map(|q| {
+            Biquad::new(BiquadCoefficients::lowpass(
+                sample_rate * OVERSAMPLE as u32,
+                sample_rate as f32 * FILTER_CUTOFF,
+                q,
+            ))
+        });
+        LoFiChannel {
+            upsample: filters.clone(),
+            downsample: filters,
+            dry_delay: DelayLine::new(MAX_DRY_DELAY),
+            held: 0.0,
+            dc_input: 0.0,
+            dc_output: 0.0,
+        }
+    }
+
+    /// Runs one sample through the shaper at the oversampled rate
+    fn saturate(&mut self, sample: f32, shaper: SaturationType) -> f32 {
+        let mut output = 0.0;
+        for step in 0..OVERSAMPLE {
+            // Zero-stuffing; the gain makes up for the inserted zeros
+            let mut x = if step == 0 { sample * OVERSAMPLE as f32 } else { 0.0 };
+            for filter in self.upsample.iter_mut() {
+                x = filter.process_sample(x);
+            }
+            let mut y = shaper.shape(x);
+            for filter in self.downsample.iter_mut() {
+                y = filter.process_sample(y);
+            }
+            if step == 0 {
+                output = y;
+            }
+        }
+        output
+    }
+
+    /// Low-frequency group delay of [`LoFiChannel::saturate`] in samples
+    fn saturate_delay(&self) -> f32 {
+        let oversampled: f32 = self
+            .upsample
+            .iter()
+            .chain(self.downsample.iter())
+            .map(|filter| dc_group_delay(filter.coefficients()))
+            .sum();
+        oversampled / OVERSAMPLE as f32
+    }
+
+    /// Clears the oversampling filters and DC blocker
+    fn reset_shaper(&mut self) {
+        for filter in self.upsample.iter_mut().chain(self.downsample.iter_mut()) {
+            filter.reset();
+        }
+        self.dc_input = 0.0;
+        self.dc_output = 0.0;
+    }
+
+    fn reset(&mut self) {
+        self.reset_shaper();
+        self.dry_delay.reset();
+        self.held = 0.0;
+    }
+}
+
+/// Stereo lo-fi processor
+///
+/// Starts with tanh saturation, drive 0 dB (off), 16 bits (off), no
+/// sample-rate reduction and the mix at 0 (bypass).
+///
+/// # Performance
+/// - Per-sample complexity: O(1)
+/// - Memory: <1KB per instance
+pub struct LoFi {
+    sample_rate: u32,
+    saturation: SaturationType,
+    drive_db: f32,
+    // Linear drive, gliding towards the setting
+    drive: SmoothedParam,
+    bits: f32,
+    levels: f32,
+    target_rate: f32,
+
+    // Sample-and-hold position in held samples (a new one is taken at 1.0)
+    hold_phase: f32,
+    dc_coefficient: f32,
+    dry_delay: f32,
+    // How far the shaper and dry delay are faded in (0 = drive off)
+    shaper: SmoothedParam,
+    mix: SmoothedParam,
+    channels: [LoFiChannel; 2],
+    silent: bool,
+}
+
+impl LoFi {
+    /// Creates a bypassed lo-fi processor
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn new(sample_rate: u32) -> Self {
+        let channel = LoFiChannel::new(sample_rate);
+        LoFi {
+            sample_rate,
+            saturation: SaturationType::Tanh,
+            drive_db: 0.0,
+            drive: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            bits: MAX_BITS,
+            levels: 2.0f32.powf(MAX_BITS - 1.0),
+            target_rate: sample_rate as f32,
+            hold_phase: 1.0,
+            dc_coefficient: 1.0 - TAU * DC_BLOCK_HZ / sample_rate as f32,
+            dry_delay: channel.saturate_delay(),
+            shaper: SmoothedParam::new(0.0, sample_rate, SHAPER_FADE_MS),
+            mix: SmoothedParam::new(0.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            channels: [channel.clone(), channel],
+            silent: true,
+        }
+    }
+
+    /// Selects the saturation curve
+    pub fn set_saturation(&mut self, saturation: SaturationType) {
+        self.saturation = saturation;
+    }
+
+    /// Gets the saturation curve
+    pub fn get_saturation(&self) -> SaturationType {
+        self.saturation
+    }
+
+    /// Sets the gain into the saturation curve
+    ///
+    /// # Arguments
+    /// * `drive_db` - Drive in dB (0.0 = saturation off, to 36.0)
+    pub fn set_drive(&mut self, drive_db: f32) {
+        self.drive_db = drive_db.clamp(0.0, MAX_DRIVE_DB);
+        self.drive.set_target(db_to_linear(self.drive_db));
+
+        let engaged = if self.drive_db > 0.0 { 1.0 } else { 0.0 };
+        if self.shaper.target() != engaged {
+            // Fully off, the filters hold whatever they last saw
+            if self.shaper.current() == 0.0 {
+                for channel in self.channels.iter_mut() {
+                    channel.reset_shaper();
+                }
+            }
+            self.shaper.set_target(engaged);
+        }
+    }
+
+    /// Gets the drive in dB
+    pub fn get_drive(&self) -> f32 {
+        self.drive_db
+    }
+
+    /// Sets the bit depth
+    ///
+    /// Fractional depths are allowed, so the knob sweeps smoothly.
+    ///
+    /// # Arguments
+    /// * `bits` - Bit depth (1.0 to 16.0; 16.0 = bitcrusher off)
+    pub fn set_bit_depth(&mut self, bits: f32) {
+        self.bits = bits.clamp(1.0, MAX_BITS);
+        self.levels = 2.0f32.powf(self.bits - 1.0);
+    }
+
+    /// Gets the bit depth
+    pub fn get_bit_depth(&self) -> f32 {
+        self.bits
+    }
+
+    /// Sets the sample-and-hold rate
+    ///
+    /// # Arguments
+    /// * `rate` - Rate in Hz (200 to the sample rate; the sample rate = off)
+    pub fn set_downsample(&mut self, rate: f32) {
+        self.target_rate = rate.clamp(200.0, self.sample_rate as f32);
+    }
+
+    /// Gets the sample-and-hold rate in Hz
+    pub fn get_downsample(&self) -> f32 {
+        self.target_rate
+    }
+
+    /// Sets the dry/wet mix
+    ///
+    /// # Arguments
+    /// * `mix` - Dry/wet mix (0.0 = dry only (bypass), 1.0 = wet only)
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix.set_target(mix.clamp(0.0, 1.0));
+    }
+
+    /// Gets the dry/wet mix
+    pub fn get_mix(&self) -> f32 {
+        self.mix.target()
+    }
+
+    /// Sets how long mix and drive changes take to settle
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.mix.set_smoothing_time(time_ms);
+        self.drive.set_smoothing_time(time_ms);
+    }
+
+    /// Processes a stereo pair in place
+    ///
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if self.mix.target() == 0.0 && !self.mix.is_smoothing() {
+            if !self.silent {
+                self.reset();
+            }
+            return;
+        }
+        self.silent = false;
+
+        let saturate = self.shaper.target() > 0.0 || self.shaper.is_smoothing();
+        let crush = self.bits < MAX_BITS;
+        let hold_step = self.target_rate / self.sample_rate as f32;
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let mix = self.mix.next_value();
+            let drive = self.drive.next_value();
+            let shaper = self.shaper.next_value();
+            let sample_new = self.hold_phase >= 1.0;
+            if sample_new {
+                self.hold_phase -= 1.0;
+            }
+            self.hold_phase += hold_step;
+
+            for (sample, channel) in [l, r].into_iter().zip(self.channels.iter_mut()) {
+                let input = *sample;
+                let mut dry = input;
+                let mut wet = input;
+                channel.dry_delay.write(input);
+                if saturate {
+                    let shaped = channel.saturate(input * drive, self.saturation);
+                    let delayed = channel.dry_delay.read(self.dry_delay + 1.0);
+
+                    // Removes the offset the tube curve leaves behind
+                    let blocked = shaped - channel.dc_input + self.dc_coefficient * channel.dc_output;
+                    channel.dc_input = shaped;
+                    channel.dc_output = blocked;
+
+                    wet = input + (blocked - input) * shaper;
+                    dry = input + (delayed - input) * shaper;
+                }
+                if crush {
+                    wet = (wet * self.levels).round() / self.levels;
+                }
+                if sample_new {
+                    channel.held = wet;
+                }
+                *sample = dry + (channel.held - dry) * mix;
+            }
+        }
+    }
+
+    /// Clears the filter and hold state
+    pub fn reset(&mut self) {
+        for channel in self.channels.iter_mut() {
+            channel.reset();
+        }
+        self.shaper.set_immediate(self.shaper.target());
+        self.hold_phase = 1.0;
+        self.silent = true;
+    }
+}
+
+/// Group delay of a biquad at DC in samples: the difference between the
+/// centres of mass of its numerator and denominator taps
+fn dc_group_delay(c: &BiquadCoefficients) -> f32 {
+    let zeros = (c.b1 + 2.0 * c.b2) / (c.b0 + c.b1 + c.b2);
+    let poles = (c.a1 + 2.0 * c.a2) / (1.0 + c.a1 + c.a2);
+    zeros - poles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Level of one frequency in a signal (Goertzel)
+    fn level_at(signal: &[f32], freq: f32, sample_rate: f32) -> f32 {
+        let coefficient = 2.0 * (TAU * freq / sample_rate).cos();
+        let (mut s1, mut s2) = (0.0f32, 0.0f32);
+        for &x in signal {
+            let s0 = x + coefficient * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        (s1 * s1 + s2 * s2 - coefficient * s1 * s2).sqrt() * 2.0 / signal.len() as f32
+    }
+
+    fn wet_only() -> LoFi {
+        let mut lofi = LoFi::new(48000);
+        lofi.set_smoothing_time(0.0);
+        lofi.set_mix(1.0);
+        lofi
+    }
+
+    #[test]
+    fn test_zero_mix_is_bypass() {
+        let mut lofi = LoFi::new(48000);
+        lofi.set_drive(24.0);
+        lofi.set_bit_depth(4.0);
+        let input: Vec<f32> = (0..512).map(|i| (i as f32 * 0.1).sin()).collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+        lofi.process_stereo(&mut left, &mut right);
+        assert_eq!(left, input);
+    }
+
+    #[test]
+    fn test_bit_depth_quantizes() {
+        let mut lofi = wet_only();
+        lofi.set_bit_depth(3.0);
+
+        // 3 bits: steps of 1/4
+        let mut left = vec![0.3, -0.6, 0.9, 0.1];
+        let mut right = left.clone();
+        lofi.process_stereo(&mut left, &mut right);
+        assert_eq!(left, vec![0.25, -0.5, 1.0, 0.0]);
+
+        lofi.set_bit_depth(0.0);
+        assert_eq!(lofi.get_bit_depth(), 1.0);
+    }
+
+    #[test]
+    fn test_sample_and_hold() {
+        let mut lofi = wet_only();
+        lofi.set_downsample(12000.0);
+
+        // A quarter of the sample rate: each sample held for four
+        let mut left: Vec<f32> = (0..64).map(|i| i as f32 / 64.0).collect();
+        let mut right = left.clone();
+        lofi.process_stereo(&mut left, &mut right);
+        for (i, &sample) in left.iter().enumerate() {
+            assert_eq!(sample, (i - i % 4) as f32 / 64.0);
+        }
+    }
+
+    #[test]
+    fn test_partial_mix_does_not_comb() {
+        let mut lofi = LoFi::new(48000);
+        lofi.set_smoothing_time(0.0);
+        lofi.set_mix(0.5);
+        lofi.set_drive(0.5);
+        assert!((lofi.dry_delay - 3.9).abs() < 0.3, "{}", lofi.dry_delay);
+
+        // Barely driven, the wet signal is the dry one, four samples late;
+        // uncompensated they would cancel around 6 kHz
+        for freq in [1000.0, 6000.0, 12000.0] {
+            let mut left: Vec<f32> = (0..9600)
+                .map(|i| (TAU * freq * i as f32 / 48000.0).sin() * 0.1)
+                .collect();
+            let mut right = left.clone();
+            lofi.process_stereo(&mut left, &mut right);
+            let level = level_at(&left[4800..], freq, 48000.0);
+            assert!(level > 0.09, "{} Hz: {}", freq, level);
+        }
+    }
+
+    #[test]
+    fn test_drive_engages_smoothly() {
+        let mut lofi = LoFi::new(48000);
+        lofi.set_mix(0.5);
+        let input: Vec<f32> = (0..24000)
+            .map(|i| (TAU * 1000.0 * i as f32 / 48000.0).sin() * 0.5)
+            .collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+
+        // Drive off and on again, with stale filter state from the first go
+        for (block, (l, r)) in left.chunks_mut(128).zip(right.chunks_mut(128)).enumerate() {
+            match block {
+                20 => lofi.set_drive(6.0),
+                60 => lofi.set_drive(0.0),
+                100 => lofi.set_drive(6.0),
+                _ => {}
+            }
+            lofi.process_stereo(l, r);
+        }
+
+        // Settled, the largest step is that of the driven tone itself
+        let steps: Vec<f32> = left.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
+        let settled = steps[16000..].iter().fold(0.0f32, |m, &s| m.max(s));
+        let largest = steps[1000..].iter().fold(0.0f32, |m, &s| m.max(s));
+        assert!(largest < settled * 1.05, "{} vs {}", largest, settled);
+    }
+
+    #[test]
+    fn test_oversampling_reduces_aliasing() {
+        let mut lofi = wet_only();
+        lofi.set_drive(24.0);
+
+        // Hard-driven 10 kHz: the 3rd harmonic (30 kHz) would alias to 18 kHz
+        let input: Vec<f32> = (0..9600)
+            .map(|i| (TAU * 10000.0 * i as f32 / 48000.0).sin() * 0.5)
+            .collect();
+        let naive: Vec<f32> = input.iter().map(|&x| soft_clip(x * lofi.drive.current())).collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+        lofi.process_stereo(&mut left, &mut right);
+
+        let aliased = level_at(&naive[4800..], 18000.0, 48000.0);
+        let oversampled = level_at(&left[4800..], 18000.0, 48000.0);
+        assert!(aliased > 0.1, "{}", aliased);
+        assert!(oversampled < aliased * 0.03, "{} vs {}", oversampled, aliased);
+        assert!(level_at(&left[4800..], 10000.0, 48000.0) > 0.5);
+
+        for saturation in [SaturationType::Tube, SaturationType::Foldback] {
+            lofi.set_saturation(saturation);
+            lofi.process_stereo(&mut left, &mut right);
+            assert!(left.iter().all(|s| s.is_finite() && s.abs() < 2.0));
+        }
+    }
+}
@@ -0,0 +1,1107 @@
+//! Multi-deck mixer
+//!
+//! Owns one [`AudioProcessor`] per deck and sums them into a master stereo
+//! output in a single call, so channel faders and the crossfader act with
+//! sample accuracy instead of across separate Web Audio nodes.
+//!
+//! ```text
+//! Deck 1 → Latency Pad → Channel Volume ─┐
+//! Deck 2 → Latency Pad → Channel Volume ─┼→ A / B / Thru buses → Crossfader → Compressor → Master EQ → Master Volume → Limiter → Output
+//! Deck N → Latency Pad → Channel Volume ─┘
+//! ```
+//!
+//! Decks run only their channel strip (see
+//! [`AudioProcessor::render_channel`]); the master EQ, master volume and
+//! limiter are applied once, here, to the summed mix. The decks' own master
+//! settings have no effect inside the mixer.
+//!
+//! A deck's channel latency depends on its key lock and time stretch
+//! engine. Every deck is delayed to match the slowest one, so decks fed
+//! the same source position stay aligned on the master; changes of that
+//! padding crossfade over a few milliseconds.
+//!
+//! Each deck is assigned to the A side, the B side or "thru" (bypassing the
+//! crossfader). The crossfader blends the A and B buses along its selected
+//! [`FaderCurve`]; thru decks are always heard at their channel volume.
+//!
+//! A second stereo output, the headphone cue bus, sums the decks flagged for
+//! pre-fader listen (PFL) ahead of their channel faders, blended with the
+//! master mix by the cue mix knob. In split-cue mode the cue bus carries the
+//! PFL decks on the left ear and the master on the right, each mono-summed.
+//! The cue bus hears the master as it leaves the limiter, and the PFL sum
+//! is delayed by the limiter lookahead to stay in time with it.
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(decks × frames), plus each deck's own processing
+//! - Memory: ~100KB of scratch buffers plus ~3MB per deck, and a latency
+//!   pad per deck (~27KB at an FFT size of 1024)
+//! - Latency: the slowest deck's time stretch and pitch shift plus the
+//!   master limiter lookahead (see [`Mixer::get_latency_frames`])
+
+use wasm_bindgen::prelude::*;
+
+use crate::compressor::{Compressor, DynamicsMode};
+use crate::delay_line::DelayLine;
+use crate::fader::{Fader, FaderCurve};
+use crate::limiter::Limiter;
+use crate::parametric_eq::{EqBand, FilterType, ParametricEq};
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+use crate::{AudioProcessor, MAX_FRAME_SIZE};
+
+/// Maximum number of decks
+pub const MAX_DECKS: usize = 8;
+
+/// Crossfade time when a latency pad changes length (ms)
+const PAD_FADE_MS: f32 = 10.0;
+
+/// Crossfader side a deck is routed to
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrossfaderAssign {
+    /// Bypasses the crossfader
+    Thru = 0,
+    /// Left side of the crossfader
+    A = 1,
+    /// Right side of the crossfader
+    B = 2,
+}
+
+/// A deck with its channel strip settings
+struct MixerChannel {
+    processor: AudioProcessor,
+    pad: LatencyPad,
+    volume: SmoothedParam,
+    assign: CrossfaderAssign,
+    cue: bool,
+}
+
+/// Stereo delay that lines a deck up with a slower one
+///
+/// A change of delay crossfades from the old read position to the new one.
+struct LatencyPad {
+    lines: [DelayLine; 2],
+    delay: usize,
+    previous: usize,
+    fade: SmoothedParam,
+}
+
+impl LatencyPad {
+    fn new(sample_rate: u32, max_delay: usize, delay: usize) -> Self {
+        LatencyPad {
+            lines: [DelayLine::new(max_delay + 1), DelayLine::new(max_delay + 1)],
+            delay,
+            previous: delay,
+            fade: SmoothedParam::new(1.0, sample_rate, PAD_FADE_MS),
+        }
+    }
+
+    fn set_delay(&mut self, delay: usize) {
+        if delay == self.delay {
+            return;
+        }
+
+        self.previous = self.delay;
+        self.delay = delay;
+        self.fade.set_immediate(0.0);
+        self.fade.set_target(1.0);
+    }
+
+    /// Delays a stereo block in place
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let [line_left, line_right] = &mut self.lines;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            line_left.write(*l);
+            line_right.write(*r);
+
+            // `read(1.0)` is the sample just written
+            let weight = self.fade.next_value();
+            let tap = |line: &DelayLine| {
+                let current = line.read(self.delay as f32 + 1.0);
+                if weight < 1.0 {
+                    let previous = line.read(self.previous as f32 + 1.0);
+                    previous + (current - previous) * weight
+                } else {
+                    current
+                }
+            };
+            *l = tap(line_left);
+            *r = tap(line_right);
+        }
+    }
+}
+
+/// Mixer summing N decks through channel faders and a crossfader
+///
+/// Decks start at full channel volume, assigned thru and not cued, with the
+/// crossfader centred on the constant-power curve. The cue mix starts at cue
+/// only.
+///
+/// # Performance
+/// - No allocations in the hot path beyond the returned output block, once
+///   each deck's time-stretch queues have grown to fit the largest block
+#[wasm_bindgen]
+pub struct Mixer {
+    sample_rate: u32,
+    channels: Vec<MixerChannel>,
+    crossfader: Fader,
+    compressor: Compressor,
+    master_eq: ParametricEq,
+    master_volume: SmoothedParam,
+    limiter: Limiter,
+    smoothing_ms: f32,
+
+    // Headphone cue: the mix knob is a fader from cue (A side) to master (B side)
+    cue_mix: Fader,
+    cue_volume: SmoothedParam,
+    split_cue: bool,
+    pfl_pad: LatencyPad,
+    cue_output: Vec<f32>,
+    cue_frames: usize,
+
+    // Scratch buffers: one deck's output, the Thru/A/B buses
+    // (indexed by `CrossfaderAssign as usize`) and the PFL sum
+    deck_left: Vec<f32>,
+    deck_right: Vec<f32>,
+    bus_left: [Vec<f32>; 3],
+    bus_right: [Vec<f32>; 3],
+    pfl_left: Vec<f32>,
+    pfl_right: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl Mixer {
+    /// Creates a mixer with `deck_count` decks
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz (typically 48000)
+    /// * `fft_size` - FFT size for each deck's phase vocoder (256-4096)
+    /// * `deck_count` - Number of decks (1-8)
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: u32, fft_size: usize, deck_count: usize) -> Result<Mixer, JsValue> {
+        if !(1..=MAX_DECKS).contains(&deck_count) {
+            return Err(JsValue::from_str("Deck count must be between 1 and 8"));
+        }
+
+        let mut channels = Vec::with_capacity(deck_count);
+        for _ in 0..deck_count {
+            let processor = AudioProcessor::new(sample_rate, fft_size)?;
+            channels.push(MixerChannel {
+                pad: LatencyPad::new(sample_rate, processor.max_channel_latency_frames(), 0),
+                processor,
+                volume: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+                assign: CrossfaderAssign::Thru,
+                cue: false,
+            });
+        }
+
+        let mut cue_mix = Fader::new(sample_rate);
+        cue_mix.set_smoothing_time(0.0);
+        cue_mix.set_position(-1.0);
+        cue_mix.set_smoothing_time(DEFAULT_SMOOTHING_MS);
+
+        let limiter = Limiter::new(sample_rate);
+        let lookahead = limiter.latency();
+
+        Ok(Mixer {
+            sample_rate,
+            channels,
+            crossfader: Fader::new(sample_rate),
+            compressor: Compressor::new(sample_rate),
+            master_eq: ParametricEq::new(sample_rate),
+            master_volume: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            limiter,
+            smoothing_ms: DEFAULT_SMOOTHING_MS,
+            cue_mix,
+            cue_volume: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            split_cue: false,
+            pfl_pad: LatencyPad::new(sample_rate, lookahead, lookahead),
+            cue_output: vec![0.0; MAX_FRAME_SIZE * 2],
+            cue_frames: 0,
+            deck_left: vec![0.0; MAX_FRAME_SIZE],
+            deck_right: vec![0.0; MAX_FRAME_SIZE],
+            bus_left: [vec![0.0; MAX_FRAME_SIZE], vec![0.0; MAX_FRAME_SIZE], vec![0.0; MAX_FRAME_SIZE]],
+            bus_right: [vec![0.0; MAX_FRAME_SIZE], vec![0.0; MAX_FRAME_SIZE], vec![0.0; MAX_FRAME_SIZE]],
+            pfl_left: vec![0.0; MAX_FRAME_SIZE],
+            pfl_right: vec![0.0; MAX_FRAME_SIZE],
+        })
+    }
+
+    /// Gets the number of decks
+    #[wasm_bindgen]
+    pub fn get_deck_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Gets the sample rate in Hz
+    #[wasm_bindgen]
+    pub fn get_sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns deck `deck`'s channel latency in frames (0 for an unknown deck)
+    ///
+    /// Before padding; see [`AudioProcessor::get_channel_latency_frames`].
+    #[wasm_bindgen]
+    pub fn get_deck_latency_frames(&self, deck: usize) -> usize {
+        self.channels
+            .get(deck)
+            .map_or(0, |channel| channel.processor.get_channel_latency_frames())
+    }
+
+    /// Returns the master output latency in frames
+    ///
+    /// The slowest deck's channel latency, which every deck is padded to,
+    /// plus the limiter lookahead.
+    #[wasm_bindgen]
+    pub fn get_latency_frames(&self) -> usize {
+        self.padded_latency() + self.limiter.latency()
+    }
+
+    /// Returns how many input frames deck `deck` needs for `output_frames`
+    ///
+    /// Depends on the deck's tempo; see
+    /// [`AudioProcessor::get_input_frames_required`]. Returns 0 for an
+    /// unknown deck.
+    #[wasm_bindgen]
+    pub fn get_deck_input_frames_required(&self, deck: usize, output_frames: usize) -> usize {
+        self.channels
+            .get(deck)
+            .map_or(0, |channel| channel.processor.get_input_frames_required(output_frames))
+    }
+
+    /// Returns the total input length [`Mixer::process`] expects
+    ///
+    /// The sum over all decks of left plus right input samples.
+    #[wasm_bindgen]
+    pub fn get_input_samples_required(&self, output_frames: usize) -> usize {
+        self.channels
+            .iter()
+            .map(|channel| 2 * channel.processor.get_input_frames_required(output_frames))
+            .sum()
+    }
+
+    /// Renders every deck and mixes them to the master output
+    ///
+    /// The input holds each deck's source audio as planar blocks, deck by
+    /// deck: `[deck 0 left, deck 0 right, deck 1 left, deck 1 right, ...]`,
+    /// where every block of deck `i` is
+    /// [`Mixer::get_deck_input_frames_required`]`(i, output_frames)` long.
+    /// A deck whose input runs short is silent for the missing part.
+    ///
+    /// The matching block of the cue bus is available afterwards from
+    /// [`Mixer::get_cue_output`].
+    ///
+    /// # Arguments
+    /// * `input` - Planar source audio for all decks
+    /// * `output_frames` - Number of stereo frames to output (1-4096)
+    ///
+    /// # Returns
+    /// Interleaved stereo master output of `output_frames * 2` samples
+    #[wasm_bindgen]
+    pub fn process(&mut self, input: &[f32], output_frames: usize) -> Box<[f32]> {
+        if output_frames == 0 || output_frames > MAX_FRAME_SIZE {
+            return Box::new([]);
+        }
+
+        let frames = output_frames;
+        for bus in self.bus_left.iter_mut().chain(self.bus_right.iter_mut()) {
+            bus[..frames].fill(0.0);
+        }
+        self.pfl_left[..frames].fill(0.0);
+        self.pfl_right[..frames].fill(0.0);
+
+        let padded = self.padded_latency();
+        let mut offset = 0;
+        for channel in self.channels.iter_mut() {
+            let required = channel.processor.get_input_frames_required(frames);
+            let input_left = planar_block(input, offset, required);
+            let input_right = planar_block(input, offset + required, required);
+            offset += 2 * required;
+
+            let left = &mut self.deck_left[..frames];
+            let right = &mut self.deck_right[..frames];
+            channel.processor.render_channel(input_left, input_right, left, right);
+            channel
+                .pad
+                .set_delay(padded - channel.processor.get_channel_latency_frames());
+            channel.pad.process(left, right);
+
+            // PFL taps the deck ahead of its channel fader
+            if channel.cue {
+                for i in 0..frames {
+                    self.pfl_left[i] += left[i];
+                    self.pfl_right[i] += right[i];
+                }
+            }
+
+            let bus = channel.assign as usize;
+            let bus_left = &mut self.bus_left[bus][..frames];
+            let bus_right = &mut self.bus_right[bus][..frames];
+            for i in 0..frames {
+                let volume = channel.volume.next_value();
+                bus_left[i] += left[i] * volume;
+                bus_right[i] += right[i] * volume;
+            }
+        }
+
+        // Master mix is built in the (now free) deck scratch buffers
+        let [thru_left, a_left, b_left] = &self.bus_left;
+        let [thru_right, a_right, b_right] = &self.bus_right;
+        for i in 0..frames {
+            let (a_gain, b_gain) = self.crossfader.next_gains();
+            self.deck_left[i] = thru_left[i] + a_left[i] * a_gain + b_left[i] * b_gain;
+            self.deck_right[i] = thru_right[i] + a_right[i] * a_gain + b_right[i] * b_gain;
+        }
+
+        let (left, right) = (&mut self.deck_left[..frames], &mut self.deck_right[..frames]);
+        self.compressor.process_stereo(left, right);
+        self.master_eq.process_stereo(left, right);
+        for i in 0..frames {
+            let volume = self.master_volume.next_value();
+            left[i] *= volume;
+            right[i] *= volume;
+        }
+        self.limiter.process_stereo(left, right);
+
+        // Headphones hear the finished master, with PFL delayed to match the limiter
+        let (pfl_left, pfl_right) = (&mut self.pfl_left[..frames], &mut self.pfl_right[..frames]);
+        self.pfl_pad.process(pfl_left, pfl_right);
+        for i in 0..frames {
+            let (cue_gain, master_gain) = self.cue_mix.next_gains();
+            let (cue_left, cue_right) = if self.split_cue {
+                ((pfl_left[i] + pfl_right[i]) * 0.5, (left[i] + right[i]) * 0.5)
+            } else {
+                (
+                    pfl_left[i] * cue_gain + left[i] * master_gain,
+                    pfl_right[i] * cue_gain + right[i] * master_gain,
+                )
+            };
+
+            let cue_volume = self.cue_volume.next_value();
+            self.cue_output[i * 2] = cue_left * cue_volume;
+            self.cue_output[i * 2 + 1] = cue_right * cue_volume;
+        }
+        self.cue_frames = frames;
+
+        let mut output = vec![0.0; frames * 2];
+        for i in 0..frames {
+            output[i * 2] = left[i];
+            output[i * 2 + 1] = right[i];
+        }
+
+        output.into_boxed_slice()
+    }
+
+    // ===== Channel Control =====
+
+    /// Sets a deck's channel fader
+    ///
+    /// # Arguments
+    /// * `deck` - Deck index
+    /// * `volume` - Volume in linear units (0.0-1.0)
+    #[wasm_bindgen]
+    pub fn set_channel_volume(&mut self, deck: usize, volume: f32) {
+        if let Some(channel) = self.channels.get_mut(deck) {
+            channel.volume.set_target(volume.clamp(0.0, 1.0));
+        }
+    }
+
+    /// Gets a deck's channel fader (0 for an unknown deck)
+    #[wasm_bindgen]
+    pub fn get_channel_volume(&self, deck: usize) -> f32 {
+        self.channels.get(deck).map_or(0.0, |channel| channel.volume.target())
+    }
+
+    /// Routes a deck to a crossfader side
+    #[wasm_bindgen]
+    pub fn set_crossfader_assign(&mut self, deck: usize, assign: CrossfaderAssign) {
+        if let Some(channel) = self.channels.get_mut(deck) {
+            channel.assign = assign;
+        }
+    }
+
+    /// Gets a deck's crossfader side (thru for an unknown deck)
+    #[wasm_bindgen]
+    pub fn get_crossfader_assign(&self, deck: usize) -> CrossfaderAssign {
+        self.channels
+            .get(deck)
+            .map_or(CrossfaderAssign::Thru, |channel| channel.assign)
+    }
+
+    /// Sets a deck's pre-fader listen (cue) flag
+    #[wasm_bindgen]
+    pub fn set_cue(&mut self, deck: usize, enabled: bool) {
+        if let Some(channel) = self.channels.get_mut(deck) {
+            channel.cue = enabled;
+        }
+    }
+
+    /// Gets a deck's cue flag (false for an unknown deck)
+    #[wasm_bindgen]
+    pub fn get_cue(&self, deck: usize) -> bool {
+        self.channels.get(deck).is_some_and(|channel| channel.cue)
+    }
+
+    // ===== Crossfader Control =====
+
+    /// Sets the crossfader position
+    ///
+    /// # Arguments
+    /// * `position` - Crossfader position (-1.0 to 1.0)
+    ///   - -1.0 = A side only
+    ///   - 0.0 = centre
+    ///   - 1.0 = B side only
+    #[wasm_bindgen]
+    pub fn set_crossfader_position(&mut self, position: f32) {
+        self.crossfader.set_position(position);
+    }
+
+    /// Gets the crossfader position
+    #[wasm_bindgen]
+    pub fn get_crossfader_position(&self) -> f32 {
+        self.crossfader.get_position()
+    }
+
+    /// Sets the crossfade curve
+    #[wasm_bindgen]
+    pub fn set_crossfader_curve(&mut self, curve: FaderCurve) {
+        self.crossfader.set_curve(curve);
+    }
+
+    /// Gets the crossfade curve
+    #[wasm_bindgen]
+    pub fn get_crossfader_curve(&self) -> FaderCurve {
+        self.crossfader.get_curve()
+    }
+
+    /// Sets the slope of the custom crossfade curve
+    ///
+    /// # Arguments
+    /// * `slope` - Curve slope (0.0 = constant power, 1.0 = sharp cut)
+    #[wasm_bindgen]
+    pub fn set_crossfader_curve_slope(&mut self, slope: f32) {
+        self.crossfader.set_curve_slope(slope);
+    }
+
+    /// Gets the slope of the custom crossfade curve
+    #[wasm_bindgen]
+    pub fn get_crossfader_curve_slope(&self) -> f32 {
+        self.crossfader.get_curve_slope()
+    }
+
+    // ===== Master Volume Control =====
+
+    /// Sets the master volume
+    ///
+    /// # Arguments
+    /// * `volume` - Volume in linear units (0.0-2.0)
+    #[wasm_bindgen]
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.master_volume.set_target(volume.clamp(0.0, 2.0));
+    }
+
+    /// Gets the master volume
+    #[wasm_bindgen]
+    pub fn get_master_volume(&self) -> f32 {
+        self.master_volume.target()
+    }
+
+    /// Sets the master limiter ceiling
+    ///
+    /// # Arguments
+    /// * `db` - Ceiling in dBFS (-24.0 to 0.0, default -0.3)
+    #[wasm_bindgen]
+    pub fn set_limiter_ceiling(&mut self, db: f32) {
+        self.limiter.set_ceiling(db);
+    }
+
+    /// Gets the master limiter ceiling in dBFS
+    #[wasm_bindgen]
+    pub fn get_limiter_ceiling(&self) -> f32 {
+        self.limiter.get_ceiling()
+    }
+
+    /// Sets the master limiter release time (10-1000ms, default 100)
+    #[wasm_bindgen]
+    pub fn set_limiter_release(&mut self, time_ms: f32) {
+        self.limiter.set_release(time_ms);
+    }
+
+    /// Gets the master limiter release time in milliseconds
+    #[wasm_bindgen]
+    pub fn get_limiter_release(&self) -> f32 {
+        self.limiter.get_release()
+    }
+
+    /// Gets the largest master limiter gain reduction in the last block (positive dB)
+    #[wasm_bindgen]
+    pub fn get_gain_reduction(&self) -> f32 {
+        self.limiter.gain_reduction_db()
+    }
+
+    // ===== Master EQ Control =====
+    //
+    // See the matching `AudioProcessor` methods for ranges.
+
+    /// Adds a master EQ band
+    ///
+    /// Returns the band index, or `undefined` if all 16 bands are in use.
+    #[wasm_bindgen]
+    pub fn add_master_eq_band(&mut self, filter_type: FilterType, frequency: f32, q: f32, gain_db: f32) -> Option<usize> {
+        self.master_eq.add_band(EqBand::new(filter_type, frequency, q, gain_db))
+    }
+
+    /// Updates master EQ band `index`
+    ///
+    /// Returns `false` if there is no such band.
+    #[wasm_bindgen]
+    pub fn set_master_eq_band(&mut self, index: usize, filter_type: FilterType, frequency: f32, q: f32, gain_db: f32) -> bool {
+        self.master_eq.set_band(index, EqBand::new(filter_type, frequency, q, gain_db))
+    }
+
+    /// Removes master EQ band `index`; later bands move down by one
+    #[wasm_bindgen]
+    pub fn remove_master_eq_band(&mut self, index: usize) -> bool {
+        self.master_eq.remove_band(index).is_some()
+    }
+
+    /// Removes all master EQ bands
+    #[wasm_bindgen]
+    pub fn clear_master_eq(&mut self) {
+        self.master_eq.clear();
+    }
+
+    /// Gets the number of master EQ bands
+    #[wasm_bindgen]
+    pub fn get_master_eq_band_count(&self) -> usize {
+        self.master_eq.band_count()
+    }
+
+    /// Gets the combined master EQ response in dB at each frequency
+    #[wasm_bindgen]
+    pub fn get_master_eq_response(&self, frequencies: &[f32]) -> Box<[f32]> {
+        self.master_eq.magnitude_response(frequencies).into_boxed_slice()
+    }
+
+    // ===== Master Compressor Control =====
+    //
+    // See the matching `AudioProcessor::set_compressor_*` methods for ranges;
+    // a ratio of 1 (the default) bypasses the compressor.
+
+    /// Sets master compression or expansion
+    #[wasm_bindgen]
+    pub fn set_master_compressor_mode(&mut self, mode: DynamicsMode) {
+        self.compressor.set_mode(mode);
+    }
+
+    /// Gets the master compressor mode
+    #[wasm_bindgen]
+    pub fn get_master_compressor_mode(&self) -> DynamicsMode {
+        self.compressor.get_mode()
+    }
+
+    /// Sets the master compressor threshold in dBFS
+    #[wasm_bindgen]
+    pub fn set_master_compressor_threshold(&mut self, db: f32) {
+        self.compressor.set_threshold(db);
+    }
+
+    /// Gets the master compressor threshold in dBFS
+    #[wasm_bindgen]
+    pub fn get_master_compressor_threshold(&self) -> f32 {
+        self.compressor.get_threshold()
+    }
+
+    /// Sets the master compressor ratio
+    #[wasm_bindgen]
+    pub fn set_master_compressor_ratio(&mut self, ratio: f32) {
+        self.compressor.set_ratio(ratio);
+    }
+
+    /// Gets the master compressor ratio
+    #[wasm_bindgen]
+    pub fn get_master_compressor_ratio(&self) -> f32 {
+        self.compressor.get_ratio()
+    }
+
+    /// Sets the master compressor knee width in dB
+    #[wasm_bindgen]
+    pub fn set_master_compressor_knee(&mut self, db: f32) {
+        self.compressor.set_knee(db);
+    }
+
+    /// Gets the master compressor knee width in dB
+    #[wasm_bindgen]
+    pub fn get_master_compressor_knee(&self) -> f32 {
+        self.compressor.get_knee()
+    }
+
+    /// Sets the master compressor attack time in milliseconds
+    #[wasm_bindgen]
+    pub fn set_master_compressor_attack(&mut self, time_ms: f32) {
+        self.compressor.set_attack(time_ms);
+    }
+
+    /// Gets the master compressor attack time in milliseconds
+    #[wasm_bindgen]
+    pub fn get_master_compressor_attack(&self) -> f32 {
+        self.compressor.get_attack()
+    }
+
+    /// Sets the master compressor release time in milliseconds
+    #[wasm_bindgen]
+    pub fn set_master_compressor_release(&mut self, time_ms: f32) {
+        self.compressor.set_release(time_ms);
+    }
+
+    /// Gets the master compressor release time in milliseconds
+    #[wasm_bindgen]
+    pub fn get_master_compressor_release(&self) -> f32 {
+        self.compressor.get_release()
+    }
+
+    /// Sets the master compressor makeup gain in dB
+    #[wasm_bindgen]
+    pub fn set_master_compressor_makeup(&mut self, db: f32) {
+        self.compressor.set_makeup_gain(db);
+    }
+
+    /// Gets the master compressor makeup gain in dB
+    #[wasm_bindgen]
+    pub fn get_master_compressor_makeup(&self) -> f32 {
+        self.compressor.get_makeup_gain()
+    }
+
+    /// Sets the master compressor sidechain high-pass in Hz (0 = off)
+    #[wasm_bindgen]
+    pub fn set_master_compressor_sidechain_highpass(&mut self, freq: f32) {
+        self.compressor.set_sidechain_highpass(freq);
+    }
+
+    /// Gets the master compressor sidechain high-pass in Hz (0 = off)
+    #[wasm_bindgen]
+    pub fn get_master_compressor_sidechain_highpass(&self) -> f32 {
+        self.compressor.get_sidechain_highpass()
+    }
+
+    /// Gets the largest master compressor gain reduction in the last block (positive dB)
+    #[wasm_bindgen]
+    pub fn get_master_compressor_gain_reduction(&self) -> f32 {
+        self.compressor.gain_reduction_db()
+    }
+
+    // ===== Headphone Cue Control =====
+
+    /// Sets the cue mix knob
+    ///
+    /// Blends the cued decks with the master mix along a constant-power
+    /// curve. Ignored in split-cue mode.
+    ///
+    /// # Arguments
+    /// * `mix` - Cue/master blend (0.0 to 1.0)
+    ///   - 0.0 = cue only
+    ///   - 0.5 = equal blend
+    ///   - 1.0 = master only
+    #[wasm_bindgen]
+    pub fn set_cue_mix(&mut self, mix: f32) {
+        self.cue_mix.set_position(mix.clamp(0.0, 1.0) * 2.0 - 1.0);
+    }
+
+    /// Gets the cue mix knob
+    #[wasm_bindgen]
+    pub fn get_cue_mix(&self) -> f32 {
+        (self.cue_mix.get_position() + 1.0) * 0.5
+    }
+
+    /// Sets the headphone volume
+    ///
+    /// # Arguments
+    /// * `volume` - Volume in linear units (0.0-2.0)
+    #[wasm_bindgen]
+    pub fn set_cue_volume(&mut self, volume: f32) {
+        self.cue_volume.set_target(volume.clamp(0.0, 2.0));
+    }
+
+    /// Gets the headphone volume
+    #[wasm_bindgen]
+    pub fn get_cue_volume(&self) -> f32 {
+        self.cue_volume.target()
+    }
+
+    /// Enables or disables split cue
+    ///
+    /// When on, the left ear hears the cued decks and the right ear the
+    /// master, each summed to mono.
+    #[wasm_bindgen]
+    pub fn set_split_cue(&mut self, enabled: bool) {
+        self.split_cue = enabled;
+    }
+
+    /// Gets whether split cue is on
+    #[wasm_bindgen]
+    pub fn get_split_cue(&self) -> bool {
+        self.split_cue
+    }
+
+    /// Returns the cue bus block rendered by the last [`Mixer::process`]
+    ///
+    /// # Returns
+    /// Interleaved stereo headphone output, the same length as the master
+    /// block (empty before the first call)
+    #[wasm_bindgen]
+    pub fn get_cue_output(&self) -> Box<[f32]> {
+        self.cue_output().into()
+    }
+
+    // ===== Parameter Smoothing =====
+
+    /// Sets how long fader and master section changes take to settle, on the
+    /// mixer and every deck
+    ///
+    /// # Arguments
+    /// * `time_ms` - Smoothing time in milliseconds (0-1000, default 20)
+    #[wasm_bindgen]
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.smoothing_ms = time_ms.clamp(0.0, 1000.0);
+        self.crossfader.set_smoothing_time(self.smoothing_ms);
+        self.master_volume.set_smoothing_time(self.smoothing_ms);
+        self.cue_mix.set_smoothing_time(self.smoothing_ms);
+        self.cue_volume.set_smoothing_time(self.smoothing_ms);
+        self.compressor.set_smoothing_time(self.smoothing_ms);
+        self.master_eq.set_smoothing_time(self.smoothing_ms);
+        for channel in self.channels.iter_mut() {
+            channel.volume.set_smoothing_time(self.smoothing_ms);
+            channel.processor.set_smoothing_time(self.smoothing_ms);
+        }
+    }
+
+    /// Gets the parameter smoothing time in milliseconds
+    #[wasm_bindgen]
+    pub fn get_smoothing_time(&self) -> f32 {
+        self.smoothing_ms
+    }
+
+    // ===== Deck Control =====
+    //
+    // Forwarders to the deck processors; calls for an unknown deck are
+    // ignored. See the matching `AudioProcessor` methods for ranges.
+
+    /// Sets a deck's tempo ratio (0.5-2.0)
+    #[wasm_bindgen]
+    pub fn set_deck_tempo_ratio(&mut self, deck: usize, ratio: f32) {
+        if let Some(processor) = self.deck_mut(deck) {
+            processor.set_tempo_ratio(ratio);
+        }
+    }
+
+    /// Sets a deck's key lock
+    #[wasm_bindgen]
+    pub fn set_deck_key_lock(&mut self, deck: usize, enabled: bool) {
+        if let Some(processor) = self.deck_mut(deck) {
+            processor.set_key_lock(enabled);
+        }
+    }
+
+    /// Sets a deck's pitch shift in cents (-1200 to +1200)
+    #[wasm_bindgen]
+    pub fn set_deck_pitch_cents(&mut self, deck: usize, cents: f32) {
+        if let Some(processor) = self.deck_mut(deck) {
+            processor.set_pitch_cents(cents);
+        }
+    }
+
+    /// Sets a deck's trim (input gain, 0.0-2.0)
+    #[wasm_bindgen]
+    pub fn set_deck_trim(&mut self, deck: usize, gain: f32) {
+        if let Some(processor) = self.deck_mut(deck) {
+            processor.set_input_gain(gain);
+        }
+    }
+
+    /// Sets a deck's 3-band EQ gains in dB
+    #[wasm_bindgen]
+    pub fn set_deck_eq(&mut self, deck: usize, low_db: f32, mid_db: f32, high_db: f32) {
+        if let Some(processor) = self.deck_mut(deck) {
+            processor.set_low_gain(low_db);
+            processor.set_mid_gain(mid_db);
+            processor.set_high_gain(high_db);
+        }
+    }
+
+    /// Sets a deck's DJ filter knob (-1.0 low-pass to 1.0 high-pass)
+    #[wasm_bindgen]
+    pub fn set_deck_filter_position(&mut self, deck: usize, position: f32) {
+        if let Some(processor) = self.deck_mut(deck) {
+            processor.set_filter_position(position);
+        }
+    }
+
+    /// Sets a deck's pan position (-1.0 left to 1.0 right)
+    #[wasm_bindgen]
+    pub fn set_deck_pan(&mut self, deck: usize, position: f32) {
+        if let Some(processor) = self.deck_mut(deck) {
+            processor.set_pan(position);
+        }
+    }
+}
+
+impl Mixer {
+    /// Gets a deck's processor
+    pub fn deck(&self, deck: usize) -> Option<&AudioProcessor> {
+        self.channels.get(deck).map(|channel| &channel.processor)
+    }
+
+    /// The cue bus block rendered by the last [`Mixer::process`] (interleaved)
+    pub fn cue_output(&self) -> &[f32] {
+        &self.cue_output[..self.cue_frames * 2]
+    }
+
+    /// Gets a deck's processor for full control
+    pub fn deck_mut(&mut self, deck: usize) -> Option<&mut AudioProcessor> {
+        self.channels.get_mut(deck).map(|channel| &mut channel.processor)
+    }
+
+    /// Channel latency every deck is padded to
+    fn padded_latency(&self) -> usize {
+        self.channels
+            .iter()
+            .map(|channel| channel.processor.get_channel_latency_frames())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// The `len` samples of `input` starting at `start`, cut short at its end
+fn planar_block(input: &[f32], start: usize, len: usize) -> &[f32] {
+    let start = start.min(input.len());
+    let end = (start + len).min(input.len());
+    &input[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAMES: usize = 256;
+
+    /// Feeds every deck a constant level and returns the settled output
+    fn settle(mixer: &mut Mixer, levels: &[f32]) -> Box<[f32]> {
+        let mut output = Box::default();
+        for _ in 0..40 {
+            let mut input = Vec::new();
+            for (deck, &level) in levels.iter().enumerate() {
+                let required = mixer.get_deck_input_frames_required(deck, FRAMES);
+                input.resize(input.len() + 2 * required, level);
+            }
+            output = mixer.process(&input, FRAMES);
+        }
+        output
+    }
+
+    #[test]
+    fn test_mixer_creation() {
+        let mixer = Mixer::new(48000, 1024, 4).unwrap();
+        assert_eq!(mixer.get_deck_count(), 4);
+        assert_eq!(mixer.get_channel_volume(0), 1.0);
+        assert_eq!(mixer.get_crossfader_assign(3), CrossfaderAssign::Thru);
+        assert!(!mixer.get_cue(0));
+        assert_eq!(mixer.get_cue_mix(), 0.0);
+        assert!(mixer.cue_output().is_empty());
+        assert!(mixer.deck(4).is_none());
+    }
+
+    #[test]
+    fn test_thru_decks_sum() {
+        let mut mixer = Mixer::new(48000, 1024, 2).unwrap();
+        mixer.set_smoothing_time(0.0);
+        mixer.set_crossfader_position(1.0);
+
+        let output = settle(&mut mixer, &[0.1, 0.3]);
+        assert_eq!(output.len(), FRAMES * 2);
+        assert!((output[FRAMES * 2 - 2] - 0.4).abs() < 0.01);
+
+        mixer.set_channel_volume(1, 0.0);
+        let output = settle(&mut mixer, &[0.1, 0.3]);
+        assert!((output[FRAMES * 2 - 1] - 0.1).abs() < 0.01);
+
+        // Two loud decks at +6 dB master are held at the limiter ceiling
+        mixer.set_channel_volume(1, 1.0);
+        mixer.set_master_volume(2.0);
+        mixer.set_limiter_ceiling(-1.0);
+        let output = settle(&mut mixer, &[0.8, 0.8]);
+        let ceiling = crate::buffer_manager::sample_utils::db_to_linear(-1.0);
+        assert!(output.iter().all(|s| s.abs() <= ceiling));
+        assert!(mixer.get_gain_reduction() > 0.0);
+    }
+
+    #[test]
+    fn test_master_section_runs_on_the_mix() {
+        let mut mixer = Mixer::new(48000, 1024, 2).unwrap();
+        mixer.set_smoothing_time(0.0);
+
+        // A deck's own master volume is not part of its channel strip
+        mixer.deck_mut(0).unwrap().set_master_volume(0.0);
+        let output = settle(&mut mixer, &[0.1, 0.3]);
+        assert!((output[FRAMES * 2 - 2] - 0.4).abs() < 0.01);
+
+        // The mixer's master EQ is: a high-pass removes the DC mix
+        assert_eq!(mixer.add_master_eq_band(FilterType::HighPass, 1000.0, 0.707, 0.0), Some(0));
+        assert_eq!(mixer.get_master_eq_band_count(), 1);
+        let output = settle(&mut mixer, &[0.1, 0.3]);
+        assert!(output[FRAMES * 2 - 2].abs() < 0.01);
+    }
+
+    #[test]
+    fn test_crossfader_assignment() {
+        let mut mixer = Mixer::new(48000, 1024, 2).unwrap();
+        mixer.set_smoothing_time(0.0);
+        mixer.set_crossfader_assign(0, CrossfaderAssign::A);
+        mixer.set_crossfader_assign(1, CrossfaderAssign::B);
+
+        for &(position, expected) in &[(-1.0, 0.1), (1.0, 0.3)] {
+            mixer.set_crossfader_position(position);
+            let output = settle(&mut mixer, &[0.1, 0.3]);
+            assert!((output[FRAMES * 2 - 2] - expected).abs() < 0.01, "{}", position);
+        }
+
+        // Constant-power centre: both sides at -3 dB
+        mixer.set_crossfader_position(0.0);
+        let output = settle(&mut mixer, &[0.1, 0.3]);
+        let expected = 0.4 * std::f32::consts::FRAC_1_SQRT_2;
+        assert!((output[FRAMES * 2 - 2] - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_planar_input_follows_deck_tempo() {
+        let mut mixer = Mixer::new(48000, 1024, 2).unwrap();
+        for deck in 0..2 {
+            mixer.set_deck_key_lock(deck, false);
+        }
+        mixer.set_deck_tempo_ratio(1, 2.0);
+
+        let slow = mixer.get_deck_input_frames_required(0, FRAMES);
+        let fast = mixer.get_deck_input_frames_required(1, FRAMES);
+        assert!(fast > slow * 3 / 2);
+        assert_eq!(mixer.get_input_samples_required(FRAMES), 2 * (slow + fast));
+        assert_eq!(mixer.get_deck_input_frames_required(2, FRAMES), 0);
+
+        // Short input is tolerated
+        assert_eq!(mixer.process(&[0.5; 10], FRAMES).len(), FRAMES * 2);
+        assert!(mixer.process(&[], 0).is_empty());
+    }
+
+    #[test]
+    fn test_cue_is_pre_fader() {
+        let mut mixer = Mixer::new(48000, 1024, 2).unwrap();
+        mixer.set_smoothing_time(0.0);
+        mixer.set_cue(1, true);
+        mixer.set_channel_volume(1, 0.0);
+
+        // Deck 1 is silent on the master but heard in the headphones
+        let output = settle(&mut mixer, &[0.1, 0.3]);
+        let cue = mixer.get_cue_output();
+        assert_eq!(cue.len(), output.len());
+        assert!((output[FRAMES * 2 - 2] - 0.1).abs() < 0.01);
+        assert!((cue[FRAMES * 2 - 2] - 0.3).abs() < 0.01);
+
+        // Master only, at half headphone volume
+        mixer.set_cue_mix(1.0);
+        mixer.set_cue_volume(0.5);
+        settle(&mut mixer, &[0.1, 0.3]);
+        assert!((mixer.cue_output()[FRAMES * 2 - 1] - 0.05).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_split_cue() {
+        let mut mixer = Mixer::new(48000, 1024, 2).unwrap();
+        mixer.set_smoothing_time(0.0);
+        mixer.set_cue(0, true);
+        mixer.set_channel_volume(0, 0.0);
+        mixer.set_deck_pan(1, 1.0);
+        mixer.set_split_cue(true);
+
+        // Left ear: cued deck 0; right ear: mono master (deck 1, panned right)
+        settle(&mut mixer, &[0.2, 0.4]);
+        let cue = mixer.cue_output();
+        assert!((cue[FRAMES * 2 - 2] - 0.2).abs() < 0.01);
+        assert!((cue[FRAMES * 2 - 1] - 0.2).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_cue_hears_the_finished_master() {
+        let mut mixer = Mixer::new(48000, 1024, 1).unwrap();
+        mixer.set_smoothing_time(0.0);
+        mixer.set_cue(0, true);
+        mixer.set_split_cue(true);
+
+        // PFL (left ear) and master (right ear) arrive together
+        let mut arrivals = [None, None];
+        for block in 0..60 {
+            let required = mixer.get_deck_input_frames_required(0, FRAMES);
+            let level = if block < 20 { 0.0 } else { 0.5 };
+            mixer.process(&vec![level; 2 * required], FRAMES);
+            for (ear, arrival) in arrivals.iter_mut().enumerate() {
+                let frame = mixer.cue_output().chunks(2).position(|frame| frame[ear] > 0.25);
+                if let (None, Some(frame)) = (*arrival, frame) {
+                    *arrival = Some(block * FRAMES + frame);
+                }
+            }
+        }
+        assert!(arrivals[0].is_some());
+        assert_eq!(arrivals[0], arrivals[1]);
+
+        // The master in the headphones is after master volume and EQ
+        mixer.set_split_cue(false);
+        mixer.set_cue_mix(1.0);
+        mixer.set_master_volume(0.5);
+        let output = settle(&mut mixer, &[0.4]);
+        assert!((output[FRAMES * 2 - 2] - 0.2).abs() < 0.01);
+        assert_eq!(mixer.cue_output(), &output[..]);
+    }
+
+    /// Output frame at which a step fed to `deck` at source frame 4096 reaches half level
+    fn step_arrival(mixer: &mut Mixer, deck: usize) -> usize {
+        const ONSET: usize = 4096;
+        let mut positions = vec![0; mixer.get_deck_count()];
+        for block in 0..60 {
+            let mut input = Vec::new();
+            for (d, position) in positions.iter_mut().enumerate() {
+                let required = mixer.get_deck_input_frames_required(d, FRAMES);
+                let level = |frame: usize| if d == deck && frame >= ONSET { 0.5 } else { 0.0 };
+                let block: Vec<f32> = (*position..*position + required).map(level).collect();
+                input.extend_from_slice(&block);
+                input.extend_from_slice(&block);
+                *position += required;
+            }
+
+            let output = mixer.process(&input, FRAMES);
+            if let Some(frame) = output.chunks(2).position(|frame| frame[0] > 0.25) {
+                return block * FRAMES + frame - ONSET;
+            }
+        }
+        panic!("deck {} never arrived", deck);
+    }
+
+    #[test]
+    fn test_decks_are_padded_to_the_slowest() {
+        let run = |deck: usize| {
+            let mut mixer = Mixer::new(48000, 1024, 2).unwrap();
+            mixer.set_smoothing_time(0.0);
+            mixer.set_deck_key_lock(0, false);
+            let arrival = step_arrival(&mut mixer, deck);
+            (mixer, arrival)
+        };
+
+        let (mixer, varispeed) = run(0);
+        let (_, vocoder) = run(1);
+        let slowest = mixer.get_deck_latency_frames(1);
+        assert!(mixer.get_deck_latency_frames(0) < slowest);
+        assert_eq!(mixer.get_latency_frames(), slowest + mixer.limiter.latency());
+
+        // Both decks reach the master together, after the slowest deck's latency
+        let latency = mixer.get_latency_frames();
+        for arrival in [varispeed, vocoder] {
+            assert!(arrival.abs_diff(latency) < 16, "{} vs {}", arrival, latency);
+        }
+    }
+}
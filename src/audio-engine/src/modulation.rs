@@ -0,0 +1,406 @@
+//! Modulation effects: flanger, phaser and chorus
+//!
+//! One insert with a selectable effect, all driven by a sine LFO whose
+//! period is a number of beats at the deck tempo, so sweeps land on the
+//! beat grid.
+//!
+//! | Effect  | Signal path                                              |
+//! |---------|----------------------------------------------------------|
+//! | Flanger | 0.5-5.5ms modulated delay with feedback (jet sweep)      |
+//! | Phaser  | 4, 8 or 12 swept first-order allpass stages, feedback    |
+//! | Chorus  | 12-20ms modulated delay, no feedback (doubling)          |
+//!
+//! The right channel's LFO runs a quarter cycle ahead of the left for a
+//! wide stereo image.
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(1) per sample (O(stages) for the phaser)
+//! - Memory: ~12KB at 48kHz
+//! - Latency: none
+
+use std::f32::consts::{PI, TAU};
+
+use wasm_bindgen::prelude::*;
+
+use crate::delay_line::DelayLine;
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+
+/// Longest modulated delay in milliseconds
+const MAX_DELAY_MS: f32 = 30.0;
+
+/// Most phaser stages
+const MAX_STAGES: usize = 12;
+
+/// Shortest and longest LFO period in beats
+const MIN_LFO_BEATS: f32 = 0.25;
+const MAX_LFO_BEATS: f32 = 32.0;
+
+/// Flanger delay sweep (ms): base, and range at full depth
+const FLANGER_DELAY_MS: (f32, f32) = (0.5, 5.0);
+
+/// Chorus delay sweep (ms): base, and range at full depth
+const CHORUS_DELAY_MS: (f32, f32) = (12.0, 8.0);
+
+/// Phaser sweep: lowest allpass corner (Hz) and octaves covered at full depth
+const PHASER_MIN_HZ: f32 = 200.0;
+const PHASER_OCTAVES: f32 = 5.0;
+
+/// Regeneration of the flanger and phaser
+const FLANGER_FEEDBACK: f32 = 0.5;
+const PHASER_FEEDBACK: f32 = 0.3;
+
+/// LFO phase lead of the right channel (fraction of a cycle)
+const STEREO_PHASE: f32 = 0.25;
+
+/// Modulation effect selection
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModulationType {
+    Flanger = 0,
+    Phaser = 1,
+    Chorus = 2,
+}
+
+/// Per-channel state of the phaser allpass chain
+#[derive(Clone, Copy, Debug, Default)]
+struct PhaserChannel {
+    // (previous input, previous output) of each stage
+    stages: [(f32, f32); MAX_STAGES],
+    last: f32,
+}
+
+/// Stereo flanger / phaser / chorus
+///
+/// Starts as a chorus, 4-beat LFO at 120 BPM, depth 0.5, phaser with 4
+/// stages, mix 0 (bypass).
+///
+/// # Performance
+/// - Per-sample complexity: O(1), O(stages) for the phaser
+/// - Memory: ~12KB per instance at 48kHz
+pub struct Modulation {
+    sample_rate: u32,
+    effect: ModulationType,
+    bpm: f32,
+    beats: f32,
+    stages: usize,
+
+    // LFO position in cycles (0-1) and increment per sample
+    phase: f32,
+    phase_step: f32,
+
+    depth: SmoothedParam,
+    mix: SmoothedParam,
+    lines: [DelayLine; 2],
+    phaser: [PhaserChannel; 2],
+    silent: bool,
+}
+
+impl Modulation {
+    /// Creates a bypassed modulation effect
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn new(sample_rate: u32) -> Self {
+        let max_delay = (MAX_DELAY_MS * 0.001 * sample_rate as f32) as usize + 2;
+        let mut modulation = Modulation {
+            sample_rate,
+            effect: ModulationType::Chorus,
+            bpm: 120.0,
+            beats: 4.0,
+            stages: 4,
+            phase: 0.0,
+            phase_step: 0.0,
+            depth: SmoothedParam::new(0.5, sample_rate, DEFAULT_SMOOTHING_MS),
+            mix: SmoothedParam::new(0.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            lines: [DelayLine::new(max_delay), DelayLine::new(max_delay)],
+            phaser: [PhaserChannel::default(); 2],
+            silent: true,
+        };
+        modulation.update_rate();
+        modulation
+    }
+
+    /// Selects the effect; switching clears the effect state
+    pub fn set_effect(&mut self, effect: ModulationType) {
+        if effect != self.effect {
+            self.effect = effect;
+            self.reset();
+        }
+    }
+
+    /// Gets the selected effect
+    pub fn get_effect(&self) -> ModulationType {
+        self.effect
+    }
+
+    /// Sets the tempo the LFO locks to
+    ///
+    /// # Arguments
+    /// * `bpm` - Beats per minute (40-300)
+    pub fn set_bpm(&mut self, bpm: f32) {
+        self.bpm = bpm.clamp(40.0, 300.0);
+        self.update_rate();
+    }
+
+    /// Gets the tempo in BPM
+    pub fn get_bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Sets the LFO period in beats
+    ///
+    /// # Arguments
+    /// * `beats` - One full sweep per this many beats (0.25 to 32.0)
+    pub fn set_lfo_beats(&mut self, beats: f32) {
+        self.beats = beats.clamp(MIN_LFO_BEATS, MAX_LFO_BEATS);
+        self.update_rate();
+    }
+
+    /// Gets the LFO period in beats
+    pub fn get_lfo_beats(&self) -> f32 {
+        self.beats
+    }
+
+    /// LFO period in seconds at the current tempo
+    pub fn get_lfo_period(&self) -> f32 {
+        self.beats * 60.0 / self.bpm
+    }
+
+    /// Moves the LFO to a point in its cycle
+    ///
+    /// Lets the host align the sweep with its beat clock, e.g. phase 0 on
+    /// the downbeat.
+    ///
+    /// # Arguments
+    /// * `phase` - Position in the cycle (0.0 to 1.0; wraps)
+    pub fn set_lfo_phase(&mut self, phase: f32) {
+        self.phase = phase.rem_euclid(1.0);
+    }
+
+    /// Gets the LFO position in its cycle (0.0 to 1.0)
+    pub fn get_lfo_phase(&self) -> f32 {
+        self.phase
+    }
+
+    /// Sets the number of phaser stages (rounded to 4, 8 or 12)
+    pub fn set_phaser_stages(&mut self, stages: u32) {
+        let stages = (stages.clamp(4, MAX_STAGES as u32) as f32 / 4.0).round() as usize * 4;
+        if stages != self.stages {
+            self.stages = stages;
+            self.phaser = [PhaserChannel::default(); 2];
+        }
+    }
+
+    /// Gets the number of phaser stages
+    pub fn get_phaser_stages(&self) -> u32 {
+        self.stages as u32
+    }
+
+    /// Sets the sweep depth
+    ///
+    /// # Arguments
+    /// * `depth` - Depth (0.0 = static to 1.0 = full sweep)
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth.set_target(depth.clamp(0.0, 1.0));
+    }
+
+    /// Gets the sweep depth
+    pub fn get_depth(&self) -> f32 {
+        self.depth.target()
+    }
+
+    /// Sets the dry/wet mix
+    ///
+    /// A linear crossfade: the notches of the flanger and phaser are
+    /// deepest at 0.5, where dry and wet are equal.
+    ///
+    /// # Arguments
+    /// * `mix` - Dry/wet mix (0.0 = dry only (bypass), 1.0 = wet only)
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix.set_target(mix.clamp(0.0, 1.0));
+    }
+
+    /// Gets the dry/wet mix
+    pub fn get_mix(&self) -> f32 {
+        self.mix.target()
+    }
+
+    /// Sets how long depth and mix changes take to settle
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.depth.set_smoothing_time(time_ms);
+        self.mix.set_smoothing_time(time_ms);
+    }
+
+    /// Processes a stereo pair in place
+    ///
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let frames = left.len().min(right.len());
+        if self.mix.target() == 0.0 && !self.mix.is_smoothing() {
+            if !self.silent {
+                self.reset();
+            }
+            // Keep the LFO running so it stays on the beat grid
+            self.phase = (self.phase + self.phase_step * frames as f32).fract();
+            return;
+        }
+        self.silent = false;
+
+        let ms = self.sample_rate as f32 / 1000.0;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let depth = self.depth.next_value();
+            let mix = self.mix.next_value();
+
+            for (channel, sample) in [l, r].into_iter().enumerate() {
+                let phase = self.phase + STEREO_PHASE * channel as f32;
+                let sweep = 0.5 - 0.5 * (TAU * phase).cos();
+                let input = *sample;
+
+                let wet = match self.effect {
+                    ModulationType::Flanger | ModulationType::Chorus => {
+                        let ((base, range), feedback) = if self.effect == ModulationType::Flanger {
+                            (FLANGER_DELAY_MS, FLANGER_FEEDBACK)
+                        } else {
+                            (CHORUS_DELAY_MS, 0.0)
+                        };
+                        let line = &mut self.lines[channel];
+                        let delayed = line.read((base + range * depth * sweep) * ms);
+                        line.write(input + delayed * feedback);
+                        delayed
+                    }
+                    ModulationType::Phaser => {
+                        let corner = PHASER_MIN_HZ * (PHASER_OCTAVES * depth * sweep).exp2();
+                        let t = (PI * corner / self.sample_rate as f32).tan();
+                        let a = (t - 1.0) / (t + 1.0);
+
+                        let state = &mut self.phaser[channel];
+                        let mut x = input + state.last * PHASER_FEEDBACK;
+                        for (x1, y1) in state.stages[..self.stages].iter_mut() {
+                            let y = a * x + *x1 - a * *y1;
+                            *x1 = x;
+                            *y1 = y;
+                            x = y;
+                        }
+                        state.last = x;
+                        x
+                    }
+                };
+
+                *sample = input * (1.0 - mix) + wet * mix;
+            }
+
+            self.phase += self.phase_step;
+            if self.phase >= 1.0 {
+                self.phase -= 1.0;
+            }
+        }
+    }
+
+    /// Clears the effect state (the LFO keeps its phase)
+    pub fn reset(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.reset();
+        }
+        self.phaser = [PhaserChannel::default(); 2];
+        self.silent = true;
+    }
+
+    fn update_rate(&mut self) {
+        self.phase_step = 1.0 / (self.get_lfo_period() * self.sample_rate as f32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone_peak(modulation: &mut Modulation, freq: f32) -> f32 {
+        modulation.reset();
+        let mut left: Vec<f32> = (0..9600)
+            .map(|i| (TAU * freq * i as f32 / 48000.0).sin())
+            .collect();
+        let mut right = left.clone();
+        modulation.process_stereo(&mut left, &mut right);
+        left[4800..].iter().fold(0.0f32, |m, s| m.max(s.abs()))
+    }
+
+    #[test]
+    fn test_zero_mix_is_bypass() {
+        let mut modulation = Modulation::new(48000);
+        let input: Vec<f32> = (0..512).map(|i| (i as f32 * 0.3).sin()).collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+        modulation.process_stereo(&mut left, &mut right);
+        assert_eq!(left, input);
+
+        // The LFO kept running: 512 of 96000 samples per 4-beat cycle
+        assert!((modulation.get_lfo_phase() - 512.0 / 96000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_lfo_locks_to_tempo() {
+        let mut modulation = Modulation::new(48000);
+        modulation.set_bpm(128.0);
+        modulation.set_lfo_beats(2.0);
+        assert!((modulation.get_lfo_period() - 0.9375).abs() < 1e-6);
+
+        modulation.set_smoothing_time(0.0);
+        modulation.set_mix(0.5);
+        modulation.set_lfo_phase(1.25);
+        let mut left = vec![0.0; 45000];
+        let mut right = vec![0.0; 45000];
+        modulation.process_stereo(&mut left, &mut right);
+        assert!((modulation.get_lfo_phase() - 0.25).abs() < 1e-3);
+
+        modulation.set_lfo_beats(100.0);
+        assert_eq!(modulation.get_lfo_beats(), MAX_LFO_BEATS);
+    }
+
+    #[test]
+    fn test_flanger_and_chorus_delays() {
+        let mut modulation = Modulation::new(48000);
+        modulation.set_smoothing_time(0.0);
+        modulation.set_mix(1.0);
+        modulation.set_depth(0.0);
+
+        // At zero depth the delay sits at its base: 0.5ms = 24 samples
+        // for the flanger (with repeats), 12ms = 576 samples for the chorus
+        for &(effect, delay, repeat) in &[
+            (ModulationType::Flanger, 24, FLANGER_FEEDBACK),
+            (ModulationType::Chorus, 576, 0.0),
+        ] {
+            modulation.set_effect(effect);
+            let mut left = vec![0.0; 2048];
+            left[0] = 1.0;
+            let mut right = left.clone();
+            modulation.process_stereo(&mut left, &mut right);
+
+            let first = left.iter().position(|&s| s != 0.0).unwrap();
+            assert_eq!(first, delay, "{:?}", effect);
+            assert!((left[delay] - 1.0).abs() < 1e-6);
+            assert!((left[delay * 2] - repeat).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_phaser_notches() {
+        let mut modulation = Modulation::new(48000);
+        modulation.set_smoothing_time(0.0);
+        modulation.set_effect(ModulationType::Phaser);
+        modulation.set_mix(0.5);
+        modulation.set_depth(0.0);
+
+        // Four stages at 200 Hz: 180° (a notch) at 200 × tan(22.5°) ≈ 83 Hz
+        let notch = tone_peak(&mut modulation, 83.0);
+        let pass = tone_peak(&mut modulation, 200.0);
+        assert!(notch < 0.2, "{}", notch);
+        assert!(pass > 0.9, "{}", pass);
+
+        modulation.set_phaser_stages(7);
+        assert_eq!(modulation.get_phaser_stages(), 8);
+        modulation.set_phaser_stages(40);
+        assert_eq!(modulation.get_phaser_stages(), 12);
+    }
+}
@@ -0,0 +1,204 @@
+//! Stereo pan/balance with selectable pan laws
+//!
+//! Places a deck in the stereo field. The pan law sets how much each side
+//! is attenuated at centre, trading loudness consistency for centre level:
+//!
+//! | Law           | Centre  | Character                                  |
+//! |---------------|---------|--------------------------------------------|
+//! | ConstantPower | -3 dB   | Constant loudness when summed acoustically |
+//! | Compromise    | -4.5 dB | Between constant power and linear          |
+//! | Linear        | -6 dB   | Constant level when summed to mono         |
+//! | Balance       | 0 dB    | Only the opposite side is turned down      |
+
+use std::f32::consts::FRAC_PI_2;
+
+use wasm_bindgen::prelude::*;
+
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+
+/// Gain law applied across the pan range
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanLaw {
+    /// sin/cos law, -3 dB per side at centre
+    ConstantPower = 0,
+    /// Geometric mean of constant power and linear, -4.5 dB at centre
+    Compromise = 1,
+    /// Straight-line law, -6 dB per side at centre
+    Linear = 2,
+    /// Unity at centre; panning only attenuates the opposite side
+    Balance = 3,
+}
+
+/// Stereo panner
+///
+/// With `t` = (position + 1) / 2 and θ = π t / 2:
+/// ```text
+/// ConstantPower:  L = cos θ                R = sin θ
+/// Compromise:     L = √((1 - t) cos θ)     R = √(t sin θ)
+/// Linear:         L = 1 - t                R = t
+/// Balance:        L = min(1, 1 - position) R = min(1, 1 + position)
+/// ```
+///
+/// Gains glide to their new values (see [`SmoothedParam`]) so pan moves do
+/// not click.
+pub struct Panner {
+    position: f32,
+    law: PanLaw,
+    left_gain: SmoothedParam,
+    right_gain: SmoothedParam,
+}
+
+impl Panner {
+    /// Creates a centred panner using the balance law (unity gain)
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz (for gain smoothing)
+    pub fn new(sample_rate: u32) -> Self {
+        Panner {
+            position: 0.0,
+            law: PanLaw::Balance,
+            left_gain: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            right_gain: SmoothedParam::new(1.0, sample_rate, DEFAULT_SMOOTHING_MS),
+        }
+    }
+
+    /// Sets the pan position
+    ///
+    /// # Arguments
+    /// * `position` - Pan position (-1.0 to 1.0)
+    ///   - -1.0 = hard left
+    ///   - 0.0 = centre
+    ///   - 1.0 = hard right
+    pub fn set_position(&mut self, position: f32) {
+        self.position = position.clamp(-1.0, 1.0);
+        self.update_targets();
+    }
+
+    /// Gets the pan position
+    pub fn get_position(&self) -> f32 {
+        self.position
+    }
+
+    /// Sets the pan law
+    pub fn set_law(&mut self, law: PanLaw) {
+        self.law = law;
+        self.update_targets();
+    }
+
+    /// Gets the pan law
+    pub fn get_law(&self) -> PanLaw {
+        self.law
+    }
+
+    /// Sets how long gain changes take to settle
+    ///
+    /// # Arguments
+    /// * `time_ms` - Smoothing time in milliseconds (0 = immediate)
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.left_gain.set_smoothing_time(time_ms);
+        self.right_gain.set_smoothing_time(time_ms);
+    }
+
+    /// Left and right gains for the current position and law
+    pub fn gains(&self) -> (f32, f32) {
+        let t = (self.position + 1.0) * 0.5;
+        let theta = FRAC_PI_2 * t;
+
+        match self.law {
+            PanLaw::ConstantPower => (theta.cos(), theta.sin()),
+            PanLaw::Compromise => (((1.0 - t) * theta.cos()).sqrt(), (t * theta.sin()).sqrt()),
+            PanLaw::Linear => (1.0 - t, t),
+            PanLaw::Balance => ((1.0 - self.position).min(1.0), (1.0 + self.position).min(1.0)),
+        }
+    }
+
+    /// Applies the pan gains to a stereo pair in place
+    ///
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if !self.left_gain.is_smoothing() && !self.right_gain.is_smoothing() {
+            let left_gain = self.left_gain.current();
+            let right_gain = self.right_gain.current();
+            if left_gain == 1.0 && right_gain == 1.0 {
+                return;
+            }
+
+            left.iter_mut().for_each(|s| *s *= left_gain);
+            right.iter_mut().for_each(|s| *s *= right_gain);
+            return;
+        }
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            *l *= self.left_gain.next_value();
+            *r *= self.right_gain.next_value();
+        }
+    }
+
+    fn update_targets(&mut self) {
+        let (left, right) = self.gains();
+        self.left_gain.set_target(left);
+        self.right_gain.set_target(right);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db(gain: f32) -> f32 {
+        20.0 * gain.log10()
+    }
+
+    #[test]
+    fn test_centre_attenuation() {
+        let mut panner = Panner::new(48000);
+        assert_eq!(panner.gains(), (1.0, 1.0));
+
+        for &(law, expected) in &[
+            (PanLaw::ConstantPower, -3.01),
+            (PanLaw::Compromise, -4.52),
+            (PanLaw::Linear, -6.02),
+        ] {
+            panner.set_law(law);
+            let (left, right) = panner.gains();
+            assert_eq!(left, right);
+            assert!((db(left) - expected).abs() < 0.01, "{:?}: {}", law, db(left));
+        }
+    }
+
+    #[test]
+    fn test_hard_pan() {
+        let mut panner = Panner::new(48000);
+        for &law in &[PanLaw::ConstantPower, PanLaw::Compromise, PanLaw::Linear, PanLaw::Balance] {
+            panner.set_law(law);
+            panner.set_position(-1.0);
+            let (left, right) = panner.gains();
+            assert!((left - 1.0).abs() < 1e-6, "{:?}", law);
+            assert!(right.abs() < 1e-6, "{:?}", law);
+        }
+
+        // Balance only turns the opposite side down
+        panner.set_position(0.5);
+        assert_eq!(panner.gains(), (0.5, 1.0));
+    }
+
+    #[test]
+    fn test_process() {
+        let mut panner = Panner::new(48000);
+        panner.set_smoothing_time(0.0);
+        panner.set_law(PanLaw::ConstantPower);
+        panner.set_position(1.0);
+
+        let mut left = vec![1.0; 64];
+        let mut right = vec![1.0; 64];
+        panner.process(&mut left, &mut right);
+        assert!(left.iter().all(|s| s.abs() < 1e-6));
+        assert!(right.iter().all(|s| (s - 1.0).abs() < 1e-6));
+
+        panner.set_position(2.0);
+        assert_eq!(panner.get_position(), 1.0);
+    }
+}
@@ -0,0 +1,410 @@
+//! Parametric N-band equalizer for the master bus
+//!
+//! Complements the channel [`Equalizer`](crate::equalizer::Equalizer) with
+//! freely placed bands for correcting room and PA response. Each band is an
+//! RBJ biquad of a selectable type; bands run in series.
+//!
+//! Changing a band glides its frequency, Q and gain to the new settings;
+//! changing its type crossfades from the old filter to the new one.
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(bands) per sample (twice that for a band changing
+//!   type)
+//! - Memory: ~60 bytes per band
+//! - Latency: none
+
+use wasm_bindgen::prelude::*;
+
+use crate::biquad::{Biquad, BiquadCoefficients};
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+
+/// Maximum number of bands
+pub const MAX_BANDS: usize = 16;
+
+/// Samples between coefficient updates while a band is gliding
+const SMOOTHING_BLOCK: usize = 16;
+
+/// Shape of a parametric EQ band
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterType {
+    /// Bell boost/cut around the frequency
+    Peak = 0,
+    /// Boost/cut below the frequency
+    LowShelf = 1,
+    /// Boost/cut above the frequency
+    HighShelf = 2,
+    /// Narrow rejection at the frequency (gain ignored)
+    Notch = 3,
+    /// 12 dB/octave low-pass (gain ignored)
+    LowPass = 4,
+    /// 12 dB/octave high-pass (gain ignored)
+    HighPass = 5,
+}
+
+/// Settings of one parametric band
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EqBand {
+    pub filter_type: FilterType,
+    /// Centre/corner frequency in Hz
+    pub frequency: f32,
+    /// Quality factor (0.1-20)
+    pub q: f32,
+    /// Gain in dB (-24 to +24; ignored by notch and pass types)
+    pub gain_db: f32,
+}
+
+impl EqBand {
+    /// Creates band settings, clamping Q and gain to their ranges
+    pub fn new(filter_type: FilterType, frequency: f32, q: f32, gain_db: f32) -> Self {
+        EqBand {
+            filter_type,
+            frequency: frequency.max(1.0),
+            q: q.clamp(0.1, 20.0),
+            gain_db: gain_db.clamp(-24.0, 24.0),
+        }
+    }
+
+    /// Biquad coefficients for this band
+    pub fn coefficients(&self, sample_rate: u32) -> BiquadCoefficients {
+        let (freq, q, gain) = (self.frequency, self.q, self.gain_db);
+        match self.filter_type {
+            FilterType::Peak => BiquadCoefficients::peaking(sample_rate, freq, q, gain),
+            FilterType::LowShelf => BiquadCoefficients::low_shelf(sample_rate, freq, q, gain),
+            FilterType::HighShelf => BiquadCoefficients::high_shelf(sample_rate, freq, q, gain),
+            FilterType::Notch => BiquadCoefficients::notch(sample_rate, freq, q),
+            FilterType::LowPass => BiquadCoefficients::lowpass(sample_rate, freq, q),
+            FilterType::HighPass => BiquadCoefficients::highpass(sample_rate, freq, q),
+        }
+    }
+}
+
+/// A band's settings with its stereo filter state
+struct BandState {
+    settings: EqBand,
+    // Frequency (log2 Hz), Q and gain applied, gliding towards the settings
+    frequency: SmoothedParam,
+    q: SmoothedParam,
+    gain_db: SmoothedParam,
+    filters: [Biquad; 2],
+
+    // Filters of the previous type, fading out after a type change
+    outgoing: Option<[Biquad; 2]>,
+    fade: SmoothedParam,
+}
+
+impl BandState {
+    fn new(band: EqBand, sample_rate: u32, smoothing_ms: f32) -> Self {
+        let param = |value: f32| SmoothedParam::new(value, sample_rate, smoothing_ms);
+        let filter = Biquad::new(band.coefficients(sample_rate));
+        BandState {
+            settings: band,
+            frequency: param(band.frequency.log2()),
+            q: param(band.q),
+            gain_db: param(band.gain_db),
+            filters: [filter.clone(), filter],
+            outgoing: None,
+            fade: param(0.0),
+        }
+    }
+
+    fn set_smoothing_time(&mut self, time_ms: f32) {
+        for param in [&mut self.frequency, &mut self.q, &mut self.gain_db, &mut self.fade] {
+            param.set_smoothing_time(time_ms);
+        }
+    }
+
+    /// Glides to new settings, or crossfades to them if the type changed
+    fn set(&mut self, band: EqBand, sample_rate: u32) {
+        let retype = band.filter_type != self.settings.filter_type;
+        self.settings = band;
+
+        if retype {
+            let filter = Biquad::new(band.coefficients(sample_rate));
+            self.outgoing = Some(std::mem::replace(&mut self.filters, [filter.clone(), filter]));
+            self.frequency.set_immediate(band.frequency.log2());
+            self.q.set_immediate(band.q);
+            self.gain_db.set_immediate(band.gain_db);
+            self.fade.set_immediate(1.0);
+            self.fade.set_target(0.0);
+            return;
+        }
+
+        for (param, value) in [
+            (&mut self.frequency, band.frequency.log2()),
+            (&mut self.q, band.q),
+            (&mut self.gain_db, band.gain_db),
+        ] {
+            if param.target() != value {
+                param.set_target(value);
+            }
+        }
+    }
+
+    fn is_gliding(&self) -> bool {
+        self.frequency.is_smoothing() || self.q.is_smoothing() || self.gain_db.is_smoothing()
+    }
+
+    /// Runs the band in sub-blocks so gliding settings can update the filters
+    fn process(&mut self, left: &mut [f32], right: &mut [f32], sample_rate: u32) {
+        let mut start = 0;
+        while start < left.len() {
+            let end = if self.is_gliding() {
+                (start + SMOOTHING_BLOCK).min(left.len())
+            } else {
+                left.len()
+            };
+
+            if self.is_gliding() {
+                let applied = EqBand {
+                    frequency: self.frequency.skip(end - start).exp2(),
+                    q: self.q.skip(end - start),
+                    gain_db: self.gain_db.skip(end - start),
+                    ..self.settings
+                };
+                let coefficients = applied.coefficients(sample_rate);
+                for filter in self.filters.iter_mut() {
+                    filter.set_coefficients(coefficients);
+                }
+            }
+
+            let [left_filter, right_filter] = &mut self.filters;
+            match self.outgoing.as_mut() {
+                Some([left_outgoing, right_outgoing]) => {
+                    for (l, r) in left[start..end].iter_mut().zip(right[start..end].iter_mut()) {
+                        let fade = self.fade.next_value();
+                        let new_left = left_filter.process_sample(*l);
+                        let new_right = right_filter.process_sample(*r);
+                        *l = new_left + (left_outgoing.process_sample(*l) - new_left) * fade;
+                        *r = new_right + (right_outgoing.process_sample(*r) - new_right) * fade;
+                    }
+                    if !self.fade.is_smoothing() {
+                        self.outgoing = None;
+                    }
+                }
+                None => {
+                    left_filter.process(&mut left[start..end]);
+                    right_filter.process(&mut right[start..end]);
+                }
+            }
+            start = end;
+        }
+    }
+}
+
+/// Stereo parametric equalizer with up to [`MAX_BANDS`] bands
+///
+/// An empty EQ is a bypass.
+pub struct ParametricEq {
+    sample_rate: u32,
+    smoothing_ms: f32,
+    bands: Vec<BandState>,
+}
+
+impl ParametricEq {
+    /// Creates an empty (flat) parametric EQ
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn new(sample_rate: u32) -> Self {
+        ParametricEq {
+            sample_rate,
+            smoothing_ms: DEFAULT_SMOOTHING_MS,
+            bands: Vec::with_capacity(MAX_BANDS),
+        }
+    }
+
+    /// Appends a band
+    ///
+    /// Returns the band index, or `None` if [`MAX_BANDS`] are in use.
+    pub fn add_band(&mut self, band: EqBand) -> Option<usize> {
+        if self.bands.len() >= MAX_BANDS {
+            return None;
+        }
+
+        self.bands.push(BandState::new(band, self.sample_rate, self.smoothing_ms));
+        Some(self.bands.len() - 1)
+    }
+
+    /// Replaces the settings of band `index`
+    ///
+    /// Frequency, Q and gain glide to the new values; a new filter type
+    /// crossfades in over the smoothing time.
+    ///
+    /// Returns `false` if there is no such band.
+    pub fn set_band(&mut self, index: usize, band: EqBand) -> bool {
+        let Some(state) = self.bands.get_mut(index) else {
+            return false;
+        };
+
+        state.set(band, self.sample_rate);
+        true
+    }
+
+    /// Sets how long band changes take to settle
+    ///
+    /// # Arguments
+    /// * `time_ms` - Smoothing time in milliseconds (0 = immediate)
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.smoothing_ms = time_ms.clamp(0.0, 1000.0);
+        for band in self.bands.iter_mut() {
+            band.set_smoothing_time(self.smoothing_ms);
+        }
+    }
+
+    /// Gets the settings of band `index`
+    pub fn band(&self, index: usize) -> Option<EqBand> {
+        self.bands.get(index).map(|state| state.settings)
+    }
+
+    /// Removes band `index`; later bands move down by one
+    pub fn remove_band(&mut self, index: usize) -> Option<EqBand> {
+        (index < self.bands.len()).then(|| self.bands.remove(index).settings)
+    }
+
+    /// Removes all bands
+    pub fn clear(&mut self) {
+        self.bands.clear();
+    }
+
+    /// Number of bands in use
+    pub fn band_count(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// Processes a stereo pair through all bands in series
+    ///
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for band in self.bands.iter_mut() {
+            band.process(left, right, self.sample_rate);
+        }
+    }
+
+    /// Linear magnitude response of all bands combined at `freq`
+    ///
+    /// Reflects the band settings, not the gliding values currently applied.
+    pub fn magnitude_at(&self, freq: f32) -> f32 {
+        self.bands
+            .iter()
+            .map(|band| band.settings.coefficients(self.sample_rate).magnitude_at(freq, self.sample_rate))
+            .product()
+    }
+
+    /// Combined magnitude response in dB at each of `frequencies`
+    ///
+    /// Intended for drawing the EQ curve; values are floored at -120 dB.
+    pub fn magnitude_response(&self, frequencies: &[f32]) -> Vec<f32> {
+        frequencies
+            .iter()
+            .map(|&freq| 20.0 * self.magnitude_at(freq).max(1e-6).log10())
+            .collect()
+    }
+
+    /// Clears the filter state of every band
+    pub fn reset(&mut self) {
+        for filter in self.bands.iter_mut().flat_map(|band| band.filters.iter_mut()) {
+            filter.reset();
+        }
+        for band in self.bands.iter_mut() {
+            band.outgoing = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_eq_is_flat() {
+        let mut eq = ParametricEq::new(48000);
+        assert_eq!(eq.magnitude_response(&[20.0, 1000.0, 20000.0]), vec![0.0; 3]);
+
+        let input: Vec<f32> = (0..256).map(|i| (i as f32 * 0.1).sin()).collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+        eq.process_stereo(&mut left, &mut right);
+        assert_eq!(left, input);
+    }
+
+    #[test]
+    fn test_band_management() {
+        let mut eq = ParametricEq::new(48000);
+        for i in 0..MAX_BANDS {
+            assert_eq!(eq.add_band(EqBand::new(FilterType::Peak, 100.0, 1.0, 3.0)), Some(i));
+        }
+        assert_eq!(eq.add_band(EqBand::new(FilterType::Peak, 100.0, 1.0, 3.0)), None);
+
+        let notch = EqBand::new(FilterType::Notch, 60.0, 50.0, 0.0);
+        assert!(eq.set_band(3, notch));
+        assert_eq!(eq.band(3).unwrap().q, 20.0);
+        assert!(!eq.set_band(MAX_BANDS, notch));
+
+        assert_eq!(eq.remove_band(3).unwrap().filter_type, FilterType::Notch);
+        assert_eq!(eq.band_count(), MAX_BANDS - 1);
+        eq.clear();
+        assert_eq!(eq.band_count(), 0);
+    }
+
+    #[test]
+    fn test_combined_response() {
+        let mut eq = ParametricEq::new(48000);
+        eq.add_band(EqBand::new(FilterType::Peak, 1000.0, 2.0, 6.0));
+        eq.add_band(EqBand::new(FilterType::Peak, 1000.0, 2.0, 3.0));
+        eq.add_band(EqBand::new(FilterType::HighPass, 30.0, 0.707, 0.0));
+
+        let response = eq.magnitude_response(&[1000.0, 10.0, 10000.0]);
+        assert!((response[0] - 9.0).abs() < 0.05);
+        assert!(response[1] < -15.0);
+        assert!(response[2].abs() < 0.1);
+    }
+
+    #[test]
+    fn test_band_changes_are_smoothed() {
+        let mut eq = ParametricEq::new(48000);
+        eq.add_band(EqBand::new(FilterType::Peak, 1000.0, 1.0, 0.0));
+
+        // A 12 dB boost, then a switch to a low-pass, under a steady tone
+        let input: Vec<f32> = (0..24000)
+            .map(|i| (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin() * 0.25)
+            .collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+        for (block, (l, r)) in left.chunks_mut(128).zip(right.chunks_mut(128)).enumerate() {
+            match block {
+                40 => assert!(eq.set_band(0, EqBand::new(FilterType::Peak, 1000.0, 1.0, 12.0))),
+                100 => assert!(eq.set_band(0, EqBand::new(FilterType::LowPass, 300.0, 0.707, 0.0))),
+                _ => {}
+            }
+            eq.process_stereo(l, r);
+        }
+
+        // At 1 kHz a sine peaking at 1.0 moves by at most 0.13 per sample
+        let largest_step = left.windows(2).fold(0.0f32, |m, w| m.max((w[1] - w[0]).abs()));
+        assert!(largest_step < 0.15, "step {}", largest_step);
+        let boosted = left[11000..12800].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((boosted - 1.0).abs() < 0.01, "{}", boosted);
+    }
+
+    #[test]
+    fn test_process_matches_response() {
+        let mut eq = ParametricEq::new(48000);
+        eq.add_band(EqBand::new(FilterType::LowShelf, 200.0, 0.707, -6.0));
+        eq.add_band(EqBand::new(FilterType::Peak, 3000.0, 1.0, 4.0));
+
+        for &freq in &[100.0, 3000.0] {
+            eq.reset();
+            let mut left: Vec<f32> = (0..48000)
+                .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / 48000.0).sin())
+                .collect();
+            let mut right = left.clone();
+            eq.process_stereo(&mut left, &mut right);
+
+            let peak = left[24000..].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+            assert!((peak - eq.magnitude_at(freq)).abs() < 1e-3, "{} Hz", freq);
+        }
+    }
+}
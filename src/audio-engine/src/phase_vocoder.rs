@@ -18,6 +18,78 @@
 //! 3. Unwrapping phase to estimate instantaneous frequencies
 //! 4. Stretching or compressing the time axis
 //! 5. Resynthesizing audio using modified phase and original magnitude
+//!
+//! Frames are resynthesized with a fixed synthesis hop of `fft_size / 4`
+//! (75% overlap) while the analysis hop is `synthesis_hop * stretch_ratio`.
+//!
+//! # Streaming
+//! For real-time use the vocoder keeps its input FIFO and overlap-add state
+//! between calls. A host asks [`PhaseVocoder::input_required`] how much
+//! source audio the next output block needs, feeds exactly that amount with
+//! [`PhaseVocoder::push_input`] and reads the block back with
+//! [`PhaseVocoder::pull_output`].
+//!
+//! # Transient preservation
+//! In [`StretchMode::TransientPreserving`] the vocoder uses identity phase
+//! locking (Laroche & Dolson): only spectral peaks are phase-propagated and
+//! the bins around each peak keep their analysis phase offset to it. Frames
+//! whose multi-band spectral flux jumps well above its running average are
+//! treated as transients and resynthesized with their analysis phases, which
+//! keeps kick and hi-hat attacks sharp.
+//!
+//! # Envelope warping
+//! [`PhaseVocoder::set_envelope_warp`] moves the spectral envelope of each
+//! frame (estimated by cepstral liftering) along the frequency axis while
+//! keeping its harmonic fine structure, which lets the pitch shifter keep
+//! formants in place.
+
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+use wasm_bindgen::prelude::*;
+
+use crate::audio_analysis::{AudioAnalyzer, BandMagnitudes, FLUX_BAND_COUNT};
+use crate::time_stretch::TimeStretcher;
+
+/// Number of overlapping frames per FFT window (synthesis hop = fft_size / OVERLAP)
+const OVERLAP: usize = 4;
+
+/// Maximum unread input kept per channel, in FFT frames; older audio is dropped
+const MAX_BACKLOG_FRAMES: usize = 16;
+
+/// Frames whose summed magnitude falls below this (per FFT bin) count as silence
+const SILENCE_THRESHOLD: f32 = 1e-6;
+
+/// Minimum spectral flux (relative to frame energy) for a frame to count as a transient
+const TRANSIENT_MIN_FLUX: f32 = 0.25;
+
+/// How far above its running average the relative flux must jump for a transient
+const TRANSIENT_FLUX_RATIO: f32 = 2.5;
+
+/// Smoothing factor for the running average of the relative flux
+const TRANSIENT_AVERAGE_COEFF: f32 = 0.1;
+
+/// Unity-ratio hops after a transient, enough for the attack to cross the window
+const TRANSIENT_HOLD_HOPS: usize = OVERLAP - 1;
+
+/// Highest fundamental (Hz) whose harmonics the cepstral lifter smooths over;
+/// sets the lifter cutoff to `sample_rate / LIFTER_MAX_F0` quefrency samples
+const LIFTER_MAX_F0: u32 = 800;
+
+/// Floor for magnitudes before taking the logarithm
+const LOG_FLOOR: f32 = 1e-9;
+
+
+/// Time-stretch algorithm variant
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StretchMode {
+    /// Classic phase vocoder: every bin is phase-propagated independently
+    Standard = 0,
+    /// Identity phase locking with phase reset on detected transients
+    TransientPreserving = 1,
+}
 
 /// Phase vocoder for time-stretching without pitch change
 ///
@@ -26,25 +98,44 @@
 pub struct PhaseVocoder {
     // Control parameters
     stretch_ratio: f32,
+    mode: StretchMode,
+
+    // STFT engine (FFT plans, window and scratch space)
+    stft: Stft,
+
+    // Streaming state (left/right), analysis hop schedule and transient detection
+    channels: [ChannelState; 2],
+    planner: HopPlanner,
+    detector: TransientDetector,
 }
 
 impl PhaseVocoder {
     /// Creates a new phase vocoder with specified FFT size
-    /// 
+    ///
     /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz (used by transient detection and
+    ///   envelope estimation)
     /// * `fft_size` - FFT size (must be power of 2: 256, 512, 1024, 2048, 4096)
-    /// 
+    ///
     /// # Performance
     /// - Initialization: ~1ms
     /// - Memory allocation: ~300KB for fft_size=4096
-    pub fn new(_fft_size: usize) -> Self {
+    pub fn new(sample_rate: u32, fft_size: usize) -> Self {
         PhaseVocoder {
             stretch_ratio: 1.0,
+            mode: StretchMode::Standard,
+            stft: Stft::new(sample_rate, fft_size),
+            channels: [
+                ChannelState::streaming(fft_size),
+                ChannelState::streaming(fft_size),
+            ],
+            planner: HopPlanner::default(),
+            detector: TransientDetector::new(sample_rate),
         }
     }
 
     /// Sets the stretch ratio for time-stretching
-    /// 
+    ///
     /// # Arguments
     /// * `ratio` - Time-stretch factor (0.5 = half speed, 2.0 = double speed)
     pub fn set_stretch_ratio(&mut self, ratio: f32) {
@@ -56,127 +147,804 @@ impl PhaseVocoder {
         self.stretch_ratio
     }
 
-    /// Processes a mono audio frame with time-stretching
-    /// 
-    /// This is a placeholder implementation that returns input unchanged.
-    /// Full FFT-based phase vocoder would go here.
-    /// 
+    /// Selects the time-stretch algorithm variant
+    pub fn set_mode(&mut self, mode: StretchMode) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.detector.reset();
+        }
+    }
+
+    /// Gets the current time-stretch algorithm variant
+    pub fn get_mode(&self) -> StretchMode {
+        self.mode
+    }
+
+    /// Warps the spectral envelope while stretching
+    ///
+    /// With `Some(ratio)`, every output bin takes the spectral envelope
+    /// (formant structure) found at `ratio` times its frequency, while the
+    /// harmonic fine structure stays where it is. The pitch shifter uses
+    /// this to cancel the formant shift of its resampling stage. `None`
+    /// disables warping.
+    ///
+    /// # Performance
+    /// Costs two extra FFTs per channel and frame while enabled.
+    pub fn set_envelope_warp(&mut self, ratio: Option<f32>) {
+        self.stft.envelope_warp = ratio.map(|r| r.clamp(0.5, 2.0));
+    }
+
+    /// Gets the current envelope warp ratio
+    pub fn get_envelope_warp(&self) -> Option<f32> {
+        self.stft.envelope_warp
+    }
+
+    /// Gets the FFT size used for analysis and resynthesis
+    pub fn fft_size(&self) -> usize {
+        self.stft.fft_size
+    }
+
+    /// Gets the synthesis hop size in samples (`fft_size / 4`)
+    pub fn hop_size(&self) -> usize {
+        self.stft.hop_size
+    }
+
+    /// Time-stretches a complete mono buffer
+    ///
+    /// The buffer is treated as a self-contained signal: it is zero-padded on
+    /// both sides, run through the STFT analysis/resynthesis loop and trimmed
+    /// so that the output is aligned with the input.
+    ///
     /// # Arguments
     /// * `input` - Input audio frame
-    /// 
+    ///
     /// # Returns
-    /// Time-stretched audio frame
+    /// Time-stretched audio of `ceil(input.len() / stretch_ratio)` samples
     pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
-        // Simplified phase vocoder: linear interpolation based on stretch ratio
-        // Full implementation would use FFT analysis-synthesis
-        
         if (self.stretch_ratio - 1.0).abs() < 0.001 {
             return input.to_vec();
         }
 
-        let input_len = input.len();
-        let output_len = (input_len as f32 / self.stretch_ratio).ceil() as usize;
-        let mut output = vec![0.0; output_len];
-
-        // Simple linear interpolation for now
-        for i in 0..output_len {
-            let src_pos = i as f32 * self.stretch_ratio;
-            let src_idx = src_pos as usize;
-            
-            if src_idx + 1 < input_len {
-                let frac = src_pos - src_idx as f32;
-                output[i] = input[src_idx] * (1.0 - frac) + input[src_idx + 1] * frac;
-            } else if src_idx < input_len {
-                output[i] = input[src_idx];
+        let fft_size = self.stft.fft_size;
+        let hop_size = self.stft.hop_size;
+        let ratio = self.stretch_ratio;
+        let output_len = (input.len() as f32 / ratio).ceil() as usize;
+
+        // Lead-in so the first real sample sees a fully overlapped output,
+        // tail so the last one is flushed out of the overlap-add buffer
+        let lead_in = fft_size - hop_size;
+        let tail = fft_size * 2;
+
+        let mut channel = ChannelState::new(fft_size);
+        channel.input.resize(lead_in, 0.0);
+        channel.input.extend_from_slice(input);
+        channel.input.resize(lead_in + input.len() + tail, 0.0);
+
+        let mut planner = HopPlanner::default();
+        let mut detector = TransientDetector::new(self.detector.sample_rate);
+        let channels = std::slice::from_mut(&mut channel);
+        while channels[0].read_pos + fft_size <= channels[0].input.len() {
+            let transient = self
+                .stft
+                .process_frame(channels, self.mode, &mut detector, !planner.is_holding());
+            let analysis_hop = planner.next(hop_size, ratio, transient);
+            advance_channels(channels, analysis_hop);
+        }
+
+        // Frame centres map input time t to output time (t - N/2) / ratio + N/2
+        let half = fft_size as f32 * 0.5;
+        let start = ((lead_in as f32 - half) / ratio + half).round() as usize;
+
+        (0..output_len)
+            .map(|i| channel.output.get(start + i).copied().unwrap_or(0.0))
+            .collect()
+    }
+
+    /// Gets the streaming latency in input samples
+    ///
+    /// Every stream starts with `fft_size - hop_size` samples of silence so
+    /// that the first real sample is already fully overlapped.
+    pub fn latency(&self) -> usize {
+        self.stft.fft_size - self.stft.hop_size
+    }
+
+    /// Returns how many more input samples (per channel) must be pushed
+    /// before `output_len` samples can be pulled at the current ratio
+    ///
+    /// Accounts for input already buffered and output already synthesized,
+    /// so calling this before every render quantum and pushing exactly the
+    /// returned amount keeps the stream running without drift.
+    ///
+    /// When slowing down in [`StretchMode::TransientPreserving`], a small
+    /// reserve is kept buffered because transients are stretched at unity
+    /// and therefore consume input faster than the nominal ratio.
+    pub fn input_required(&self, output_len: usize) -> usize {
+        let channel = &self.channels[0];
+        let hop_size = self.stft.hop_size;
+
+        let ready = channel.output.len();
+        if output_len <= ready {
+            return 0;
+        }
+
+        // Frames still to synthesize, and where the last of them starts
+        let frames = (output_len - ready).div_ceil(hop_size);
+        let mut planner = self.planner.clone();
+        let mut last_frame_pos = channel.read_pos;
+        for _ in 1..frames {
+            last_frame_pos += planner.next(hop_size, self.stretch_ratio, false);
+        }
+
+        let reserve = if self.mode == StretchMode::TransientPreserving && self.stretch_ratio < 1.0 {
+            let shortfall = (hop_size as f32 * (1.0 - self.stretch_ratio)).ceil() as usize;
+            shortfall * TRANSIENT_HOLD_HOPS
+        } else {
+            0
+        };
+
+        (last_frame_pos + self.stft.fft_size + reserve).saturating_sub(channel.input.len())
+    }
+
+    /// Appends stereo input to the streaming FIFOs
+    ///
+    /// Both channels receive `min(left.len(), right.len())` samples. If the
+    /// host pushes far more than it pulls, the oldest unread audio is
+    /// dropped once the backlog exceeds 16 FFT frames.
+    pub fn push_input(&mut self, left: &[f32], right: &[f32]) {
+        let len = left.len().min(right.len());
+        let max_backlog = self.stft.fft_size * MAX_BACKLOG_FRAMES;
+
+        for (channel, input) in self.channels.iter_mut().zip([left, right]) {
+            channel.input.extend_from_slice(&input[..len]);
+
+            let backlog = channel.input.len() - channel.read_pos;
+            if backlog > max_backlog {
+                channel.read_pos += backlog - max_backlog;
             }
         }
+    }
 
-        output
+    /// Synthesizes and copies stereo output into the given buffers
+    ///
+    /// Runs only as many STFT frames as the request needs, so input stays
+    /// queued (and follows ratio changes) until it is actually consumed.
+    ///
+    /// # Returns
+    /// Number of samples written per channel. If too little input was
+    /// pushed, the remainder of the buffers is filled with silence.
+    pub fn pull_output(&mut self, left: &mut [f32], right: &mut [f32]) -> usize {
+        let requested = left.len().min(right.len());
+        let fft_size = self.stft.fft_size;
+        let hop_size = self.stft.hop_size;
+
+        while self.channels[0].output.len() < requested
+            && self.channels[0].read_pos + fft_size <= self.channels[0].input.len()
+        {
+            let transient = self.stft.process_frame(
+                &mut self.channels,
+                self.mode,
+                &mut self.detector,
+                !self.planner.is_holding(),
+            );
+            let analysis_hop = self.planner.next(hop_size, self.stretch_ratio, transient);
+            advance_channels(&mut self.channels, analysis_hop);
+        }
+
+        let written = requested.min(self.channels[0].output.len());
+        for (channel, output) in self.channels.iter_mut().zip([left, right]) {
+            output[..written].copy_from_slice(&channel.output[..written]);
+            output[written..].fill(0.0);
+            channel.consume(written);
+        }
+
+        written
     }
 
-    /// Processes stereo audio with phase vocoder
-    /// 
-    /// Currently uses simple interpolation. In production, this would use
-    /// full FFT-based analysis-synthesis with phase unwrapping.
+    /// Processes stereo audio in place as a continuous stream
+    ///
+    /// Pushes the buffers into the stream and pulls the same number of
+    /// samples back out. At ratios other than 1.0 the host must feed
+    /// [`PhaseVocoder::input_required`] samples per block for the stream to
+    /// stay filled; see [`PhaseVocoder::push_input`] and
+    /// [`PhaseVocoder::pull_output`].
     pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
-        let left_processed = self.process(left);
-        let right_processed = self.process(right);
-        
-        // Copy back to original buffers
-        for (i, sample) in left_processed.iter().enumerate() {
-            if i < left.len() {
-                left[i] = *sample;
+        self.push_input(left, right);
+        self.pull_output(left, right);
+    }
+
+    /// Clears all streaming state (input FIFOs, phases and overlap-add buffers)
+    pub fn reset(&mut self) {
+        let fft_size = self.stft.fft_size;
+        self.channels = [
+            ChannelState::streaming(fft_size),
+            ChannelState::streaming(fft_size),
+        ];
+        self.planner = HopPlanner::default();
+        self.detector.reset();
+    }
+}
+
+impl TimeStretcher for PhaseVocoder {
+    fn set_stretch_ratio(&mut self, ratio: f32) {
+        PhaseVocoder::set_stretch_ratio(self, ratio);
+    }
+
+    fn get_stretch_ratio(&self) -> f32 {
+        PhaseVocoder::get_stretch_ratio(self)
+    }
+
+    fn input_required(&self, output_len: usize) -> usize {
+        PhaseVocoder::input_required(self, output_len)
+    }
+
+    fn push_input(&mut self, left: &[f32], right: &[f32]) {
+        PhaseVocoder::push_input(self, left, right);
+    }
+
+    fn pull_output(&mut self, left: &mut [f32], right: &mut [f32]) -> usize {
+        PhaseVocoder::pull_output(self, left, right)
+    }
+
+    fn latency(&self) -> usize {
+        PhaseVocoder::latency(self)
+    }
+
+    fn reset(&mut self) {
+        PhaseVocoder::reset(self);
+    }
+}
+
+/// Per-channel STFT state
+///
+/// Holds the pending input, the phase history used for instantaneous
+/// frequency estimation and the overlap-add accumulator.
+struct ChannelState {
+    /// Pending input samples; the next analysis frame starts at `read_pos`
+    input: Vec<f32>,
+    read_pos: usize,
+
+    /// Analysis hop that led to the current frame (0 before the first frame)
+    last_analysis_hop: usize,
+    /// Whether the previous frame was silent (phases restart from the analysis)
+    prev_silent: bool,
+
+    magnitude: Vec<f32>,
+    phase: Vec<f32>,
+    prev_phase: Vec<f32>,
+    synth_phase: Vec<f32>,
+    /// Spectral peak bins of the current frame (phase locking scratch)
+    peaks: Vec<usize>,
+
+    /// Overlap-add accumulator (fft_size samples)
+    overlap: Vec<f32>,
+    /// Finished output samples
+    output: Vec<f32>,
+}
+
+impl ChannelState {
+    fn new(fft_size: usize) -> Self {
+        let bins = fft_size / 2 + 1;
+        ChannelState {
+            input: Vec::with_capacity(fft_size * 4),
+            read_pos: 0,
+            last_analysis_hop: 0,
+            prev_silent: true,
+            magnitude: vec![0.0; bins],
+            phase: vec![0.0; bins],
+            prev_phase: vec![0.0; bins],
+            synth_phase: vec![0.0; bins],
+            peaks: Vec::with_capacity(bins / 2),
+            overlap: vec![0.0; fft_size],
+            output: Vec::with_capacity(fft_size * 4),
+        }
+    }
+
+    /// Creates channel state for streaming, primed with the lead-in silence
+    fn streaming(fft_size: usize) -> Self {
+        let mut channel = ChannelState::new(fft_size);
+        channel.input.resize(fft_size - fft_size / OVERLAP, 0.0);
+        channel
+    }
+
+    /// Drops `count` output samples that were handed to the caller, along
+    /// with input that no future frame will read
+    fn consume(&mut self, count: usize) {
+        self.output.drain(..count);
+        let consumed = self.read_pos.min(self.input.len());
+        self.input.drain(..consumed);
+        self.read_pos -= consumed;
+    }
+}
+
+/// STFT analysis/resynthesis engine shared by all channels
+struct Stft {
+    fft_size: usize,
+    hop_size: usize,
+    forward: Arc<dyn Fft<f32>>,
+    inverse: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    /// Compensates the inverse FFT scaling and the windowed overlap-add gain
+    output_scale: f32,
+    spectrum: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+
+    // Spectral envelope warping (log-domain envelope and peak scratch)
+    envelope_warp: Option<f32>,
+    lifter_cutoff: usize,
+    cepstrum: Vec<Complex<f32>>,
+    envelope: Vec<f32>,
+    envelope_peaks: Vec<usize>,
+}
+
+impl Stft {
+    fn new(sample_rate: u32, fft_size: usize) -> Self {
+        let hop_size = fft_size / OVERLAP;
+        let mut planner = FftPlanner::new();
+        let forward = planner.plan_fft_forward(fft_size);
+        let inverse = planner.plan_fft_inverse(fft_size);
+        let scratch_len = forward
+            .get_inplace_scratch_len()
+            .max(inverse.get_inplace_scratch_len());
+
+        let window = create_hann_window(fft_size);
+
+        // Sum of squared windows at synthesis hop spacing (analysis + synthesis window)
+        let overlap_gain: f32 = (0..hop_size)
+            .map(|n| {
+                window[n..]
+                    .iter()
+                    .step_by(hop_size)
+                    .map(|w| w * w)
+                    .sum::<f32>()
+            })
+            .sum::<f32>()
+            / hop_size as f32;
+
+        Stft {
+            fft_size,
+            hop_size,
+            forward,
+            inverse,
+            window,
+            output_scale: 1.0 / (fft_size as f32 * overlap_gain),
+            spectrum: vec![Complex::new(0.0, 0.0); fft_size],
+            scratch: vec![Complex::new(0.0, 0.0); scratch_len],
+            envelope_warp: None,
+            lifter_cutoff: ((sample_rate / LIFTER_MAX_F0) as usize).clamp(1, fft_size / 2 - 1),
+            cepstrum: vec![Complex::new(0.0, 0.0); fft_size],
+            envelope: vec![0.0; fft_size / 2 + 1],
+            envelope_peaks: Vec::with_capacity(fft_size / 4),
+        }
+    }
+
+    /// Runs one analysis/resynthesis step on all channels
+    ///
+    /// Channels are analyzed first so that transient detection sees the
+    /// combined spectrum and resets every channel on the same frame.
+    /// Appends `hop_size` finished samples to each channel's output.
+    ///
+    /// # Returns
+    /// Whether the frame was treated as a transient (only possible when
+    /// `allow_transient` is set)
+    fn process_frame(
+        &mut self,
+        channels: &mut [ChannelState],
+        mode: StretchMode,
+        detector: &mut TransientDetector,
+        allow_transient: bool,
+    ) -> bool {
+        for channel in channels.iter_mut() {
+            let frame = &channel.input[channel.read_pos..channel.read_pos + self.fft_size];
+            self.analyze(frame);
+
+            for (k, bin) in self.spectrum[..=self.fft_size / 2].iter().enumerate() {
+                channel.magnitude[k] = bin.norm();
+                channel.phase[k] = bin.arg();
             }
         }
-        
-        for (i, sample) in right_processed.iter().enumerate() {
-            if i < right.len() {
-                right[i] = *sample;
+
+        let phase_locking = mode == StretchMode::TransientPreserving;
+        let transient = phase_locking && detector.detect(channels, self.fft_size) && allow_transient;
+
+        if let Some(ratio) = self.envelope_warp {
+            for channel in channels.iter_mut() {
+                self.warp_envelope(&mut channel.magnitude, ratio);
+            }
+        }
+
+        for channel in channels.iter_mut() {
+            self.propagate_phases(channel, phase_locking, transient);
+            self.synthesize(channel);
+        }
+
+        transient
+    }
+
+    /// Windows a frame and computes its spectrum into `self.spectrum`
+    fn analyze(&mut self, frame: &[f32]) {
+        for ((bin, &sample), &w) in self.spectrum.iter_mut().zip(frame).zip(&self.window) {
+            *bin = Complex::new(sample * w, 0.0);
+        }
+        self.forward
+            .process_with_scratch(&mut self.spectrum, &mut self.scratch);
+    }
+
+    /// Replaces the spectral envelope of `magnitude` with the same envelope
+    /// read at `ratio` times each bin's frequency
+    ///
+    /// The envelope is estimated by joining the spectral peaks in the log
+    /// domain (so it rests on the harmonics rather than averaging them with
+    /// the valleys in between) and smoothing the result by cepstral
+    /// liftering.
+    fn warp_envelope(&mut self, magnitude: &mut [f32], ratio: f32) {
+        let half = self.fft_size / 2;
+
+        for (envelope, &m) in self.envelope.iter_mut().zip(&magnitude[..=half]) {
+            *envelope = m.max(LOG_FLOOR).ln();
+        }
+
+        // Interpolate linearly (in dB) between peaks, holding the outer ones
+        if find_peaks(&magnitude[..=half], &mut self.envelope_peaks) {
+            let peaks = &self.envelope_peaks;
+            let first = self.envelope[peaks[0]];
+            let last = self.envelope[peaks[peaks.len() - 1]];
+            self.envelope[..peaks[0]].fill(first);
+            self.envelope[peaks[peaks.len() - 1]..].fill(last);
+
+            for pair in peaks.windows(2) {
+                let (a, b) = (pair[0], pair[1]);
+                let (start, end) = (self.envelope[a], self.envelope[b]);
+                for k in a + 1..b {
+                    let t = (k - a) as f32 / (b - a) as f32;
+                    self.envelope[k] = start + (end - start) * t;
+                }
             }
         }
+        self.smooth_envelope();
+
+        for (k, m) in magnitude[..=half].iter_mut().enumerate() {
+            let source = k as f32 * ratio;
+            let index = source as usize;
+            let warped = if index >= half {
+                self.envelope[half]
+            } else {
+                let frac = source - index as f32;
+                self.envelope[index] * (1.0 - frac) + self.envelope[index + 1] * frac
+            };
+            *m *= (warped - self.envelope[k]).exp();
+        }
+    }
+
+    /// Smooths the log-domain `envelope` in place by keeping only the
+    /// quefrencies below one period of an 800 Hz fundamental, so it follows
+    /// formants but not individual harmonics
+    fn smooth_envelope(&mut self) {
+        let half = self.fft_size / 2;
+
+        // Real cepstrum of the (symmetric) log spectrum
+        for (k, &value) in self.envelope.iter().enumerate() {
+            self.cepstrum[k] = Complex::new(value, 0.0);
+            if k > 0 && k < half {
+                self.cepstrum[self.fft_size - k] = Complex::new(value, 0.0);
+            }
+        }
+        self.inverse
+            .process_with_scratch(&mut self.cepstrum, &mut self.scratch);
+
+        // Lifter (undoing the inverse FFT scaling)
+        let scale = 1.0 / self.fft_size as f32;
+        for (q, c) in self.cepstrum.iter_mut().enumerate() {
+            if q.min(self.fft_size - q) <= self.lifter_cutoff {
+                *c *= scale;
+            } else {
+                *c = Complex::new(0.0, 0.0);
+            }
+        }
+        self.forward
+            .process_with_scratch(&mut self.cepstrum, &mut self.scratch);
+
+        for (envelope, c) in self.envelope.iter_mut().zip(&self.cepstrum) {
+            *envelope = c.re;
+        }
+    }
+
+    /// Advances synthesis phases using the instantaneous frequency of each bin
+    ///
+    /// With `phase_locking` only spectral peaks are propagated; every other
+    /// bin keeps its analysis phase offset to the peak whose region it lies
+    /// in. With `reset` the synthesis phases restart from the analysis phases.
+    fn propagate_phases(&self, channel: &mut ChannelState, phase_locking: bool, reset: bool) {
+        let analysis_hop = channel.last_analysis_hop;
+        let silent = channel.magnitude.iter().sum::<f32>() < SILENCE_THRESHOLD * self.fft_size as f32;
+
+        if analysis_hop == 0 || channel.prev_silent || reset {
+            // First frame after silence (or a transient): start from the
+            // analysis phases so the bins stay vertically coherent
+            channel.synth_phase.copy_from_slice(&channel.phase);
+        } else if phase_locking && find_peaks(&channel.magnitude, &mut channel.peaks) {
+            for &peak in &channel.peaks {
+                channel.synth_phase[peak] = self.advance_phase(channel, peak, analysis_hop);
+            }
+
+            // Regions of influence end halfway between neighbouring peaks
+            let bins = channel.phase.len();
+            let mut region_start = 0;
+            for (i, &peak) in channel.peaks.iter().enumerate() {
+                let region_end = channel
+                    .peaks
+                    .get(i + 1)
+                    .map_or(bins, |&next| (peak + next) / 2 + 1);
+                let rotation = channel.synth_phase[peak] - channel.phase[peak];
+
+                for k in region_start..region_end {
+                    if k != peak {
+                        channel.synth_phase[k] = wrap_phase(channel.phase[k] + rotation);
+                    }
+                }
+                region_start = region_end;
+            }
+        } else {
+            for k in 0..channel.phase.len() {
+                channel.synth_phase[k] = self.advance_phase(channel, k, analysis_hop);
+            }
+        }
+
+        channel.prev_phase.copy_from_slice(&channel.phase);
+        channel.prev_silent = silent;
+    }
+
+    /// Returns the next synthesis phase of bin `k` from its instantaneous frequency
+    #[inline]
+    fn advance_phase(&self, channel: &ChannelState, k: usize, analysis_hop: usize) -> f32 {
+        let deviation = unwrap_phase(
+            channel.phase[k],
+            channel.prev_phase[k],
+            k,
+            self.fft_size,
+            analysis_hop,
+        );
+        let bin_advance = 2.0 * PI * k as f32 * self.hop_size as f32 / self.fft_size as f32;
+        let hop_ratio = self.hop_size as f32 / analysis_hop as f32;
+        wrap_phase(channel.synth_phase[k] + bin_advance + deviation * hop_ratio)
+    }
+
+    /// Rebuilds the spectrum from magnitudes and synthesis phases, inverse
+    /// transforms it and overlap-adds the windowed result
+    fn synthesize(&mut self, channel: &mut ChannelState) {
+        let half = self.fft_size / 2;
+
+        for k in 0..=half {
+            self.spectrum[k] = Complex::from_polar(channel.magnitude[k], channel.synth_phase[k]);
+        }
+        // DC and Nyquist bins of a real signal are real
+        self.spectrum[0].im = 0.0;
+        self.spectrum[half].im = 0.0;
+        for k in 1..half {
+            self.spectrum[self.fft_size - k] = self.spectrum[k].conj();
+        }
+
+        self.inverse
+            .process_with_scratch(&mut self.spectrum, &mut self.scratch);
+
+        for ((acc, bin), &w) in channel.overlap.iter_mut().zip(&self.spectrum).zip(&self.window) {
+            *acc += bin.re * w * self.output_scale;
+        }
+
+        // The first hop of the accumulator is now complete
+        channel.output.extend_from_slice(&channel.overlap[..self.hop_size]);
+        channel.overlap.copy_within(self.hop_size.., 0);
+        let tail = self.fft_size - self.hop_size;
+        channel.overlap[tail..].fill(0.0);
+    }
+}
+
+/// Detects transient frames from multi-band spectral flux
+///
+/// Uses the same band split and weighting as the BPM onset detector in
+/// [`AudioAnalyzer`], summed over all channels.
+struct TransientDetector {
+    sample_rate: u32,
+    prev_bands: Option<BandMagnitudes>,
+    average_flux: f32,
+    /// Set when the previous frame triggered, so one attack resets only once
+    triggered: bool,
+}
+
+impl TransientDetector {
+    fn new(sample_rate: u32) -> Self {
+        TransientDetector {
+            sample_rate,
+            prev_bands: None,
+            average_flux: 0.0,
+            triggered: false,
+        }
+    }
+
+    /// Returns whether the current frame of `channels` starts a transient
+    fn detect(&mut self, channels: &[ChannelState], fft_size: usize) -> bool {
+        let mut bands = [0.0; FLUX_BAND_COUNT];
+        for channel in channels {
+            let channel_bands = AudioAnalyzer::band_magnitudes(&channel.magnitude, self.sample_rate, fft_size);
+            for (band, value) in bands.iter_mut().zip(channel_bands.iter()) {
+                *band += value;
+            }
+        }
+
+        let energy: f32 = bands.iter().sum();
+        let flux = self
+            .prev_bands
+            .as_ref()
+            .map_or(0.0, |prev| AudioAnalyzer::band_spectral_flux(&bands, prev));
+        // Relative flux is independent of the playback level
+        let relative_flux = if energy > SILENCE_THRESHOLD { flux / energy } else { 0.0 };
+
+        let transient = !self.triggered
+            && relative_flux > TRANSIENT_MIN_FLUX
+            && relative_flux > self.average_flux * TRANSIENT_FLUX_RATIO;
+
+        self.average_flux += (relative_flux - self.average_flux) * TRANSIENT_AVERAGE_COEFF;
+        self.prev_bands = Some(bands);
+        self.triggered = transient;
+
+        transient
+    }
+
+    fn reset(&mut self) {
+        self.prev_bands = None;
+        self.average_flux = 0.0;
+        self.triggered = false;
     }
 }
 
+/// Collects local magnitude maxima (greater than both neighbours on each side)
+///
+/// Returns `false` if the spectrum has no peaks.
+fn find_peaks(magnitude: &[f32], peaks: &mut Vec<usize>) -> bool {
+    peaks.clear();
+    for k in 2..magnitude.len().saturating_sub(2) {
+        let m = magnitude[k];
+        if m > magnitude[k - 1] && m > magnitude[k - 2] && m >= magnitude[k + 1] && m >= magnitude[k + 2] {
+            peaks.push(k);
+        }
+    }
+    !peaks.is_empty()
+}
+
+/// Advances every channel's analysis position by `analysis_hop`
+fn advance_channels(channels: &mut [ChannelState], analysis_hop: usize) {
+    for channel in channels.iter_mut() {
+        channel.read_pos += analysis_hop;
+        channel.last_analysis_hop = analysis_hop;
+    }
+}
+
+/// Schedules analysis hops
+///
+/// Normally the hop is `hop_size * ratio`. After a transient the next
+/// [`TRANSIENT_HOLD_HOPS`] hops equal the synthesis hop, so every frame that
+/// contains the attack is resynthesized at unity (and therefore exactly).
+/// The input consumed ahead of or behind schedule is tracked as debt and
+/// repaid gradually so the long-term ratio stays exact.
+#[derive(Clone, Default)]
+struct HopPlanner {
+    /// Fractional part carried between integer hops
+    remainder: f32,
+    /// Input consumed beyond the nominal schedule (negative: behind)
+    debt: f32,
+    /// Remaining unity hops around the last transient
+    hold: usize,
+}
+
+impl HopPlanner {
+    fn is_holding(&self) -> bool {
+        self.hold > 0
+    }
+
+    /// Returns the hop from the current frame to the next one
+    fn next(&mut self, hop_size: usize, ratio: f32, transient: bool) -> usize {
+        let scheduled = next_analysis_hop(hop_size, ratio, &mut self.remainder);
+
+        if transient {
+            self.hold = TRANSIENT_HOLD_HOPS;
+        }
+        if self.hold > 0 {
+            self.hold -= 1;
+            self.debt += hop_size as f32 - scheduled as f32;
+            return hop_size;
+        }
+
+        // Repay at most half the scheduled hop per frame, so playback never
+        // stalls while catching up after a transient
+        let max_step = (scheduled / 2) as f32;
+        let adjust = self.debt.clamp(-max_step, max_step).round();
+        self.debt -= adjust;
+        (scheduled as f32 - adjust).max(1.0) as usize
+    }
+}
+
+/// Computes the next integer analysis hop, carrying the fractional part over
+/// so that the average hop is exactly `hop_size * ratio`
+fn next_analysis_hop(hop_size: usize, ratio: f32, remainder: &mut f32) -> usize {
+    let exact = hop_size as f32 * ratio + *remainder;
+    let hop = exact.floor().max(1.0);
+    *remainder = exact - hop;
+    hop as usize
+}
+
 /// Creates a Hann window for STFT analysis
-/// 
+///
 /// The Hann window provides good frequency resolution and perfect
 /// reconstruction with 75% overlap.
-/// 
+///
 /// Formula:
 /// ```text
 /// w[n] = 0.5 * (1 - cos(2π * n / (N-1)))
 /// ```
-#[allow(dead_code)]
 fn create_hann_window(size: usize) -> Vec<f32> {
     let mut window = vec![0.0; size];
     let n_minus_1 = (size - 1) as f32;
-    
+
     for (n, w) in window.iter_mut().enumerate() {
         let n_f = n as f32;
         *w = 0.5 * (1.0 - (2.0 * std::f32::consts::PI * n_f / n_minus_1).cos());
     }
-    
+
     window
 }
 
 /// Implements phase unwrapping for bin frequency estimation
-/// 
+///
 /// Used to estimate instantaneous frequency from phase differences
-/// between consecutive frames.
-#[allow(dead_code)]
+/// between consecutive frames. Returns the deviation from the bin's
+/// expected phase advance, wrapped to [-π, π].
 fn unwrap_phase(phase: f32, prev_phase: f32, bin_idx: usize, fft_size: usize, hop_size: usize) -> f32 {
-    let mut delta = phase - prev_phase;
-    
     // Expected phase advance for bin
-    let expected_phase = 2.0 * std::f32::consts::PI * bin_idx as f32 * hop_size as f32 / fft_size as f32;
-    
-    // Wrap delta to [-π, π]
-    while delta > std::f32::consts::PI {
-        delta -= 2.0 * std::f32::consts::PI;
-    }
-    while delta < -std::f32::consts::PI {
-        delta += 2.0 * std::f32::consts::PI;
-    }
-    
-    delta - expected_phase
+    let expected_phase = 2.0 * PI * bin_idx as f32 * hop_size as f32 / fft_size as f32;
+
+    wrap_phase(phase - prev_phase - expected_phase)
+}
+
+/// Wraps a phase value to [-π, π]
+#[inline]
+fn wrap_phase(phase: f32) -> f32 {
+    phase - 2.0 * PI * (phase / (2.0 * PI)).round()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Estimates the frequency of a (mostly) sinusoidal signal from its
+    /// positive-going zero crossings
+    fn estimate_frequency(samples: &[f32], sample_rate: f32) -> f32 {
+        let crossings: Vec<f32> = samples
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| w[0] <= 0.0 && w[1] > 0.0)
+            .map(|(i, w)| i as f32 + w[0] / (w[0] - w[1]))
+            .collect();
+        let periods = (crossings.len() - 1) as f32;
+        sample_rate * periods / (crossings[crossings.len() - 1] - crossings[0])
+    }
+
+    fn sine(freq: f32, sample_rate: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * PI * freq * i as f32 / sample_rate).sin() * 0.5)
+            .collect()
+    }
+
     #[test]
     fn test_phase_vocoder_creation() {
-        let vocoder = PhaseVocoder::new(1024);
+        let vocoder = PhaseVocoder::new(48000, 1024);
         assert_eq!(vocoder.get_stretch_ratio(), 1.0);
     }
 
     #[test]
     fn test_stretch_ratio_setting() {
-        let mut vocoder = PhaseVocoder::new(1024);
+        let mut vocoder = PhaseVocoder::new(48000, 1024);
         vocoder.set_stretch_ratio(2.0);
         assert_eq!(vocoder.get_stretch_ratio(), 2.0);
-        
+
         // Should clamp
         vocoder.set_stretch_ratio(3.0);
         assert_eq!(vocoder.get_stretch_ratio(), 2.0);
@@ -186,12 +954,12 @@ mod tests {
     fn test_hann_window() {
         let window = create_hann_window(256);
         assert_eq!(window.len(), 256);
-        
+
         // Window should be symmetric
         for i in 0..window.len() / 2 {
             assert!((window[i] - window[window.len() - 1 - i]).abs() < 0.001);
         }
-        
+
         // Window should be zero at endpoints
         assert!(window[0].abs() < 0.001);
         assert!(window[window.len() - 1].abs() < 0.001);
@@ -199,9 +967,248 @@ mod tests {
 
     #[test]
     fn test_process() {
-        let mut vocoder = PhaseVocoder::new(1024);
+        let mut vocoder = PhaseVocoder::new(48000, 1024);
         let input = vec![0.1; 256];
         let output = vocoder.process(&input);
         assert!(!output.is_empty());
     }
+
+    #[test]
+    fn test_output_length_follows_ratio() {
+        let mut vocoder = PhaseVocoder::new(48000, 1024);
+        let input = vec![0.1; 4800];
+
+        vocoder.set_stretch_ratio(0.5);
+        assert_eq!(vocoder.process(&input).len(), 9600);
+
+        vocoder.set_stretch_ratio(2.0);
+        assert_eq!(vocoder.process(&input).len(), 2400);
+    }
+
+    #[test]
+    fn test_unwrap_phase_recovers_bin_offset() {
+        // A sinusoid a quarter bin above bin 10 advances slightly faster than the bin centre
+        let fft_size = 1024;
+        let hop = 256;
+        let freq_bins = 10.25;
+        let advance = 2.0 * PI * freq_bins * hop as f32 / fft_size as f32;
+        let deviation = unwrap_phase(wrap_phase(1.0 + advance), 1.0, 10, fft_size, hop);
+        let expected = 2.0 * PI * 0.25 * hop as f32 / fft_size as f32;
+        assert!((deviation - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_pitch_preserved_across_ratio_range() {
+        let sample_rate = 48000.0;
+        let input = sine(440.0, sample_rate, 48000);
+
+        for &ratio in &[0.5, 0.75, 1.25, 1.5, 2.0] {
+            let mut vocoder = PhaseVocoder::new(48000, 2048);
+            vocoder.set_stretch_ratio(ratio);
+            let output = vocoder.process(&input);
+
+            // Ignore the edges where the zero padding fades in and out
+            let quarter = output.len() / 4;
+            let freq = estimate_frequency(&output[quarter..output.len() - quarter], sample_rate);
+            assert!(
+                (freq - 440.0).abs() < 440.0 * 0.01,
+                "ratio {} produced {} Hz",
+                ratio,
+                freq
+            );
+        }
+    }
+
+    #[test]
+    fn test_unity_ratio_reconstruction() {
+        // With equal analysis and synthesis hops the STFT must reconstruct the input
+        let mut vocoder = PhaseVocoder::new(48000, 1024);
+        vocoder.set_stretch_ratio(1.0 + 0.002);
+        let input = sine(1000.0, 48000.0, 8192);
+        let output = vocoder.process(&input);
+
+        let rms = |s: &[f32]| (s.iter().map(|x| x * x).sum::<f32>() / s.len() as f32).sqrt();
+        let mid = &output[2048..6000];
+        assert!((rms(mid) - rms(&input[2048..6000])).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_streaming_accounting() {
+        let mut vocoder = PhaseVocoder::new(48000, 1024);
+        let input = sine(440.0, 48000.0, 96000);
+        let mut consumed = 0;
+        let mut left = [0.0; 128];
+        let mut right = [0.0; 128];
+
+        for &ratio in &[0.5, 1.0, 1.37, 2.0] {
+            vocoder.set_stretch_ratio(ratio);
+            let start = consumed;
+
+            for _ in 0..100 {
+                let needed = vocoder.input_required(128);
+                let chunk = &input[consumed..consumed + needed];
+                vocoder.push_input(chunk, chunk);
+                consumed += needed;
+
+                // Exactly the reported amount is always enough
+                assert_eq!(vocoder.pull_output(&mut left, &mut right), 128);
+                assert_eq!(vocoder.input_required(0), 0);
+            }
+
+            // Input consumption follows the ratio (within one analysis frame)
+            let expected = 12800.0 * ratio;
+            let actual = (consumed - start) as f32;
+            assert!((actual - expected).abs() <= 1024.0, "ratio {}: {} vs {}", ratio, actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_streaming_preserves_pitch() {
+        let mut vocoder = PhaseVocoder::new(48000, 2048);
+        vocoder.set_stretch_ratio(0.8);
+        let input = sine(440.0, 48000.0, 96000);
+        let mut output = Vec::new();
+        let mut consumed = 0;
+        let mut left = [0.0; 128];
+        let mut right = [0.0; 128];
+
+        while consumed + 4096 < input.len() {
+            let needed = vocoder.input_required(128);
+            vocoder.push_input(&input[consumed..consumed + needed], &input[consumed..consumed + needed]);
+            consumed += needed;
+            vocoder.pull_output(&mut left, &mut right);
+            output.extend_from_slice(&left);
+        }
+
+        let freq = estimate_frequency(&output[8192..], 48000.0);
+        assert!((freq - 440.0).abs() < 440.0 * 0.01, "got {} Hz", freq);
+    }
+
+    #[test]
+    fn test_process_stereo_streams_with_latency() {
+        let mut vocoder = PhaseVocoder::new(48000, 1024);
+        let input = sine(1000.0, 48000.0, 8192);
+        let mut output = Vec::new();
+
+        for chunk in input.chunks(256) {
+            let mut left = chunk.to_vec();
+            let mut right = chunk.to_vec();
+            vocoder.process_stereo(&mut left, &mut right);
+            assert_eq!(left, right);
+            output.extend_from_slice(&left);
+        }
+
+        // At unity ratio the stream is the input delayed by the lead-in
+        let latency = vocoder.latency();
+        for i in 4096..8192 {
+            assert!((output[i] - input[i - latency]).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_underrun_outputs_silence() {
+        let mut vocoder = PhaseVocoder::new(48000, 1024);
+        let mut left = [1.0; 128];
+        let mut right = [1.0; 128];
+        assert_eq!(vocoder.pull_output(&mut left, &mut right), 0);
+        assert!(left.iter().chain(right.iter()).all(|&s| s == 0.0));
+    }
+
+    /// Decaying 3 kHz clicks every 250ms over a quiet 110 Hz bed
+    fn click_track(sample_rate: f32, len: usize) -> (Vec<f32>, Vec<usize>) {
+        let period = (sample_rate * 0.25) as usize;
+        let onsets: Vec<usize> = (period / 2..len).step_by(period).collect();
+        let mut samples: Vec<f32> = (0..len)
+            .map(|i| (2.0 * PI * 110.0 * i as f32 / sample_rate).sin() * 0.05)
+            .collect();
+        for &onset in &onsets {
+            for (i, sample) in samples[onset..].iter_mut().take(period / 2).enumerate() {
+                let t = i as f32 / sample_rate;
+                *sample += (2.0 * PI * 3000.0 * t).sin() * (-t * 400.0).exp() * 0.8;
+            }
+        }
+        (samples, onsets)
+    }
+
+    /// Number of samples within each stretched click whose level exceeds 10% of its peak
+    fn click_spread(output: &[f32], onsets: &[usize], ratio: f32) -> usize {
+        onsets[1..onsets.len() - 1]
+            .iter()
+            .map(|&onset| {
+                let centre = (onset as f32 / ratio) as usize;
+                let segment = &output[centre - 4000..centre + 8000];
+                let peak = segment.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+                segment.iter().filter(|s| s.abs() > peak * 0.1).count()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_transient_mode_keeps_attacks_sharp() {
+        let (input, onsets) = click_track(48000.0, 48000 * 2);
+
+        let mut spreads = Vec::new();
+        for mode in [StretchMode::Standard, StretchMode::TransientPreserving] {
+            let mut vocoder = PhaseVocoder::new(48000, 2048);
+            vocoder.set_stretch_ratio(0.5);
+            vocoder.set_mode(mode);
+            let output = vocoder.process(&input);
+            spreads.push(click_spread(&output, &onsets, 0.5));
+        }
+
+        assert!(spreads[1] * 3 < spreads[0] * 2, "standard {} vs transient {}", spreads[0], spreads[1]);
+    }
+
+    #[test]
+    fn test_hop_repayment_after_transient() {
+        // At ratio 0.5 the scheduled hop is half the 256-sample synthesis hop
+        let mut planner = HopPlanner::default();
+        let mut hops = vec![planner.next(256, 0.5, true)];
+        hops.extend((0..40).map(|_| planner.next(256, 0.5, false)));
+
+        // Unity hops across the attack, then the extra input is paid back
+        // without the hop collapsing (which would freeze the sound)
+        assert_eq!(hops[..TRANSIENT_HOLD_HOPS], [256; TRANSIENT_HOLD_HOPS]);
+        let min = *hops[TRANSIENT_HOLD_HOPS..].iter().min().unwrap();
+        assert!(min >= 64, "{}", min);
+        assert_eq!(hops.iter().sum::<usize>(), 41 * 128);
+    }
+
+    #[test]
+    fn test_transient_mode_preserves_pitch() {
+        let input = sine(440.0, 48000.0, 48000);
+        for &ratio in &[0.5, 2.0] {
+            let mut vocoder = PhaseVocoder::new(48000, 2048);
+            vocoder.set_mode(StretchMode::TransientPreserving);
+            vocoder.set_stretch_ratio(ratio);
+            let output = vocoder.process(&input);
+            let quarter = output.len() / 4;
+            let freq = estimate_frequency(&output[quarter..output.len() - quarter], 48000.0);
+            assert!((freq - 440.0).abs() < 440.0 * 0.01, "ratio {} produced {} Hz", ratio, freq);
+        }
+    }
+
+    #[test]
+    fn test_transient_mode_streaming_accounting() {
+        let (input, _) = click_track(48000.0, 48000 * 4);
+        let mut left = [0.0; 128];
+        let mut right = [0.0; 128];
+
+        for &ratio in &[0.5, 1.5] {
+            let mut vocoder = PhaseVocoder::new(48000, 1024);
+            vocoder.set_mode(StretchMode::TransientPreserving);
+            vocoder.set_stretch_ratio(ratio);
+            let mut consumed = 0;
+
+            for _ in 0..400 {
+                let needed = vocoder.input_required(128);
+                vocoder.push_input(&input[consumed..consumed + needed], &input[consumed..consumed + needed]);
+                consumed += needed;
+                assert_eq!(vocoder.pull_output(&mut left, &mut right), 128);
+            }
+
+            let expected = 51200.0 * ratio;
+            assert!((consumed as f32 - expected).abs() <= 2048.0, "ratio {}: {}", ratio, consumed);
+        }
+    }
 }
@@ -1,25 +1,95 @@
 //! Pitch shifting without changing tempo
 //!
-//! Implements real-time pitch shifting using a semi-tone based approach
-//! with resampling. Preserves tempo while shifting pitch up or down.
+//! Implements real-time pitch shifting by combining time-stretching with
+//! resampling. Preserves tempo while shifting pitch up or down.
 //!
-//! Algorithm: Phase Vocoder-based pitch shifting
-//! - Modifies the stretch ratio in the analysis-synthesis loop
-//! - Combines with inverse stretch ratio to maintain original tempo
-//! - Produces artifact-free pitch shifts ±12 semitones
+//! The latency is the same at every setting, so moving the pitch never
+//! moves the deck against the beat grid. At 0 cents the stages are
+//! bypassed and the input is only delayed. Engaging the pitch restarts the
+//! stages from recent input and crossfades into the shifted stream, which
+//! is delayed to line up with the dry one; returning to 0 cents fades back.
+//!
+//! Algorithm: Phase vocoder stretch + varispeed resampling
+//! - Stretches the stream by the pitch ratio with the phase vocoder
+//!   (duration × ratio, pitch unchanged), using phase locking so every
+//!   stretched frame stays vertically coherent
+//! - Resamples the stretched stream by the same ratio
+//!   (duration restored, pitch × ratio)
+//! - Both stages keep their state between calls, so block boundaries are
+//!   seamless
+//!
+//! # Formant preservation
+//! Resampling moves the spectral envelope along with the harmonics, so
+//! voices shifted by more than a few semitones sound chipmunk-like or muddy.
+//! With formant preservation enabled the vocoder pre-warps the envelope in
+//! the opposite direction (see [`PhaseVocoder::set_envelope_warp`]), so after
+//! resampling the harmonics move but the formants stay in place.
 //!
 //! # Performance
-//! - Time complexity: O(n) for simple case, O(n log n) for full FFT
-//! - Memory: ~50KB per instance
-//! - Latency: Depends on FFT size
+//! - Time complexity: O(n log n) per frame (FFT-based)
+//! - Memory: O(fft_size) for buffers
+//! - Latency: fft_size plus twice the phase vocoder latency, at any pitch
+//!   (see [`PitchShifter::latency`])
+
+use crate::delay_line::DelayLine;
+use crate::phase_vocoder::{PhaseVocoder, StretchMode};
+use crate::resampler::Resampler;
+use crate::smoothing::SmoothedParam;
+use crate::time_stretch::TimeStretcher;
+
+/// Input primed into the vocoder, in FFT frames, so the resampler never
+/// waits on a partially filled analysis frame
+///
+/// Blocks are processed in chunks of up to one frame, each of which needs up
+/// to a frame of stretched output before the vocoder has seen the input
+/// behind it. One frame also covers the transient reserve and sudden jumps
+/// across the whole ratio range; half a frame underruns.
+const CUSHION_FRAMES: usize = 1;
+
+/// Lowest pitch ratio, where the vocoder latency (measured on the stretched
+/// stream) is longest
+const MIN_RATIO: f32 = 0.5;
+
+/// Extra stretched samples the resampler may ask for beyond 2 × fft_size
+/// (its read position and interpolation lookahead)
+const STRETCH_SLACK: usize = 8;
+
+/// Crossfade between the dry input and the shifted stream
+const BYPASS_FADE_MS: f32 = 10.0;
+
+/// Glide of the alignment delay when the pitch ratio changes
+const ALIGN_GLIDE_MS: f32 = 50.0;
 
 /// Pitch shifter for shifting pitch without changing tempo
 ///
-/// Implements pitch shifting through frequency-domain processing.
-/// Shifts pitch while maintaining playback speed.
+/// Streams audio through a phase vocoder and a resampler running at
+/// reciprocal ratios. Shifts pitch while maintaining playback speed.
 pub struct PitchShifter {
-    // Pitch control
+    // Pitch control (cents are kept as set so they read back exactly)
     pitch_ratio: f32,
+    pitch_cents: f32,
+    preserve_formants: bool,
+
+    fft_size: usize,
+    latency: usize,
+    vocoder: PhaseVocoder,
+    resampler: Resampler,
+
+    // Bypass: the input, which is both the dry signal (read `latency` back)
+    // and the history that primes the stages on engage; dry samples still
+    // to play while the stages fill; and the dry/shifted crossfade
+    bypassed: bool,
+    dry_delay: [DelayLine; 2],
+    warmup: usize,
+    wet: SmoothedParam,
+
+    // Tops the stage latency, which depends on the ratio, up to `latency`
+    align_delay: [DelayLine; 2],
+    align: SmoothedParam,
+
+    // Scratch buffers for the stretched stream and mono processing
+    stretched: [Vec<f32>; 2],
+    mono_scratch: Vec<f32>,
 }
 
 impl PitchShifter {
@@ -27,11 +97,37 @@ impl PitchShifter {
     /// 
     /// # Arguments
     /// * `sample_rate` - Sample rate in Hz
-    /// * `fft_size` - FFT size for analysis
-    pub fn new(_sample_rate: u32, _fft_size: usize) -> Self {
-        PitchShifter {
+    /// * `fft_size` - FFT size for the phase vocoder stage
+    pub fn new(sample_rate: u32, fft_size: usize) -> Self {
+        let mut vocoder = PhaseVocoder::new(sample_rate, fft_size);
+        vocoder.set_mode(StretchMode::TransientPreserving);
+        let longest_lead_in = (vocoder.latency() as f32 / MIN_RATIO).ceil() as usize;
+        let latency = fft_size * CUSHION_FRAMES + longest_lead_in;
+
+        let mut shifter = PitchShifter {
             pitch_ratio: 1.0,
-        }
+            pitch_cents: 0.0,
+            preserve_formants: false,
+            fft_size,
+            latency,
+            vocoder,
+            resampler: Resampler::new(),
+            bypassed: true,
+            // Room for a whole chunk on top of the latency
+            dry_delay: [DelayLine::new(latency + fft_size), DelayLine::new(latency + fft_size)],
+            warmup: 0,
+            wet: SmoothedParam::new(0.0, sample_rate, BYPASS_FADE_MS),
+            align_delay: [DelayLine::new(latency), DelayLine::new(latency)],
+            align: SmoothedParam::new(0.0, sample_rate, ALIGN_GLIDE_MS),
+            // Sized for the worst case (an octave up), so processing never allocates
+            stretched: [
+                vec![0.0; fft_size * 2 + STRETCH_SLACK],
+                vec![0.0; fft_size * 2 + STRETCH_SLACK],
+            ],
+            mono_scratch: Vec::with_capacity(fft_size),
+        };
+        shifter.reset();
+        shifter
     }
 
     /// Sets the pitch shift ratio
@@ -40,6 +136,8 @@ impl PitchShifter {
     /// * `ratio` - Pitch ratio (0.5 = one octave down, 2.0 = one octave up)
     pub fn set_pitch_ratio(&mut self, ratio: f32) {
         self.pitch_ratio = ratio.clamp(0.5, 2.0);
+        self.pitch_cents = ratio_to_cents(self.pitch_ratio);
+        self.update_stages();
     }
 
     /// Gets the current pitch ratio
@@ -47,56 +145,218 @@ impl PitchShifter {
         self.pitch_ratio
     }
 
+    /// Sets the pitch shift in cents (1/100 of a semitone)
+    /// 
+    /// # Arguments
+    /// * `cents` - Pitch shift in cents (-1200.0 to +1200.0)
+    ///   - 50.0 = quarter tone up
+    ///   - -31.77 = retune A440 to A432
+    pub fn set_pitch_cents(&mut self, cents: f32) {
+        self.pitch_cents = cents.clamp(-1200.0, 1200.0);
+        self.pitch_ratio = cents_to_ratio(self.pitch_cents).clamp(0.5, 2.0);
+        self.update_stages();
+    }
+
+    /// Gets the current pitch shift in cents
+    pub fn get_pitch_cents(&self) -> f32 {
+        self.pitch_cents
+    }
+
+    /// Enables or disables formant preservation
+    /// 
+    /// Keeps the spectral envelope (vocal formants) in place while the
+    /// pitch moves. Roughly doubles the FFT work of the shifter.
+    pub fn set_formant_preservation(&mut self, enabled: bool) {
+        self.preserve_formants = enabled;
+        self.update_stages();
+    }
+
+    /// Gets whether formant preservation is enabled
+    pub fn get_formant_preservation(&self) -> bool {
+        self.preserve_formants
+    }
+
+    /// Gets the delay between input and output in samples
+    /// 
+    /// Fixed for a given FFT size. The stages are slowest an octave down,
+    /// since the vocoder latency is measured on the stretched stream; at
+    /// other ratios, and when bypassed, the output is delayed to match.
+    pub fn latency(&self) -> usize {
+        self.latency
+    }
+
     /// Processes a mono buffer with pitch shifting
     /// 
-    /// Uses a simple resampling approach that preserves tempo
+    /// Shares the stream with [`PitchShifter::process_stereo`]; use one or
+    /// the other for a given stream.
     pub fn process(&mut self, buffer: &mut [f32]) {
-        if (self.pitch_ratio - 1.0).abs() < 0.001 {
-            return;
-        }
+        let mut right = std::mem::take(&mut self.mono_scratch);
+        right.clear();
+        right.extend_from_slice(buffer);
 
-        // Simple pitch shift through resampling
-        self._resample(buffer);
+        self.process_stereo(buffer, &mut right);
+        self.mono_scratch = right;
     }
 
     /// Processes stereo audio with pitch shifting
+    /// 
+    /// The output has the same length as the input, delayed by
+    /// [`PitchShifter::latency`] samples.
     pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
-        if (self.pitch_ratio - 1.0).abs() < 0.001 {
+        let len = left.len().min(right.len());
+
+        // Work in chunks of at most one FFT frame so large host blocks never
+        // overflow the vocoder backlog
+        for (left, right) in left[..len]
+            .chunks_mut(self.fft_size)
+            .zip(right[..len].chunks_mut(self.fft_size))
+        {
+            self.update_bypass();
+            let [dry_left, dry_right] = &mut self.dry_delay;
+            for (&l, &r) in left.iter().zip(right.iter()) {
+                dry_left.write(l);
+                dry_right.write(r);
+            }
+
+            if self.bypassed {
+                let len = left.len();
+                for (i, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+                    let (dry_l, dry_r) = self.dry_at(len - i);
+                    *l = dry_l;
+                    *r = dry_r;
+                }
+            } else {
+                self.shift_chunk(left, right);
+            }
+        }
+    }
+
+    /// Clears all streaming state
+    ///
+    /// A shift that is already set starts straight away (after the latency)
+    /// rather than fading in.
+    pub fn reset(&mut self) {
+        for delay in self.dry_delay.iter_mut() {
+            delay.reset();
+        }
+        self.warmup = 0;
+        self.bypassed = self.pitch_cents == 0.0;
+        self.wet.set_immediate(if self.bypassed { 0.0 } else { 1.0 });
+        self.restart_stages();
+        self.align.set_immediate(self.alignment());
+    }
+
+    /// Engages the stages when the pitch moves off 0 cents, and bypasses
+    /// them once the fade back to the dry input has finished
+    fn update_bypass(&mut self) {
+        let target = if self.pitch_cents != 0.0 { 1.0 } else { 0.0 };
+        if self.bypassed {
+            if target == 1.0 {
+                self.restart_stages();
+                self.bypassed = false;
+
+                // Play dry until the vocoder has worked through its lead-in
+                // and the alignment delay has filled
+                let alignment = self.alignment();
+                self.align.set_immediate(alignment);
+                let lead_in = self.vocoder.latency() as f32 / self.pitch_ratio;
+                self.warmup = (lead_in + alignment).round() as usize;
+                self.wet.set_target(1.0);
+            }
             return;
         }
 
-        // Apply same pitch shift to both channels
-        self._resample(left);
-        self._resample(right);
+        if self.wet.target() != target {
+            self.wet.set_target(target);
+        }
+        let alignment = self.alignment();
+        if self.align.target() != alignment {
+            self.align.set_target(alignment);
+        }
+        if target == 0.0 && !self.wet.is_smoothing() {
+            self.bypassed = true;
+            self.warmup = 0;
+        }
+    }
+
+    /// Delay that tops the stage latency at the current ratio up to `latency`
+    fn alignment(&self) -> f32 {
+        let lead_in = self.vocoder.latency() as f32 / self.pitch_ratio;
+        self.latency as f32 - (self.fft_size * CUSHION_FRAMES) as f32 - lead_in
+    }
+
+    /// The dry pair that lines up with the output sample `back` samples
+    /// before the end of the chunk just written to the dry delay
+    fn dry_at(&self, back: usize) -> (f32, f32) {
+        let delay = (back + self.latency) as f32;
+        (self.dry_delay[0].read(delay), self.dry_delay[1].read(delay))
     }
 
-    /// Resamples audio based on pitch ratio using linear interpolation
-    fn _resample(&self, buffer: &mut [f32]) {
-        let mut output = vec![0.0; buffer.len()];
-        let pitch_ratio = self.pitch_ratio;
+    /// Runs one chunk through the stages and mixes it with the dry input
+    fn shift_chunk(&mut self, left: &mut [f32], right: &mut [f32]) {
+        self.vocoder.push_input(left, right);
+
+        // Pull exactly what the resampler needs for this chunk
+        let needed = self.resampler.input_required(left.len());
+        let [stretched_left, stretched_right] = &mut self.stretched;
+        let (stretched_left, stretched_right) =
+            (&mut stretched_left[..needed], &mut stretched_right[..needed]);
+        self.vocoder.pull_output(stretched_left, stretched_right);
 
-        // For pitch shifting, we need to resample the time-stretched audio
-        // A higher pitch ratio means we read faster from the buffer
+        self.resampler.push_input(stretched_left, stretched_right);
+        self.resampler.pull_output(left, right);
 
-        for i in 0..buffer.len() {
-            let idx = (i as f32) * pitch_ratio;
-            let int_idx = idx.floor() as usize;
-            let frac = idx - int_idx as f32;
+        let len = left.len();
+        for (i, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+            let alignment = self.align.next_value() + 1.0;
+            let [align_left, align_right] = &mut self.align_delay;
+            align_left.write(*l);
+            align_right.write(*r);
+            *l = align_left.read(alignment);
+            *r = align_right.read(alignment);
 
-            if int_idx + 1 < buffer.len() {
-                // Linear interpolation
-                output[i] = buffer[int_idx] * (1.0 - frac) + buffer[int_idx + 1] * frac;
-            } else if int_idx < buffer.len() {
-                output[i] = buffer[int_idx];
+            let (dry_l, dry_r) = self.dry_at(len - i);
+            if self.warmup > 0 {
+                self.warmup -= 1;
+                *l = dry_l;
+                *r = dry_r;
+                continue;
             }
+            let wet = self.wet.next_value();
+            *l = dry_l + (*l - dry_l) * wet;
+            *r = dry_r + (*r - dry_r) * wet;
         }
+    }
 
-        // Copy result back
-        for (i, sample) in output.iter().enumerate() {
-            if i < buffer.len() {
-                buffer[i] = *sample;
-            }
+    /// Restarts the vocoder and resampler, primed with the last
+    /// `CUSHION_FRAMES` of input (silence after a reset) so the resampler
+    /// never waits on a partially filled analysis frame
+    fn restart_stages(&mut self) {
+        self.vocoder.reset();
+        self.resampler.reset();
+        self.update_stages();
+        for delay in self.align_delay.iter_mut() {
+            delay.reset();
+        }
+
+        let cushion = self.fft_size * CUSHION_FRAMES;
+        let [left, right] = &mut self.stretched;
+        for (i, (l, r)) in left[..cushion].iter_mut().zip(right[..cushion].iter_mut()).enumerate() {
+            let delay = (cushion - i) as f32;
+            *l = self.dry_delay[0].read(delay);
+            *r = self.dry_delay[1].read(delay);
         }
+        self.vocoder.push_input(&left[..cushion], &right[..cushion]);
+    }
+
+    /// Stretches by the pitch ratio, then resamples back to the original duration
+    fn update_stages(&mut self) {
+        self.vocoder.set_stretch_ratio(1.0 / self.pitch_ratio);
+        self.resampler.set_stretch_ratio(self.pitch_ratio);
+
+        // Resampling by p moves the envelope from k to k * p, so read it from k * p
+        let warp = self.preserve_formants.then_some(self.pitch_ratio);
+        self.vocoder.set_envelope_warp(warp);
     }
 }
 
@@ -117,9 +377,27 @@ pub fn ratio_to_semitones(ratio: f32) -> i32 {
     (ratio.log2() * 12.0).round() as i32
 }
 
+/// Converts cents to frequency ratio
+/// 
+/// Formula: ratio = 2^(cents / 1200)
+/// 
+/// # Examples
+/// - 1200 cents = 2.0 (one octave up)
+/// - 100 cents = 1.0595... (one semitone)
+/// - -31.77 cents = 432/440
+pub fn cents_to_ratio(cents: f32) -> f32 {
+    2.0_f64.powf(cents as f64 / 1200.0) as f32
+}
+
+/// Converts frequency ratio to cents (no rounding)
+pub fn ratio_to_cents(ratio: f32) -> f32 {
+    ((ratio as f64).log2() * 1200.0) as f32
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::f32::consts::PI;
 
     #[test]
     fn test_pitch_shifter_creation() {
@@ -162,18 +440,235 @@ mod tests {
         assert_eq!(back, semitones);
     }
 
+    #[test]
+    fn test_cents_conversion() {
+        assert!((cents_to_ratio(1200.0) - 2.0).abs() < 1e-6);
+        assert!((cents_to_ratio(100.0) - semitones_to_ratio(1)).abs() < 1e-6);
+        assert!((cents_to_ratio(ratio_to_cents(432.0 / 440.0)) - 432.0 / 440.0).abs() < 1e-7);
+
+        for &cents in &[-1200.0, -50.0, -31.77, 0.0, 12.5, 50.0, 700.0] {
+            assert!((ratio_to_cents(cents_to_ratio(cents)) - cents).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_pitch_cents_setting() {
+        let mut shifter = PitchShifter::new(48000, 1024);
+        shifter.set_pitch_cents(-31.77);
+        assert_eq!(shifter.get_pitch_cents(), -31.77);
+        assert!((shifter.get_pitch_ratio() * 440.0 - 432.0).abs() < 0.01);
+
+        shifter.set_pitch_cents(2000.0);
+        assert_eq!(shifter.get_pitch_cents(), 1200.0);
+        assert_eq!(shifter.get_pitch_ratio(), 2.0);
+    }
+
     #[test]
     fn test_process() {
         let mut shifter = PitchShifter::new(48000, 1024);
         shifter.set_pitch_ratio(1.5);
         
+        // Output starts after the shifter latency
         let mut buffer = vec![0.1; 256];
-        shifter.process(&mut buffer);
+        for _ in 0..16 {
+            buffer.fill(0.1);
+            shifter.process(&mut buffer);
+        }
         
         // Should maintain some content
         assert!(buffer.iter().any(|&s| s != 0.0));
     }
 
+    #[test]
+    fn test_shift_preserves_duration() {
+        let mut shifter = PitchShifter::new(48000, 1024);
+        let period = 48000.0 / 220.0;
+        let mut output = Vec::new();
+
+        for &(cents, expected) in &[(700.0, 329.63), (-1200.0, 110.0), (350.0, 269.4)] {
+            shifter.reset();
+            shifter.set_pitch_cents(cents);
+            output.clear();
+
+            // Odd block size so frames never line up with blocks
+            for block in 0..150 {
+                let mut left: Vec<f32> = (0..137)
+                    .map(|i| (2.0 * PI * (block * 137 + i) as f32 / period).sin() * 0.5)
+                    .collect();
+                let mut right = left.clone();
+                shifter.process_stereo(&mut left, &mut right);
+                output.extend_from_slice(&left);
+            }
+            assert_eq!(output.len(), 150 * 137);
+
+            // Frequency shifted, with no dropouts once the stream is running
+            let steady = &output[shifter.latency() + 2048..];
+            let freq = estimate_frequency(steady, 48000.0);
+            assert!((freq - expected).abs() < expected * 0.01, "{} cents: {} Hz", cents, freq);
+
+            for window in steady.chunks(256) {
+                let peak = window.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+                assert!(peak > 0.4 && peak < 0.6, "{} cents: block peak {}", cents, peak);
+            }
+        }
+    }
+
+    #[test]
+    fn test_latency_alignment() {
+        // A hair off unity, so the stages stay engaged
+        let mut shifter = PitchShifter::new(48000, 1024);
+        shifter.set_pitch_cents(0.01);
+
+        let input: Vec<f32> = (0..48000).map(|i| (i as f32 * 0.02).sin() * 0.5).collect();
+        let mut output = input.clone();
+        for chunk in output.chunks_mut(128) {
+            shifter.process(chunk);
+        }
+
+        let latency = shifter.latency();
+        for i in 10000..12000 {
+            assert!((output[i] - input[i - latency]).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn test_bypass_at_zero_cents() {
+        let mut shifter = PitchShifter::new(48000, 1024);
+        let latency = shifter.latency();
+        assert!(latency > 0);
+
+        // Decaying 2 kHz bursts at irregular spacing, so the envelope only
+        // lines up with itself at one lag
+        let mut input = vec![0.0; 60000];
+        let mut start = 500;
+        for k in 0.. {
+            if start + 600 > input.len() {
+                break;
+            }
+            for (i, sample) in input[start..start + 600].iter_mut().enumerate() {
+                let t = i as f32 / 48000.0;
+                *sample = 0.5 * (-(i as f32) / 120.0).exp() * (2.0 * PI * 2000.0 * t).sin();
+            }
+            start += 1500 + (k * 7919) % 1500;
+        }
+        let mut output = input.clone();
+
+        // Delayed but otherwise untouched at 0 cents
+        for chunk in output[..12000].chunks_mut(128) {
+            shifter.process(chunk);
+        }
+        assert_eq!(output[latency..12000], input[..12000 - latency]);
+
+        // Engaging keeps the shifted stream on the same grid
+        shifter.set_pitch_cents(100.0);
+        assert_eq!(shifter.latency(), latency);
+        for chunk in output[12000..36000].chunks_mut(128) {
+            shifter.process(chunk);
+        }
+
+        // As does disengaging, which lands back on the delayed input
+        shifter.set_pitch_cents(0.0);
+        for chunk in output[36000..].chunks_mut(128) {
+            shifter.process(chunk);
+        }
+        assert_eq!(output[37000..], input[37000 - latency..60000 - latency]);
+
+        let envelope = |signal: &[f32]| -> Vec<f32> {
+            let mut level = 0.0;
+            signal
+                .iter()
+                .map(|s| {
+                    level += (s * s - level) * 0.02;
+                    level
+                })
+                .collect()
+        };
+        let (input_env, output_env) = (envelope(&input), envelope(&output));
+        for (from, to) in [(6000, 12000), (10000, 20000), (20000, 36000), (32000, 44000)] {
+            let lag = (0..2 * latency)
+                .max_by(|&a, &b| {
+                    let score = |lag: usize| -> f32 {
+                        (from..to).map(|i| output_env[i] * input_env[i - lag]).sum()
+                    };
+                    score(a).total_cmp(&score(b))
+                })
+                .unwrap();
+            assert!(
+                (lag as i32 - latency as i32).abs() <= 64,
+                "lag {} over {}..{}, latency {}",
+                lag,
+                from,
+                to,
+                latency
+            );
+        }
+    }
+
+    #[test]
+    fn test_formant_preservation() {
+        // 200 Hz harmonic series with a broad formant at 1 kHz
+        let harmonics: Vec<(f32, f32)> = (1..40)
+            .map(|h| {
+                let freq = 200.0 * h as f32;
+                let detune = (freq - 1000.0) / 400.0;
+                (freq, 0.1 / (1.0 + detune * detune))
+            })
+            .collect();
+        let input: Vec<f32> = (0..96000)
+            .map(|i| {
+                let t = i as f32 / 48000.0;
+                harmonics.iter().map(|&(f, a)| a * (2.0 * PI * f * t).sin()).sum()
+            })
+            .collect();
+
+        // Shifted by a fifth the harmonics sit at multiples of 300 Hz
+        let harmonic_levels = |preserve: bool| -> Vec<f32> {
+            let mut shifter = PitchShifter::new(48000, 2048);
+            shifter.set_pitch_cents(701.955);
+            shifter.set_formant_preservation(preserve);
+            assert_eq!(shifter.get_formant_preservation(), preserve);
+
+            let mut output = input.clone();
+            for chunk in output.chunks_mut(128) {
+                shifter.process(chunk);
+            }
+
+            let steady = &output[48000..96000];
+            (1..8).map(|h| goertzel(steady, 300.0 * h as f32, 48000.0)).collect()
+        };
+
+        // Plain shifting drags the formant up to 1.5 kHz
+        let plain = harmonic_levels(false);
+        assert!(plain[4] > plain[3] && plain[4] > plain[5]);
+
+        // Preserving keeps it between the 900 Hz and 1200 Hz harmonics
+        let preserved = harmonic_levels(true);
+        let formant = preserved[2].max(preserved[3]);
+        assert!(preserved[4] < formant * 0.6);
+        assert!(preserved[1] < formant * 0.7);
+    }
+
+    /// Magnitude of a single frequency component
+    fn goertzel(signal: &[f32], freq: f32, sample_rate: f32) -> f32 {
+        let (re, im) = signal.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, &s)| {
+            let phase = 2.0 * PI * freq * i as f32 / sample_rate;
+            (re + s * phase.cos(), im + s * phase.sin())
+        });
+        (re * re + im * im).sqrt() / signal.len() as f32
+    }
+
+    /// Estimates the frequency of a sine from its rising zero crossings
+    fn estimate_frequency(signal: &[f32], sample_rate: f32) -> f32 {
+        let crossings: Vec<usize> = signal
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| w[0] <= 0.0 && w[1] > 0.0)
+            .map(|(i, _)| i)
+            .collect();
+        let span = (crossings[crossings.len() - 1] - crossings[0]) as f32;
+        sample_rate * (crossings.len() - 1) as f32 / span
+    }
+
     #[test]
     fn test_process_stereo() {
         let mut shifter = PitchShifter::new(48000, 1024);
@@ -186,5 +681,14 @@ mod tests {
         
         assert_eq!(left.len(), 256);
         assert_eq!(right.len(), 256);
+
+        // An octave up in whole-FFT chunks pulls the most stretched audio
+        shifter.set_pitch_cents(1200.0);
+        let mut left = vec![0.1; 4096];
+        let mut right = vec![0.1; 4096];
+        for _ in 0..4 {
+            shifter.process_stereo(&mut left, &mut right);
+        }
+        assert!(left.iter().all(|s| s.is_finite()));
     }
 }
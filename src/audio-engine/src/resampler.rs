@@ -0,0 +1,194 @@
+//! Streaming varispeed resampler
+//!
+//! Plays source audio back at a different rate, changing tempo and pitch
+//! together like a turntable or tape machine.
+//!
+//! Algorithm: 4-point cubic Hermite (Catmull-Rom) interpolation
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(1) per output sample
+//! - Memory: O(block size) per channel
+//! - Latency: none (2 samples of lookahead, covered by `input_required`)
+//!
+//! The resampler implements [`TimeStretcher`] so it can stand in for the
+//! time-stretch stage when key lock is off; the ratio has the same meaning
+//! (input samples consumed per output sample). No anti-aliasing filter is
+//! applied when speeding up, matching the sound of a pitched-up deck.
+
+use crate::time_stretch::TimeStretcher;
+
+/// Samples of history kept behind the read position (for interpolation)
+const HISTORY: usize = 1;
+
+/// Samples of lookahead needed after the read position
+const LOOKAHEAD: usize = 2;
+
+/// Maximum unread input kept per channel; older audio is dropped
+const MAX_BACKLOG: usize = 16384;
+
+/// Streaming stereo varispeed resampler
+pub struct Resampler {
+    ratio: f32,
+    channels: [Vec<f32>; 2],
+    /// Fractional read position in the input FIFOs
+    position: f64,
+}
+
+impl Resampler {
+    /// Creates a new resampler at unity rate
+    pub fn new() -> Self {
+        let mut resampler = Resampler {
+            ratio: 1.0,
+            channels: [Vec::with_capacity(4096), Vec::with_capacity(4096)],
+            position: 0.0,
+        };
+        resampler.reset();
+        resampler
+    }
+
+    /// Cubic Hermite interpolation between `y1` and `y2` at fraction `t`
+    #[inline]
+    fn interpolate(y0: f32, y1: f32, y2: f32, y3: f32, t: f32) -> f32 {
+        let c1 = 0.5 * (y2 - y0);
+        let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+        let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
+        ((c3 * t + c2) * t + c1) * t + y1
+    }
+
+    /// Whether enough input is buffered to interpolate at `position`
+    fn can_read(&self, position: f64) -> bool {
+        (position as usize) + LOOKAHEAD < self.channels[0].len()
+    }
+}
+
+impl Default for Resampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeStretcher for Resampler {
+    fn set_stretch_ratio(&mut self, ratio: f32) {
+        self.ratio = ratio.clamp(0.5, 2.0);
+    }
+
+    fn get_stretch_ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    fn input_required(&self, output_len: usize) -> usize {
+        if output_len == 0 {
+            return 0;
+        }
+
+        let last = self.position + self.ratio as f64 * (output_len - 1) as f64;
+        (last as usize + LOOKAHEAD + 1).saturating_sub(self.channels[0].len())
+    }
+
+    fn push_input(&mut self, left: &[f32], right: &[f32]) {
+        let len = left.len().min(right.len());
+        for (channel, input) in self.channels.iter_mut().zip([left, right]) {
+            channel.extend_from_slice(&input[..len]);
+        }
+
+        // Skip ahead rather than grow without bound if the host over-feeds
+        let backlog = self.channels[0].len().saturating_sub(self.position as usize);
+        if backlog > MAX_BACKLOG {
+            self.position += (backlog - MAX_BACKLOG) as f64;
+        }
+    }
+
+    fn pull_output(&mut self, left: &mut [f32], right: &mut [f32]) -> usize {
+        let requested = left.len().min(right.len());
+        let mut written = 0;
+
+        while written < requested && self.can_read(self.position) {
+            let index = self.position as usize;
+            let t = (self.position - index as f64) as f32;
+
+            for (channel, output) in self.channels.iter().zip([&mut *left, &mut *right]) {
+                output[written] = Self::interpolate(
+                    channel[index - HISTORY],
+                    channel[index],
+                    channel[index + 1],
+                    channel[index + 2],
+                    t,
+                );
+            }
+
+            self.position += self.ratio as f64;
+            written += 1;
+        }
+
+        left[written..requested].fill(0.0);
+        right[written..requested].fill(0.0);
+
+        // Drop input behind the interpolation history
+        let consumed = (self.position as usize - HISTORY).min(self.channels[0].len());
+        for channel in self.channels.iter_mut() {
+            channel.drain(..consumed);
+        }
+        self.position -= consumed as f64;
+
+        written
+    }
+
+    fn latency(&self) -> usize {
+        0
+    }
+
+    fn reset(&mut self) {
+        for channel in self.channels.iter_mut() {
+            channel.clear();
+            channel.resize(HISTORY, 0.0);
+        }
+        self.position = HISTORY as f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unity_ratio_passes_through() {
+        let mut resampler = Resampler::new();
+        let input: Vec<f32> = (0..512).map(|i| (i as f32 * 0.1).sin()).collect();
+        let mut left = [0.0; 128];
+        let mut right = [0.0; 128];
+        let mut consumed = 0;
+
+        for block in 0..3 {
+            let needed = resampler.input_required(128);
+            resampler.push_input(&input[consumed..consumed + needed], &input[consumed..consumed + needed]);
+            consumed += needed;
+            assert_eq!(resampler.pull_output(&mut left, &mut right), 128);
+
+            for i in 0..128 {
+                assert!((left[i] - input[block * 128 + i]).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_varispeed_changes_pitch() {
+        let mut resampler = Resampler::new();
+        resampler.set_stretch_ratio(1.5);
+
+        // Sine with a period of 100 samples
+        let input: Vec<f32> = (0..2000)
+            .map(|i| (2.0 * std::f32::consts::PI * i as f32 / 100.0).sin())
+            .collect();
+        let needed = resampler.input_required(1000);
+        assert_eq!(needed, 1501);
+        resampler.push_input(&input[..needed], &input[..needed]);
+
+        let mut left = vec![0.0; 1000];
+        let mut right = vec![0.0; 1000];
+        assert_eq!(resampler.pull_output(&mut left, &mut right), 1000);
+
+        // Period shrinks to 100 / 1.5 samples
+        let expected = (2.0 * std::f32::consts::PI * 1.5 * 500.0 / 100.0).sin();
+        assert!((left[500] - expected).abs() < 1e-3);
+    }
+}
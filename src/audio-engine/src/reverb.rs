@@ -0,0 +1,402 @@
+//! Algorithmic reverb with freeze
+//!
+//! Algorithm: Freeverb (Schroeder/Moorer) — per channel, eight parallel
+//! low-pass-feedback comb filters into four series allpass diffusers. The
+//! right channel's delays are offset slightly for stereo decorrelation.
+//! Reference: Jezar at Dreampoint, "Freeverb" (public domain, 2000)
+//!
+//! Freeze sets the comb feedback to 1 with no damping and stops new input,
+//! so whatever is in the tank rings on indefinitely — the classic breakdown
+//! build.
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(1) per sample (16 combs + 8 allpasses)
+//! - Memory: ~140KB at 48kHz
+//! - Latency: none (dry signal passes straight through)
+
+use crate::delay_line::DelayLine;
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+
+/// Comb delays in samples at 44.1kHz
+const COMB_TUNING: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+
+/// Allpass delays in samples at 44.1kHz
+const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+
+/// Extra delay of the right channel in samples at 44.1kHz
+const STEREO_SPREAD: usize = 23;
+
+/// Input attenuation keeping the sum of eight resonant combs in range
+const INPUT_GAIN: f32 = 0.015;
+
+/// Longest pre-delay in milliseconds
+const MAX_PRE_DELAY_MS: f32 = 200.0;
+
+/// Low-pass-feedback comb filter
+struct Comb {
+    buffer: Vec<f32>,
+    pos: usize,
+    store: f32,
+}
+
+impl Comb {
+    fn new(len: usize) -> Self {
+        Comb {
+            buffer: vec![0.0; len.max(1)],
+            pos: 0,
+            store: 0.0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.pos];
+        self.store = output + (self.store - output) * damping;
+        self.buffer[self.pos] = input + self.store * feedback;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        output
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.store = 0.0;
+    }
+}
+
+/// Schroeder allpass diffuser with fixed 0.5 feedback
+struct Allpass {
+    buffer: Vec<f32>,
+    pos: usize,
+}
+
+impl Allpass {
+    fn new(len: usize) -> Self {
+        Allpass {
+            buffer: vec![0.0; len.max(1)],
+            pos: 0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.pos];
+        self.buffer[self.pos] = input + delayed * 0.5;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        delayed - input
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+    }
+}
+
+/// One channel of the reverb tank
+struct Tank {
+    combs: Vec<Comb>,
+    allpasses: Vec<Allpass>,
+}
+
+impl Tank {
+    fn new(sample_rate: u32, spread: usize) -> Self {
+        let scale = |len: usize| ((len + spread) as f32 * sample_rate as f32 / 44100.0) as usize;
+        Tank {
+            combs: COMB_TUNING.iter().map(|&len| Comb::new(scale(len))).collect(),
+            allpasses: ALLPASS_TUNING.iter().map(|&len| Allpass::new(scale(len))).collect(),
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let mut output: f32 = self.combs.iter_mut().map(|comb| comb.process(input, feedback, damping)).sum();
+        for allpass in self.allpasses.iter_mut() {
+            output = allpass.process(output);
+        }
+        output
+    }
+
+    fn reset(&mut self) {
+        self.combs.iter_mut().for_each(Comb::reset);
+        self.allpasses.iter_mut().for_each(Allpass::reset);
+    }
+}
+
+/// Stereo Freeverb-style reverb
+///
+/// Starts at size 0.5, damping 0.5, no pre-delay, full width with the mix
+/// at 0 (bypass).
+///
+/// # Performance
+/// - Per-sample complexity: O(1)
+/// - Memory: ~140KB per instance at 48kHz
+pub struct Reverb {
+    sample_rate: u32,
+    size: f32,
+    damping: f32,
+    pre_delay_ms: f32,
+    width: f32,
+    frozen: bool,
+
+    mix: SmoothedParam,
+    pre_delay: DelayLine,
+    tanks: [Tank; 2],
+    // Tank holds only silence (skips clearing it again while bypassed)
+    silent: bool,
+}
+
+impl Reverb {
+    /// Creates a bypassed reverb
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn new(sample_rate: u32) -> Self {
+        let max_pre_delay = (MAX_PRE_DELAY_MS * 0.001 * sample_rate as f32) as usize + 1;
+        Reverb {
+            sample_rate,
+            size: 0.5,
+            damping: 0.5,
+            pre_delay_ms: 0.0,
+            width: 1.0,
+            frozen: false,
+            mix: SmoothedParam::new(0.0, sample_rate, DEFAULT_SMOOTHING_MS),
+            pre_delay: DelayLine::new(max_pre_delay),
+            tanks: [Tank::new(sample_rate, 0), Tank::new(sample_rate, STEREO_SPREAD)],
+            silent: true,
+        }
+    }
+
+    /// Sets the room size
+    ///
+    /// # Arguments
+    /// * `size` - Room size (0.0 = small room to 1.0 = hall, ~4s decay)
+    pub fn set_size(&mut self, size: f32) {
+        self.size = size.clamp(0.0, 1.0);
+    }
+
+    /// Gets the room size
+    pub fn get_size(&self) -> f32 {
+        self.size
+    }
+
+    /// Sets the high-frequency damping
+    ///
+    /// # Arguments
+    /// * `damping` - Damping (0.0 = bright to 1.0 = dark)
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping.clamp(0.0, 1.0);
+    }
+
+    /// Gets the damping
+    pub fn get_damping(&self) -> f32 {
+        self.damping
+    }
+
+    /// Sets the gap between the dry sound and the reverb
+    ///
+    /// # Arguments
+    /// * `time_ms` - Pre-delay in milliseconds (0-200)
+    pub fn set_pre_delay(&mut self, time_ms: f32) {
+        self.pre_delay_ms = time_ms.clamp(0.0, MAX_PRE_DELAY_MS);
+    }
+
+    /// Gets the pre-delay in milliseconds
+    pub fn get_pre_delay(&self) -> f32 {
+        self.pre_delay_ms
+    }
+
+    /// Sets the stereo width of the reverb
+    ///
+    /// # Arguments
+    /// * `width` - Width (0.0 = mono to 1.0 = full stereo)
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width.clamp(0.0, 1.0);
+    }
+
+    /// Gets the stereo width
+    pub fn get_width(&self) -> f32 {
+        self.width
+    }
+
+    /// Sets the dry/wet mix
+    ///
+    /// Same law as the echo: dry stays at full level up to the middle of the
+    /// knob, where the reverb reaches full level.
+    ///
+    /// # Arguments
+    /// * `mix` - Dry/wet mix (0.0 = dry only (bypass), 1.0 = reverb only)
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix.set_target(mix.clamp(0.0, 1.0));
+    }
+
+    /// Gets the dry/wet mix
+    pub fn get_mix(&self) -> f32 {
+        self.mix.target()
+    }
+
+    /// Freezes or releases the reverb tail
+    ///
+    /// While frozen, new input is not added and the current tail sustains
+    /// without decaying.
+    pub fn set_freeze(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    /// Gets whether the tail is frozen
+    pub fn get_freeze(&self) -> bool {
+        self.frozen
+    }
+
+    /// Sets how long mix changes take to settle
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.mix.set_smoothing_time(time_ms);
+    }
+
+    /// Processes a stereo pair in place
+    ///
+    /// # Arguments
+    /// * `left` - Left channel samples (modified in-place)
+    /// * `right` - Right channel samples (modified in-place)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if self.mix.target() == 0.0 && !self.mix.is_smoothing() {
+            if !self.silent {
+                self.reset();
+            }
+            return;
+        }
+        self.silent = false;
+
+        let (feedback, damping, input_gain) = if self.frozen {
+            (1.0, 0.0, 0.0)
+        } else {
+            (0.7 + 0.28 * self.size, 0.4 * self.damping, INPUT_GAIN)
+        };
+        let pre_delay = self.pre_delay_ms * 0.001 * self.sample_rate as f32;
+        let direct = (1.0 + self.width) * 0.5;
+        let cross = (1.0 - self.width) * 0.5;
+
+        let [left_tank, right_tank] = &mut self.tanks;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            self.pre_delay.write((*l + *r) * input_gain);
+            let input = self.pre_delay.read(pre_delay + 1.0);
+
+            let reverb_left = left_tank.process(input, feedback, damping);
+            let reverb_right = right_tank.process(input, feedback, damping);
+
+            let mix = self.mix.next_value();
+            let dry = (2.0 - 2.0 * mix).min(1.0);
+            let wet = (2.0 * mix).min(1.0);
+            *l = *l * dry + (reverb_left * direct + reverb_right * cross) * wet;
+            *r = *r * dry + (reverb_right * direct + reverb_left * cross) * wet;
+        }
+    }
+
+    /// Clears the reverb tail
+    pub fn reset(&mut self) {
+        self.pre_delay.reset();
+        for tank in self.tanks.iter_mut() {
+            tank.reset();
+        }
+        self.silent = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn energy(samples: &[f32]) -> f32 {
+        samples.iter().map(|s| s * s).sum()
+    }
+
+    /// Feeds a short noise burst, then returns `len` samples of tail
+    fn tail(reverb: &mut Reverb, len: usize) -> (Vec<f32>, Vec<f32>) {
+        let mut seed = 1u32;
+        let mut left: Vec<f32> = (0..len)
+            .map(|i| {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                if i < 4800 { (seed >> 8) as f32 / (1 << 23) as f32 - 1.0 } else { 0.0 }
+            })
+            .collect();
+        let mut right = left.clone();
+        reverb.process_stereo(&mut left, &mut right);
+        (left, right)
+    }
+
+    #[test]
+    fn test_zero_mix_is_bypass() {
+        let mut reverb = Reverb::new(48000);
+        let input: Vec<f32> = (0..512).map(|i| (i as f32 * 0.3).sin()).collect();
+        let mut left = input.clone();
+        let mut right = input.clone();
+        reverb.process_stereo(&mut left, &mut right);
+        assert_eq!(left, input);
+    }
+
+    #[test]
+    fn test_tail_decays_with_size() {
+        let mut decays = Vec::new();
+        for &size in &[0.2, 0.9] {
+            let mut reverb = Reverb::new(48000);
+            reverb.set_smoothing_time(0.0);
+            reverb.set_mix(1.0);
+            reverb.set_size(size);
+            let (left, _) = tail(&mut reverb, 96000);
+            let early = energy(&left[9600..19200]);
+            let late = energy(&left[86400..96000]);
+            assert!(early > 0.0 && late < early);
+            decays.push(late / early);
+        }
+        // A bigger room rings for longer
+        assert!(decays[1] > decays[0] * 10.0, "{:?}", decays);
+    }
+
+    #[test]
+    fn test_freeze_sustains() {
+        let mut reverb = Reverb::new(48000);
+        reverb.set_smoothing_time(0.0);
+        reverb.set_mix(1.0);
+        reverb.set_damping(0.0);
+        tail(&mut reverb, 9600);
+
+        reverb.set_freeze(true);
+        let mut left = vec![0.0; 48000];
+        let mut right = vec![0.0; 48000];
+        reverb.process_stereo(&mut left, &mut right);
+        let first = energy(&left[..9600]);
+        let last = energy(&left[38400..]);
+        assert!(first > 0.0);
+        assert!((last / first - 1.0).abs() < 0.1, "{} {}", first, last);
+
+        // Input is not added while frozen
+        let mut loud = vec![1.0; 4800];
+        let mut loud_right = loud.clone();
+        reverb.process_stereo(&mut loud, &mut loud_right);
+        let mut after = vec![0.0; 9600];
+        let mut after_right = vec![0.0; 9600];
+        reverb.process_stereo(&mut after, &mut after_right);
+        assert!((energy(&after) / first - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_pre_delay_and_width() {
+        let mut reverb = Reverb::new(48000);
+        reverb.set_smoothing_time(0.0);
+        reverb.set_mix(1.0);
+        reverb.set_pre_delay(100.0);
+        reverb.set_width(0.0);
+
+        let mut left = vec![0.0; 9600];
+        let mut right = vec![0.0; 9600];
+        left[0] = 1.0;
+        right[0] = 1.0;
+        reverb.process_stereo(&mut left, &mut right);
+
+        // Nothing before the 4800-sample pre-delay plus the shortest comb
+        let first = left.iter().position(|&s| s != 0.0).unwrap();
+        assert!(first >= 4800 + 1000, "{}", first);
+        // Zero width is mono
+        assert_eq!(left, right);
+
+        reverb.set_pre_delay(500.0);
+        assert_eq!(reverb.get_pre_delay(), MAX_PRE_DELAY_MS);
+    }
+}
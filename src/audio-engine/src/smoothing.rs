@@ -0,0 +1,263 @@
+//! Parameter smoothing (de-zippering)
+//!
+//! Control changes arrive from the UI once per block. Applying them as a
+//! step produces an audible click, so gains and other continuous controls
+//! glide to their new value over a short time instead.
+//!
+//! Two shapes are available:
+//! - [`SmoothingMode::Linear`]: constant-rate ramp that reaches the target
+//!   in exactly the smoothing time (bounded per-sample step)
+//! - [`SmoothingMode::OnePole`]: exponential approach with the smoothing time
+//!   as time constant (fast start, soft landing)
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(1) per sample (one add or one multiply-add)
+//! - Memory: ~40 bytes per parameter
+
+/// Default smoothing time in milliseconds
+pub const DEFAULT_SMOOTHING_MS: f32 = 20.0;
+
+/// Distance to the target below which a one-pole glide snaps to it
+const SETTLE_THRESHOLD: f32 = 1e-5;
+
+/// Shape of the glide towards a new target
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmoothingMode {
+    /// Straight line reaching the target after the smoothing time
+    Linear,
+    /// One-pole lowpass with the smoothing time as time constant
+    OnePole,
+}
+
+/// A control value that glides to its target one sample at a time
+#[derive(Clone, Debug)]
+pub struct SmoothedParam {
+    current: f32,
+    target: f32,
+    mode: SmoothingMode,
+    sample_rate: u32,
+    time_ms: f32,
+
+    // Linear ramp state
+    step: f32,
+    remaining: u32,
+
+    // One-pole coefficient (fraction of the distance covered per sample)
+    coefficient: f32,
+}
+
+impl SmoothedParam {
+    /// Creates a linearly smoothed parameter resting at `value`
+    ///
+    /// # Arguments
+    /// * `value` - Initial value
+    /// * `sample_rate` - Sample rate in Hz
+    /// * `time_ms` - Smoothing time in milliseconds (0 = no smoothing)
+    pub fn new(value: f32, sample_rate: u32, time_ms: f32) -> Self {
+        let mut param = SmoothedParam {
+            current: value,
+            target: value,
+            mode: SmoothingMode::Linear,
+            sample_rate,
+            time_ms: 0.0,
+            step: 0.0,
+            remaining: 0,
+            coefficient: 1.0,
+        };
+        param.set_smoothing_time(time_ms);
+        param
+    }
+
+    /// Sets the glide shape; an ongoing glide restarts from the current value
+    pub fn set_mode(&mut self, mode: SmoothingMode) {
+        self.mode = mode;
+        self.set_target(self.target);
+    }
+
+    /// Gets the glide shape
+    pub fn get_mode(&self) -> SmoothingMode {
+        self.mode
+    }
+
+    /// Sets the smoothing time in milliseconds (clamped to 0-1000)
+    pub fn set_smoothing_time(&mut self, time_ms: f32) {
+        self.time_ms = time_ms.clamp(0.0, 1000.0);
+        let samples = self.time_samples();
+        self.coefficient = if samples > 0.0 {
+            1.0 - (-1.0 / samples).exp()
+        } else {
+            1.0
+        };
+        self.set_target(self.target);
+    }
+
+    /// Gets the smoothing time in milliseconds
+    pub fn get_smoothing_time(&self) -> f32 {
+        self.time_ms
+    }
+
+    /// Starts gliding from the current value towards `value`
+    pub fn set_target(&mut self, value: f32) {
+        self.target = value;
+        let ramp = self.time_samples().round() as u32;
+
+        if ramp == 0 || self.current == value {
+            self.set_immediate(value);
+            return;
+        }
+
+        self.remaining = ramp;
+        self.step = (value - self.current) / ramp as f32;
+    }
+
+    /// Jumps to `value` without smoothing
+    pub fn set_immediate(&mut self, value: f32) {
+        self.current = value;
+        self.target = value;
+        self.remaining = 0;
+        self.step = 0.0;
+    }
+
+    /// Gets the value being glided towards
+    #[inline]
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Gets the value of the most recent sample
+    #[inline]
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// Whether the value is still moving
+    #[inline]
+    pub fn is_smoothing(&self) -> bool {
+        self.current != self.target
+    }
+
+    /// Advances one sample and returns the new value
+    #[inline]
+    pub fn next_value(&mut self) -> f32 {
+        if self.current != self.target {
+            match self.mode {
+                SmoothingMode::Linear => {
+                    self.remaining = self.remaining.saturating_sub(1);
+                    self.current = if self.remaining == 0 {
+                        self.target
+                    } else {
+                        self.current + self.step
+                    };
+                }
+                SmoothingMode::OnePole => {
+                    self.current += (self.target - self.current) * self.coefficient;
+                    if (self.target - self.current).abs() < SETTLE_THRESHOLD {
+                        self.current = self.target;
+                    }
+                }
+            }
+        }
+        self.current
+    }
+
+    /// Advances `samples` samples at once and returns the new value
+    pub fn skip(&mut self, samples: usize) -> f32 {
+        if samples == 0 || self.current == self.target {
+            return self.current;
+        }
+
+        match self.mode {
+            SmoothingMode::Linear => {
+                if samples >= self.remaining as usize {
+                    self.set_immediate(self.target);
+                } else {
+                    self.remaining -= samples as u32;
+                    self.current += self.step * samples as f32;
+                }
+            }
+            SmoothingMode::OnePole => {
+                let decay = (1.0 - self.coefficient).powi(samples as i32);
+                self.current = self.target + (self.current - self.target) * decay;
+                if (self.target - self.current).abs() < SETTLE_THRESHOLD {
+                    self.current = self.target;
+                }
+            }
+        }
+        self.current
+    }
+
+    fn time_samples(&self) -> f32 {
+        self.time_ms * 0.001 * self.sample_rate as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Largest change between consecutive samples over `samples` steps
+    fn max_step(param: &mut SmoothedParam, samples: usize) -> f32 {
+        let mut previous = param.current();
+        let mut max: f32 = 0.0;
+        for _ in 0..samples {
+            let value = param.next_value();
+            max = max.max((value - previous).abs());
+            previous = value;
+        }
+        max
+    }
+
+    #[test]
+    fn test_linear_ramp() {
+        // 20 ms at 48 kHz = 960 samples
+        let mut param = SmoothedParam::new(0.0, 48000, 20.0);
+        param.set_target(1.0);
+        assert!(param.is_smoothing());
+
+        let step = max_step(&mut param, 959);
+        assert!((step - 1.0 / 960.0).abs() < 1e-6);
+        assert!(param.current() < 1.0);
+        assert_eq!(param.next_value(), 1.0);
+        assert!(!param.is_smoothing());
+    }
+
+    #[test]
+    fn test_one_pole_ramp() {
+        let mut param = SmoothedParam::new(1.0, 48000, 10.0);
+        param.set_mode(SmoothingMode::OnePole);
+        param.set_target(0.0);
+
+        // First step is the largest: 1 - e^(-1/480)
+        let step = max_step(&mut param, 480);
+        assert!((step - (1.0 - (-1.0f32 / 480.0).exp())).abs() < 1e-6);
+        // One time constant covers ~63% of the distance
+        assert!((param.current() - (-1.0f32).exp()).abs() < 1e-3);
+
+        max_step(&mut param, 48000);
+        assert_eq!(param.current(), 0.0);
+    }
+
+    #[test]
+    fn test_skip_matches_next() {
+        for &mode in &[SmoothingMode::Linear, SmoothingMode::OnePole] {
+            let mut stepped = SmoothedParam::new(0.2, 48000, 5.0);
+            stepped.set_mode(mode);
+            let mut skipped = stepped.clone();
+            stepped.set_target(0.9);
+            skipped.set_target(0.9);
+
+            for _ in 0..100 {
+                stepped.next_value();
+            }
+            assert!((skipped.skip(100) - stepped.current()).abs() < 1e-5, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_zero_time_is_immediate() {
+        let mut param = SmoothedParam::new(0.0, 48000, 0.0);
+        param.set_target(0.5);
+        assert!(!param.is_smoothing());
+        assert_eq!(param.next_value(), 0.5);
+    }
+}
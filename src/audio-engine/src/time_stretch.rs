@@ -0,0 +1,140 @@
+//! Shared interface for streaming time-stretch engines
+//!
+//! Every engine consumes stereo source audio and produces output at a rate
+//! set by its stretch ratio, keeping its state between calls:
+//!
+//! ```text
+//! needed = stretcher.input_required(128);   // source frames for the next block
+//! stretcher.push_input(&left[..needed], &right[..needed]);
+//! stretcher.pull_output(&mut out_left, &mut out_right);
+//! ```
+//!
+//! Engines:
+//! - [`PhaseVocoder`](crate::phase_vocoder::PhaseVocoder): FFT-based, best quality
+//! - [`Wsola`](crate::wsola::Wsola): time-domain overlap-add, low CPU
+
+use wasm_bindgen::prelude::*;
+
+/// Selects which time-stretch engine a processor runs
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeStretchEngine {
+    /// FFT phase vocoder (highest quality, highest CPU)
+    PhaseVocoder = 0,
+    /// Waveform-similarity overlap-add (low CPU)
+    Wsola = 1,
+}
+
+/// Streaming stereo time-stretcher
+///
+/// The stretch ratio has the same meaning for every engine: it is the number
+/// of input samples consumed per output sample (0.5 = half speed,
+/// 2.0 = double speed), clamped to 0.5-2.0.
+pub trait TimeStretcher {
+    /// Sets the stretch ratio (clamped to 0.5-2.0)
+    fn set_stretch_ratio(&mut self, ratio: f32);
+
+    /// Gets the current stretch ratio
+    fn get_stretch_ratio(&self) -> f32;
+
+    /// Returns how many more input samples (per channel) must be pushed
+    /// before `output_len` samples can be pulled at the current ratio
+    fn input_required(&self, output_len: usize) -> usize;
+
+    /// Appends stereo input to the stream
+    fn push_input(&mut self, left: &[f32], right: &[f32]);
+
+    /// Fills the buffers with stretched output
+    ///
+    /// Returns the number of samples written per channel; the rest of the
+    /// buffers is silenced if too little input was pushed.
+    fn pull_output(&mut self, left: &mut [f32], right: &mut [f32]) -> usize;
+
+    /// Latency of the stream in samples at unity ratio
+    fn latency(&self) -> usize;
+
+    /// Clears all buffered audio and analysis state
+    fn reset(&mut self);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phase_vocoder::PhaseVocoder;
+    use crate::wsola::Wsola;
+    use std::f32::consts::PI;
+
+    fn engines() -> Vec<Box<dyn TimeStretcher>> {
+        vec![
+            Box::new(PhaseVocoder::new(48000, 1024)),
+            Box::new(Wsola::new(48000)),
+        ]
+    }
+
+    fn sine(freq: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * PI * freq * i as f32 / 48000.0).sin() * 0.5)
+            .collect()
+    }
+
+    /// Streams `input` through an engine in 128-sample blocks
+    fn stream(stretcher: &mut dyn TimeStretcher, input: &[f32], blocks: usize) -> (Vec<f32>, usize) {
+        let mut output = Vec::new();
+        let mut consumed = 0;
+        let mut left = [0.0; 128];
+        let mut right = [0.0; 128];
+
+        for _ in 0..blocks {
+            let needed = stretcher.input_required(128);
+            let chunk = &input[consumed..consumed + needed];
+            stretcher.push_input(chunk, chunk);
+            consumed += needed;
+            assert_eq!(stretcher.pull_output(&mut left, &mut right), 128);
+            output.extend_from_slice(&left);
+        }
+
+        (output, consumed)
+    }
+
+    #[test]
+    fn test_engines_share_ratio_semantics() {
+        let input = sine(440.0, 96000);
+
+        for mut engine in engines() {
+            for &ratio in &[0.5, 1.0, 2.0] {
+                engine.reset();
+                engine.set_stretch_ratio(ratio);
+                let (_, consumed) = stream(engine.as_mut(), &input, 150);
+
+                let expected = 150.0 * 128.0 * ratio;
+                assert!((consumed as f32 - expected).abs() <= 2048.0);
+            }
+
+            engine.set_stretch_ratio(5.0);
+            assert_eq!(engine.get_stretch_ratio(), 2.0);
+        }
+    }
+
+    #[test]
+    fn test_engines_preserve_pitch() {
+        let input = sine(440.0, 96000);
+
+        for mut engine in engines() {
+            for &ratio in &[0.5, 2.0] {
+                engine.reset();
+                engine.set_stretch_ratio(ratio);
+                let (output, _) = stream(engine.as_mut(), &input, 150);
+
+                let crossings: Vec<usize> = output[4096..]
+                    .windows(2)
+                    .enumerate()
+                    .filter(|(_, w)| w[0] <= 0.0 && w[1] > 0.0)
+                    .map(|(i, _)| i)
+                    .collect();
+                let span = (crossings[crossings.len() - 1] - crossings[0]) as f32;
+                let freq = 48000.0 * (crossings.len() - 1) as f32 / span;
+                assert!((freq - 440.0).abs() < 440.0 * 0.02, "ratio {}: {} Hz", ratio, freq);
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! WSOLA time-stretching
+//!
+//! Waveform Similarity Overlap-Add: a time-domain alternative to the phase
+//! vocoder for low-end machines running several decks.
+//!
+//! Algorithm: WSOLA
+//! Reference: Verhelst & Roelands, "An overlap-add technique based on
+//! waveform similarity (WSOLA) for high quality time-scale modification of
+//! speech" (ICASSP 1993)
+//!
+//! # Performance Characteristics
+//! - Time complexity: O(n) per sample (no FFT)
+//! - Memory: O(frame_size) per channel
+//! - Typical latency: 10ms (half a frame)
+//!
+//! # Theory
+//! Output is built from 20ms Hann-windowed grains at 50% overlap. Each grain
+//! is nominally taken `hop * stretch_ratio` further into the input, but its
+//! start is shifted within ±5ms to the position whose waveform best matches
+//! the natural continuation of the previous grain, so overlapping grains add
+//! up in phase and pitch is preserved.
+
+use crate::time_stretch::TimeStretcher;
+
+/// Grain length in milliseconds
+const FRAME_MS: f32 = 20.0;
+
+/// Search tolerance around the nominal grain position in milliseconds
+const SEARCH_MS: f32 = 5.0;
+
+/// Step of the coarse similarity search (refined to single samples afterwards)
+const COARSE_STEP: usize = 4;
+
+/// Maximum unread input kept per channel, in frames; older audio is dropped
+const MAX_BACKLOG_FRAMES: usize = 32;
+
+/// Low-CPU streaming time-stretcher
+///
+/// Shares the stretch ratio semantics and streaming API of the phase
+/// vocoder through [`TimeStretcher`].
+pub struct Wsola {
+    stretch_ratio: f32,
+
+    frame_size: usize,
+    hop_size: usize,
+    search_radius: usize,
+    window: Vec<f32>,
+
+    channels: [WsolaChannel; 2],
+    /// Nominal (unadjusted) start of the next grain in the input FIFO
+    nominal_pos: f64,
+    /// Natural continuation of the previous grain (its start plus one hop)
+    continuation: Option<usize>,
+}
+
+/// Per-channel WSOLA state
+struct WsolaChannel {
+    input: Vec<f32>,
+    overlap: Vec<f32>,
+    output: Vec<f32>,
+}
+
+impl Wsola {
+    /// Creates a new WSOLA stretcher
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz (sets grain and search sizes)
+    pub fn new(sample_rate: u32) -> Self {
+        let hop_size = ((sample_rate as f32 * FRAME_MS / 2000.0) as usize).max(COARSE_STEP);
+        let frame_size = hop_size * 2;
+        let search_radius =
+            ((sample_rate as f32 * SEARCH_MS / 1000.0) as usize / COARSE_STEP).max(1) * COARSE_STEP;
+
+        // Periodic Hann window sums to exactly 1 at 50% overlap
+        let window = (0..frame_size)
+            .map(|n| {
+                let phase = std::f32::consts::PI * n as f32 / frame_size as f32;
+                phase.sin() * phase.sin()
+            })
+            .collect();
+
+        let mut wsola = Wsola {
+            stretch_ratio: 1.0,
+            frame_size,
+            hop_size,
+            search_radius,
+            window,
+            channels: [
+                WsolaChannel::new(frame_size),
+                WsolaChannel::new(frame_size),
+            ],
+            nominal_pos: 0.0,
+            continuation: None,
+        };
+        wsola.reset();
+        wsola
+    }
+
+    /// Gets the grain length in samples
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+
+    /// Synthesizes one grain and appends `hop_size` samples to the output
+    fn process_frame(&mut self) {
+        let nominal = self.nominal_pos.round() as usize;
+        let start = match self.continuation {
+            Some(template) => self.best_frame_start(template, nominal),
+            None => nominal,
+        };
+
+        for channel in self.channels.iter_mut() {
+            let grain = &channel.input[start..start + self.frame_size];
+            for ((acc, &sample), &w) in channel.overlap.iter_mut().zip(grain).zip(&self.window) {
+                *acc += sample * w;
+            }
+
+            channel.output.extend_from_slice(&channel.overlap[..self.hop_size]);
+            channel.overlap.copy_within(self.hop_size.., 0);
+            channel.overlap[self.hop_size..].fill(0.0);
+        }
+
+        self.continuation = Some(start + self.hop_size);
+        self.nominal_pos += self.hop_size as f64 * self.stretch_ratio as f64;
+    }
+
+    /// Finds the grain start within ±search_radius of `nominal` whose first
+    /// half best matches the natural continuation at `template`
+    fn best_frame_start(&self, template: usize, nominal: usize) -> usize {
+        let low = nominal - self.search_radius;
+        let high = nominal + self.search_radius;
+
+        // Coarse pass on a sample-decimated mono mix
+        let mut best = nominal;
+        let mut best_score = self.similarity(template, nominal, 2);
+        for candidate in (low..=high).step_by(COARSE_STEP) {
+            let score = self.similarity(template, candidate, 2);
+            if score > best_score {
+                best = candidate;
+                best_score = score;
+            }
+        }
+
+        // Refine around the coarse winner at full resolution
+        let coarse = best;
+        best_score = self.similarity(template, coarse, 1);
+        let refine_low = coarse.saturating_sub(COARSE_STEP - 1).max(low);
+        let refine_high = (coarse + COARSE_STEP - 1).min(high);
+        for candidate in refine_low..=refine_high {
+            let score = self.similarity(template, candidate, 1);
+            if score > best_score {
+                best = candidate;
+                best_score = score;
+            }
+        }
+
+        best
+    }
+
+    /// Normalized cross-correlation between the mono mix at `template` and
+    /// at `candidate` over one hop, sampled every `stride` samples
+    fn similarity(&self, template: usize, candidate: usize, stride: usize) -> f32 {
+        let left = &self.channels[0].input;
+        let right = &self.channels[1].input;
+        let mut dot = 0.0;
+        let mut energy = 0.0;
+
+        for i in (0..self.hop_size).step_by(stride) {
+            let t = left[template + i] + right[template + i];
+            let c = left[candidate + i] + right[candidate + i];
+            dot += t * c;
+            energy += c * c;
+        }
+
+        if energy > 1e-12 {
+            dot / energy.sqrt()
+        } else {
+            0.0
+        }
+    }
+
+    /// Drops `count` output samples handed to the caller, along with input
+    /// that neither the next template nor the next search window can reach
+    fn consume(&mut self, count: usize) {
+        let search_start = (self.nominal_pos.round() as usize).saturating_sub(self.search_radius);
+        let keep_from = match self.continuation {
+            Some(template) => search_start.min(template),
+            None => search_start,
+        };
+
+        for channel in self.channels.iter_mut() {
+            channel.output.drain(..count);
+            channel.input.drain(..keep_from);
+        }
+
+        self.nominal_pos -= keep_from as f64;
+        self.continuation = self.continuation.map(|template| template - keep_from);
+    }
+
+    /// Input samples needed before the next grain can be synthesized
+    fn frame_end(&self, nominal: f64) -> usize {
+        nominal.round() as usize + self.search_radius + self.frame_size
+    }
+}
+
+impl WsolaChannel {
+    fn new(frame_size: usize) -> Self {
+        WsolaChannel {
+            input: Vec::with_capacity(frame_size * 8),
+            overlap: vec![0.0; frame_size],
+            output: Vec::with_capacity(frame_size * 4),
+        }
+    }
+}
+
+impl TimeStretcher for Wsola {
+    fn set_stretch_ratio(&mut self, ratio: f32) {
+        self.stretch_ratio = ratio.clamp(0.5, 2.0);
+    }
+
+    fn get_stretch_ratio(&self) -> f32 {
+        self.stretch_ratio
+    }
+
+    fn input_required(&self, output_len: usize) -> usize {
+        let ready = self.channels[0].output.len();
+        if output_len <= ready {
+            return 0;
+        }
+
+        let frames = (output_len - ready).div_ceil(self.hop_size);
+        let hop = self.hop_size as f64 * self.stretch_ratio as f64;
+        let last_nominal = self.nominal_pos + hop * (frames - 1) as f64;
+
+        self.frame_end(last_nominal)
+            .saturating_sub(self.channels[0].input.len())
+    }
+
+    fn push_input(&mut self, left: &[f32], right: &[f32]) {
+        let len = left.len().min(right.len());
+        for (channel, input) in self.channels.iter_mut().zip([left, right]) {
+            channel.input.extend_from_slice(&input[..len]);
+        }
+
+        // Skip ahead rather than grow without bound if the host over-feeds
+        let max_backlog = self.frame_size * MAX_BACKLOG_FRAMES;
+        let backlog = self.channels[0]
+            .input
+            .len()
+            .saturating_sub(self.frame_end(self.nominal_pos));
+        if backlog > max_backlog {
+            self.nominal_pos += (backlog - max_backlog) as f64;
+            self.continuation = None;
+        }
+    }
+
+    fn pull_output(&mut self, left: &mut [f32], right: &mut [f32]) -> usize {
+        let requested = left.len().min(right.len());
+
+        while self.channels[0].output.len() < requested
+            && self.frame_end(self.nominal_pos) <= self.channels[0].input.len()
+        {
+            self.process_frame();
+        }
+
+        let written = requested.min(self.channels[0].output.len());
+        for (channel, output) in self.channels.iter().zip([left, right]) {
+            output[..written].copy_from_slice(&channel.output[..written]);
+            output[written..].fill(0.0);
+        }
+        self.consume(written);
+
+        written
+    }
+
+    fn latency(&self) -> usize {
+        self.hop_size
+    }
+
+    fn reset(&mut self) {
+        // Prime with silence so the search window can reach behind the
+        // first grain and the first real sample is fully overlapped
+        for channel in self.channels.iter_mut() {
+            channel.input.clear();
+            channel.input.resize(self.search_radius + self.hop_size, 0.0);
+            channel.overlap.fill(0.0);
+            channel.output.clear();
+        }
+        self.nominal_pos = self.search_radius as f64;
+        self.continuation = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wsola_creation() {
+        let wsola = Wsola::new(48000);
+        assert_eq!(wsola.get_stretch_ratio(), 1.0);
+        assert_eq!(wsola.frame_size(), 960);
+        assert_eq!(wsola.latency(), 480);
+    }
+
+    #[test]
+    fn test_unity_ratio_is_delayed_input() {
+        let mut wsola = Wsola::new(48000);
+        let input: Vec<f32> = (0..9600).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let mut output = Vec::new();
+        let mut left = [0.0; 128];
+        let mut right = [0.0; 128];
+        let mut consumed = 0;
+
+        for _ in 0..60 {
+            let needed = wsola.input_required(128);
+            wsola.push_input(&input[consumed..consumed + needed], &input[consumed..consumed + needed]);
+            consumed += needed;
+            wsola.pull_output(&mut left, &mut right);
+            output.extend_from_slice(&left);
+        }
+
+        let latency = wsola.latency();
+        for i in 2000..7000 {
+            assert!((output[i] - input[i - latency]).abs() < 1e-4);
+        }
+    }
+}